@@ -0,0 +1,210 @@
+//! Compile time counterpart of the selector grammar accepted by `unselector`.
+
+use std::fmt;
+use std::ops::Range;
+
+/// A requirement parsed from selector syntax
+#[derive(Debug, PartialEq, Eq)]
+pub enum Requirement {
+    In(String, Vec<String>),
+    NotIn(String, Vec<String>),
+    Equal(String, String),
+    NotEqual(String, String),
+    Exists(String),
+    DoesNotExist(String),
+    GreaterThan(String, String),
+    LessThan(String, String),
+}
+
+/// Offending text and its byte range in the selector
+#[derive(Debug, PartialEq, Eq)]
+pub struct Error {
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to parse value as expression: '{}' at {:?}",
+            self.text, self.span
+        )
+    }
+}
+
+fn is_value_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_')
+}
+
+fn is_key_char(c: char) -> bool {
+    is_value_char(c) || c == '/'
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\x0c')
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn error(&self, span: Range<usize>) -> Error {
+        Error {
+            text: self.input[span.clone()].to_owned(),
+            span,
+        }
+    }
+
+    /// Error for the single character at the current position
+    fn unexpected(&self) -> Error {
+        let len = self.peek().map_or(0, char::len_utf8);
+        self.error(self.pos..self.pos + len)
+    }
+
+    fn requirement(&mut self) -> Result<Requirement, Error> {
+        let start = self.pos;
+        if self.peek() == Some('!') {
+            self.pos += 1;
+            let key = self.take_while(is_key_char);
+            if key.is_empty() {
+                return Err(self.error(start..self.pos));
+            }
+            return Ok(Requirement::DoesNotExist(key.to_owned()));
+        }
+
+        let key = self.take_while(is_key_char).to_owned();
+        if key.is_empty() {
+            return Err(self.unexpected());
+        }
+
+        let after_key = self.pos;
+        let spaced = !self.take_while(is_space).is_empty();
+        let rest = self.rest();
+        let operator = ["==", "!=", "=", ">", "<"]
+            .into_iter()
+            .find(|op| rest.starts_with(op));
+        if let Some(operator) = operator {
+            self.pos += operator.len();
+            self.take_while(is_space);
+            let value = self.take_while(is_value_char).to_owned();
+            let integer = value.parse::<i64>().is_ok();
+            if matches!(operator, ">" | "<") && !integer {
+                return Err(self.error(start..self.pos));
+            }
+            return Ok(match operator {
+                "!=" => Requirement::NotEqual(key, value),
+                ">" => Requirement::GreaterThan(key, value),
+                "<" => Requirement::LessThan(key, value),
+                _ => Requirement::Equal(key, value),
+            });
+        }
+
+        let keyword = self.take_while(|c| c.is_ascii_alphabetic());
+        let set = matches!(keyword, "in" | "notin")
+            && spaced
+            && !self.take_while(is_space).is_empty()
+            && self.peek() == Some('(');
+        if !set {
+            self.pos = after_key;
+            return Ok(Requirement::Exists(key));
+        }
+
+        self.pos += 1;
+        let mut values = vec![];
+        let mut empty = true;
+        loop {
+            empty &= self.take_while(|c| is_space(c) || c == ',').is_empty();
+            match self.peek() {
+                Some(')') if !(empty && values.is_empty()) => break,
+                Some(c) if is_value_char(c) => {
+                    values.push(self.take_while(is_value_char).to_owned());
+                }
+                _ => return Err(self.error(start..self.pos)),
+            }
+        }
+        self.pos += 1;
+
+        Ok(match keyword {
+            "in" => Requirement::In(key, values),
+            _ => Requirement::NotIn(key, values),
+        })
+    }
+}
+
+/// Parse a selector into requirements, failing on the first invalid one
+pub fn parse(input: &str) -> Result<Vec<Requirement>, Error> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut requirements = vec![];
+    loop {
+        parser.take_while(|c| is_space(c) || c == ',');
+        if parser.rest().is_empty() {
+            return Ok(requirements);
+        }
+        requirements.push(parser.requirement()?);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Requirement, parse};
+
+    #[test]
+    fn requirements() {
+        assert_eq!(
+            parse("a==b,,foo.bar/b-y_.6 = c_8.-z,c!=d,a in (a,b, c), a notin (a), c,!a,b>1,c < -2"),
+            Ok(vec![
+                Requirement::Equal("a".into(), "b".into()),
+                Requirement::Equal("foo.bar/b-y_.6".into(), "c_8.-z".into()),
+                Requirement::NotEqual("c".into(), "d".into()),
+                Requirement::In("a".into(), vec!["a".into(), "b".into(), "c".into()]),
+                Requirement::NotIn("a".into(), vec!["a".into()]),
+                Requirement::Exists("c".into()),
+                Requirement::DoesNotExist("a".into()),
+                Requirement::GreaterThan("b".into(), "1".into()),
+                Requirement::LessThan("c".into(), "-2".into()),
+            ])
+        );
+        assert_eq!(parse(""), Ok(vec![]));
+        assert_eq!(
+            parse("a=,b !="),
+            Ok(vec![
+                Requirement::Equal("a".into(), "".into()),
+                Requirement::NotEqual("b".into(), "".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str, span| {
+            Err(Error {
+                text: text.into(),
+                span,
+            })
+        };
+        assert_eq!(parse("a,b()"), error("(", 3..4));
+        assert_eq!(parse("a<"), error("a<", 0..2));
+        assert_eq!(parse("a in (b"), error("a in (b", 0..7));
+        assert_eq!(parse("a in ()"), error("a in (", 0..6));
+        assert_eq!(parse("!"), error("!", 0..1));
+        assert_eq!(parse("a>b"), error("a>b", 0..3));
+    }
+}
@@ -0,0 +1,92 @@
+//! Procedural macros for `unselector`, re-exported by its `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{LitStr, parse_macro_input};
+
+mod grammar;
+
+use grammar::Requirement;
+
+/// Parse a selector literal at compile time into `unselector::Expressions`.
+///
+/// Invalid selectors fail the build, pointing at the offending part of the
+/// literal where the compiler allows it.
+#[proc_macro]
+pub fn selector_str(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+    match grammar::parse(&literal.value()) {
+        Ok(requirements) => {
+            let requirements = requirements.iter().map(expand);
+            quote! {
+                <::unselector::Expressions as ::std::iter::FromIterator<::unselector::Expression>>::from_iter([
+                    #(#requirements),*
+                ])
+            }
+            .into()
+        }
+        Err(error) => syn::Error::new(span(&literal, error.span.clone()), error)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/// Span of a byte range within the literal value, or of the whole literal if
+/// the compiler can't narrow it or the literal contains escapes
+fn span(literal: &LitStr, range: std::ops::Range<usize>) -> Span {
+    let token = literal.token();
+    let source = token.to_string();
+    let value = literal.value();
+    let start = source.find('"').map_or(0, |quote| quote + 1);
+    if source.get(start..start + value.len()) != Some(value.as_str()) {
+        return literal.span();
+    }
+
+    token
+        .subspan(start + range.start..start + range.end)
+        .unwrap_or_else(|| literal.span())
+}
+
+fn expand(requirement: &Requirement) -> TokenStream2 {
+    let string = |value: &String| quote!(::std::string::String::from(#value));
+    let set = |values: &Vec<String>| {
+        let values = values.iter().map(string);
+        quote!(::std::collections::BTreeSet::from([#(#values),*]))
+    };
+
+    match requirement {
+        Requirement::In(key, values) => {
+            let (key, values) = (string(key), set(values));
+            quote!(::unselector::Expression::In(#key, #values))
+        }
+        Requirement::NotIn(key, values) => {
+            let (key, values) = (string(key), set(values));
+            quote!(::unselector::Expression::NotIn(#key, #values))
+        }
+        Requirement::Equal(key, value) => {
+            let (key, value) = (string(key), string(value));
+            quote!(::unselector::Expression::Equal(#key, #value))
+        }
+        Requirement::NotEqual(key, value) => {
+            let (key, value) = (string(key), string(value));
+            quote!(::unselector::Expression::NotEqual(#key, #value))
+        }
+        Requirement::GreaterThan(key, value) => {
+            let (key, value) = (string(key), string(value));
+            quote!(::unselector::Expression::GreaterThan(#key, #value))
+        }
+        Requirement::LessThan(key, value) => {
+            let (key, value) = (string(key), string(value));
+            quote!(::unselector::Expression::LessThan(#key, #value))
+        }
+        Requirement::Exists(key) => {
+            let key = string(key);
+            quote!(::unselector::Expression::Exists(#key))
+        }
+        Requirement::DoesNotExist(key) => {
+            let key = string(key);
+            quote!(::unselector::Expression::DoesNotExist(#key))
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use logos::Span;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Expressions;
+
+/// A reason a selector was rejected, shaped after a Kubernetes `StatusCause`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AdmissionFailure {
+    /// Path of the field holding the selector, such as `spec.selector`
+    pub field: String,
+
+    /// Description of the failure, which may be shown to users as is
+    pub message: String,
+
+    /// Offending part of the selector
+    pub span: Span,
+}
+
+impl AdmissionFailure {
+    /// Machine readable reason, as used by the API server for invalid fields
+    pub fn reason(&self) -> &'static str {
+        "FieldValueInvalid"
+    }
+}
+
+/// Validate a selector found in the field of an admitted object, such as a
+/// selector stored as a string in a custom resource
+pub fn validate_for_admission(
+    selector: &str,
+    field_path: &str,
+) -> Result<Expressions, Vec<AdmissionFailure>> {
+    Expressions::try_from(selector).map_err(|error| {
+        let (text, span) = (error.text(), error.span());
+        let message = match text.is_empty() {
+            true => format!("invalid selector '{selector}': unexpected end of input"),
+            false => format!(
+                "invalid selector '{selector}': unexpected '{text}' at position {}",
+                span.start
+            ),
+        };
+        vec![AdmissionFailure {
+            field: field_path.to_owned(),
+            message,
+            span,
+        }]
+    })
+}
+
+#[cfg(feature = "kube-rs")]
+impl From<AdmissionFailure> for kube::core::response::StatusCause {
+    fn from(failure: AdmissionFailure) -> Self {
+        Self {
+            reason: failure.reason().to_owned(),
+            message: failure.message,
+            field: failure.field,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AdmissionFailure, Expressions, validate_for_admission};
+
+    #[test]
+    fn validate() {
+        assert_eq!(
+            validate_for_admission("app=web,!canary", "spec.selector"),
+            Ok(Expressions::try_from("app=web,!canary").unwrap())
+        );
+        assert_eq!(
+            validate_for_admission("app=web,tier in db", "spec.selector"),
+            Err(vec![AdmissionFailure {
+                field: "spec.selector".into(),
+                message: "invalid selector 'app=web,tier in db': unexpected 'in' at position 13"
+                    .into(),
+                span: 13..15,
+            }])
+        );
+    }
+
+    #[cfg(feature = "kube-rs")]
+    #[test]
+    fn status_cause() {
+        use kube::core::response::StatusCause;
+
+        let failures = validate_for_admission("a=(", "spec.targets[0].selector").unwrap_err();
+        let cause = StatusCause::from(failures[0].clone());
+        assert_eq!(cause.reason, "FieldValueInvalid");
+        assert_eq!(cause.field, "spec.targets[0].selector");
+        assert_eq!(cause.message, failures[0].message);
+    }
+}
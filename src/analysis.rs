@@ -0,0 +1,453 @@
+use std::collections::BTreeMap;
+
+use crate::requirements::{self, KeyRequirements};
+use crate::{Expression, Expressions};
+
+impl Expression {
+    /// Alternatives matching the label sets this requirement doesn't match,
+    /// except for non-integer values of compared keys and for values not
+    /// matching a wildcard pattern
+    pub(crate) fn negation(&self) -> Vec<Expressions> {
+        let key = self.key();
+        let absent = || Expressions::from(Expression::does_not_exist(key));
+        let present = |expression| Expression::exists(key).and(expression);
+        match self {
+            Expression::In(_, values) => vec![
+                absent(),
+                present(Expression::NotIn(key.into(), values.clone())),
+            ],
+            Expression::NotIn(_, values) => vec![Expression::In(key.into(), values.clone()).into()],
+            Expression::Equal(_, value) => {
+                vec![absent(), present(Expression::not_equal(key, value))]
+            }
+            Expression::NotEqual(_, value) => vec![Expression::equal(key, value).into()],
+            Expression::Exists(_) => vec![absent()],
+            Expression::DoesNotExist(_) => vec![Expression::exists(key).into()],
+            // Bounds which no integer satisfies never match
+            Expression::GreaterThan(_, bound) => {
+                match bound
+                    .parse()
+                    .ok()
+                    .and_then(|bound: i64| bound.checked_add(1))
+                {
+                    Some(bound) => vec![absent(), Expression::less_than(key, bound).into()],
+                    None => vec![Expressions::default()],
+                }
+            }
+            Expression::LessThan(_, bound) => {
+                match bound
+                    .parse()
+                    .ok()
+                    .and_then(|bound: i64| bound.checked_sub(1))
+                {
+                    Some(bound) => vec![absent(), Expression::greater_than(key, bound).into()],
+                    None => vec![Expressions::default()],
+                }
+            }
+            #[cfg(feature = "regex")]
+            Expression::Matches(_, pattern) => vec![
+                absent(),
+                present(Expression::not_matching(key, pattern.clone())),
+            ],
+            #[cfg(feature = "regex")]
+            Expression::NotMatches(_, pattern) => {
+                vec![Expression::matching(key, pattern.clone()).into()]
+            }
+            #[cfg(feature = "extended")]
+            Expression::Glob(..) => vec![absent()],
+        }
+    }
+}
+
+impl Expressions {
+    /// Check whether both selectors match exactly the same label sets, such
+    /// as `a=b,c` and `c,a in (b)`.
+    ///
+    /// Patterns are compared by their source, so selectors whose patterns
+    /// are written differently are not considered equivalent.
+    pub fn equivalent(&self, other: &Expressions) -> bool {
+        self.subsumes(other) && other.subsumes(self)
+    }
+
+    /// Equivalent expressions with the fewest requirements, in canonical
+    /// order.
+    ///
+    /// Requirements on the same key are folded together, so `a=b,a in (b,c)`
+    /// becomes `a=b`, and requirements implied by others are dropped.
+    /// Selectors which can't match anything are only sorted and deduplicated.
+    pub fn normalize(&self) -> Expressions {
+        if !self.is_satisfiable() {
+            return self.to_canonical();
+        }
+        requirements::by_key(self.iter())
+            .iter()
+            .flat_map(|(key, requirements)| requirements.minimize(key))
+            .collect::<Expressions>()
+            .to_canonical()
+    }
+
+    /// Hash of the [normalized](Self::normalize) selector, so equivalent
+    /// selectors written differently share a fingerprint.
+    ///
+    /// The hash is 64 bit FNV-1a over the displayed normalized selector, and
+    /// stays the same across releases, so it can identify selectors across
+    /// processes and in persisted caches.
+    pub fn fingerprint(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        self.normalize()
+            .to_string()
+            .bytes()
+            .fold(OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+            })
+    }
+
+    /// Check whether some label set can match the expressions, rejecting
+    /// contradictions like `a=b,a=c` or `a in (x),a notin (x)`.
+    ///
+    /// Patterns are assumed to match some value.
+    pub fn is_satisfiable(&self) -> bool {
+        requirements::by_key(self.iter())
+            .values()
+            .all(KeyRequirements::satisfiable)
+    }
+
+    /// Check whether some label set could match both selectors, such as two
+    /// controllers claiming the same objects.
+    ///
+    /// Patterns are assumed to match some value, as with
+    /// [`is_satisfiable`](Self::is_satisfiable).
+    pub fn overlaps(&self, other: &Expressions) -> bool {
+        requirements::by_key(self.iter().chain(other))
+            .values()
+            .all(KeyRequirements::satisfiable)
+    }
+
+    /// Labels matched by the expressions, setting only the keys they
+    /// require, or `None` if nothing can match.
+    ///
+    /// Values for patterns are guessed from their sources, so this may also
+    /// be `None` for some selectors with patterns.
+    pub fn example_match(&self) -> Option<BTreeMap<String, String>> {
+        if !self.is_satisfiable() {
+            return None;
+        }
+        let mut labels = BTreeMap::new();
+        for (key, requirements) in requirements::by_key(self.iter()) {
+            if requirements.exists {
+                labels.insert(key.to_owned(), requirements.example()?);
+            }
+        }
+        Some(labels)
+    }
+
+    /// Labels satisfying every requirement but one, for each requirement
+    /// which can fail on its own, paired with the requirement they fail.
+    ///
+    /// Requirements implied by the others, such as duplicates, have no
+    /// counterexample and are left out.
+    pub fn counterexamples(&self) -> Vec<(&Expression, BTreeMap<String, String>)> {
+        let expressions: Vec<_> = self.iter().collect();
+        let mut counterexamples = vec![];
+        for (i, expression) in expressions.iter().enumerate() {
+            let others: Expressions = (expressions.iter().enumerate())
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other)| (*other).clone())
+                .collect();
+            let labels = (expression.negation().into_iter())
+                .find_map(|negation| others.clone().and(negation).example_match());
+            if let Some(labels) = labels {
+                counterexamples.push((*expression, labels));
+            }
+        }
+        counterexamples
+    }
+
+    /// Pairs of requirements which contradict each other, in order.
+    ///
+    /// Contradictions involving more than two requirements, such as
+    /// `a in (x,y),a!=x,a!=y`, are not reported.
+    pub fn conflicts(&self) -> Vec<(&Expression, &Expression)> {
+        let expressions: Vec<_> = self.iter().collect();
+        let mut conflicts = vec![];
+        for (i, first) in expressions.iter().enumerate() {
+            for second in &expressions[i + 1..] {
+                if first.key() == second.key()
+                    && !requirements::by_key([*first, *second])
+                        .values()
+                        .all(KeyRequirements::satisfiable)
+                {
+                    conflicts.push((*first, *second));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Check whether every label set matched by the other expressions is also
+    /// matched by these, such as `env in (prod,stage)` subsuming
+    /// `env=prod,team=a`.
+    ///
+    /// Patterns are compared by their source, so this may answer `false` for
+    /// differently written patterns which accept the same values.
+    pub fn subsumes(&self, other: &Expressions) -> bool {
+        let (keys, other_keys) = (
+            requirements::by_key(self.iter()),
+            requirements::by_key(other.iter()),
+        );
+        if other_keys.values().any(|key| !key.satisfiable()) {
+            return true;
+        }
+        if keys.values().any(|key| !key.satisfiable()) {
+            return false;
+        }
+        let unrestricted = KeyRequirements::default();
+        keys.iter().all(|(key, requirements)| {
+            requirements.includes(other_keys.get(key).unwrap_or(&unrestricted))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, Expressions};
+
+    fn equivalent(a: &str, b: &str) -> bool {
+        let (a, b) = (
+            Expressions::try_from(a).unwrap(),
+            Expressions::try_from(b).unwrap(),
+        );
+        a.equivalent(&b)
+    }
+
+    #[test]
+    fn equivalent_selectors() {
+        for (a, b) in [
+            ("a=b", "a in (b)"),
+            ("a=b,c", "c,a==b,a=b"),
+            ("a in (x,y),a!=y", "a=x"),
+            ("a>1,a>2", "a>2"),
+            ("a,a=b", "a=b"),
+            ("a notin (x),a!=y", "a notin (y,x)"),
+            ("a>5,a!=3", "a>5"),
+            ("a=b,a=c", "x,!x"),
+            ("", ""),
+        ] {
+            assert!(equivalent(a, b), "{a} and {b}");
+        }
+    }
+
+    #[test]
+    fn different_selectors() {
+        for (a, b) in [
+            ("a!=b", "a notin (b,c)"),
+            ("a>1", "a>2"),
+            ("a>1", "a"),
+            ("a", "a!=b"),
+            ("a=b", ""),
+            ("a!=b", "a,a!=b"),
+            ("a in (x,y)", "a=x"),
+        ] {
+            assert!(!equivalent(a, b), "{a} and {b}");
+        }
+    }
+
+    #[test]
+    fn normalize() {
+        for (selector, normalized) in [
+            ("a=b,a in (b,c),a=b", "a=b"),
+            ("a in (x,y,z),a notin (y)", "a in (x,z)"),
+            ("a,a>1,a>2,a!=1,a!=5", "a!=5,a>2"),
+            ("b,a!=x,b,a", "a!=x,a,b"),
+            ("a notin (x),a!=y", "a notin (x,y)"),
+            ("a notin (x),!a", "!a"),
+            ("a=b,a=c,a=b", "a=b,a=c"),
+        ] {
+            let expressions = Expressions::try_from(selector).unwrap();
+            assert_eq!(
+                expressions.normalize().to_string(),
+                normalized,
+                "{selector}"
+            );
+            assert!(
+                expressions.normalize().equivalent(&expressions),
+                "{selector}"
+            );
+        }
+    }
+
+    #[test]
+    fn fingerprint() {
+        let fingerprint = |selector| Expressions::try_from(selector).unwrap().fingerprint();
+        assert_eq!(fingerprint(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint("a=b"), 0xe650_9319_0491_a193);
+        assert_eq!(fingerprint("b,a in (b,c),a=b"), fingerprint("a=b,b"));
+        assert_ne!(fingerprint("a=b"), fingerprint("a=c"));
+        assert_ne!(fingerprint("a"), fingerprint("!a"));
+    }
+
+    #[test]
+    fn satisfiable() {
+        for selector in ["", "a=b,a in (b,c)", "a,a!=b", "a>1,a<3", "!a,a notin (b)"] {
+            let expressions = Expressions::try_from(selector).unwrap();
+            assert!(expressions.is_satisfiable(), "{selector}");
+            assert!(expressions.conflicts().is_empty(), "{selector}");
+        }
+
+        let expressions = Expressions::try_from("a=b,c,a=d,!c,a in (b)").unwrap();
+        assert!(!expressions.is_satisfiable());
+        assert_eq!(
+            expressions.conflicts(),
+            [
+                (&Expression::equal("a", "b"), &Expression::equal("a", "d")),
+                (&Expression::exists("c"), &Expression::does_not_exist("c")),
+                (&Expression::equal("a", "d"), &Expression::r#in("a", ["b"])),
+            ]
+        );
+
+        let expressions = Expressions::try_from("a in (x,y),a!=x,a!=y,b>2,b<3").unwrap();
+        assert!(!expressions.is_satisfiable());
+        assert_eq!(
+            expressions.conflicts(),
+            [(
+                &Expression::greater_than("b", 2),
+                &Expression::less_than("b", 3)
+            )]
+        );
+    }
+
+    #[test]
+    fn subsumes() {
+        let parse = |s| Expressions::try_from(s).unwrap();
+        for (broad, narrow) in [
+            ("env in (prod,stage)", "env=prod,team=a"),
+            ("", "a=b"),
+            ("a", "a>1"),
+            ("a>1", "a>2,a<5"),
+            ("a!=x", "a in (y,z)"),
+            ("a notin (x,y)", "!a"),
+            ("a!=x", "a>1,a notin (x)"),
+            ("a,b", "a,b,c"),
+            ("a=b", "a=b,a=c"),
+        ] {
+            assert!(
+                parse(broad).subsumes(&parse(narrow)),
+                "{broad} over {narrow}"
+            );
+        }
+        for (broad, narrow) in [
+            ("env=prod,team=a", "env in (prod,stage)"),
+            ("a=b", ""),
+            ("a>1", "a"),
+            ("a!=x", "a"),
+            ("a", "a notin (x)"),
+            ("a=b,a=c", "a=b"),
+        ] {
+            assert!(
+                !parse(broad).subsumes(&parse(narrow)),
+                "{broad} over {narrow}"
+            );
+        }
+    }
+
+    #[test]
+    fn overlaps() {
+        let parse = |s| Expressions::try_from(s).unwrap();
+        for (a, b) in [
+            ("app=web", "tier=db"),
+            ("env in (a,b)", "env in (b,c)"),
+            ("a>1", "a<3"),
+            ("a!=x", "!a"),
+            ("", ""),
+        ] {
+            assert!(parse(a).overlaps(&parse(b)), "{a} and {b}");
+            assert!(parse(b).overlaps(&parse(a)), "{b} and {a}");
+        }
+        for (a, b) in [
+            ("app=web", "app=api"),
+            ("env in (a,b)", "env notin (a,b)"),
+            ("a>2", "a<3"),
+            ("a", "!a"),
+            ("a=b,a=c", ""),
+        ] {
+            assert!(!parse(a).overlaps(&parse(b)), "{a} and {b}");
+            assert!(!parse(b).overlaps(&parse(a)), "{b} and {a}");
+        }
+    }
+
+    #[test]
+    fn example_match() {
+        for selector in [
+            "",
+            "app=web,tier in (db,api),!canary,team notin (a)",
+            "a,a notin (value,value-1)",
+            "a>1,a<3,a!=2",
+            "a>-3,a!=-2,a!=-1",
+            "a>9223372036854775806",
+        ] {
+            let expressions = Expressions::try_from(selector).unwrap();
+            let labels = expressions.example_match().unwrap();
+            assert!(expressions.matches(&labels), "{selector}: {labels:?}");
+        }
+
+        let labels = Expressions::try_from("app=web,!canary,team!=a")
+            .unwrap()
+            .example_match();
+        assert_eq!(labels, Some([("app".into(), "web".into())].into()));
+        assert_eq!(
+            Expressions::try_from("a=b,a=c").unwrap().example_match(),
+            None
+        );
+    }
+
+    #[test]
+    fn counterexamples() {
+        let expressions =
+            Expressions::try_from("app=web,tier in (db,api),!canary,n>1,team notin (a)").unwrap();
+        let counterexamples = expressions.counterexamples();
+        assert_eq!(counterexamples.len(), expressions.len());
+        for (expression, labels) in &counterexamples {
+            assert!(!expression.matches(labels), "{expression}: {labels:?}");
+            for other in expressions.iter().filter(|other| other != expression) {
+                assert!(other.matches(labels), "{other}: {labels:?}");
+            }
+        }
+        assert_eq!(
+            counterexamples[0].1,
+            [("n".into(), "2".into()), ("tier".into(), "api".into())].into()
+        );
+
+        let expressions = Expressions::try_from("a=b,a in (b,c),d").unwrap();
+        assert_eq!(
+            expressions
+                .counterexamples()
+                .into_iter()
+                .map(|(expression, _)| expression.to_string())
+                .collect::<Vec<_>>(),
+            ["a=b", "d"]
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn patterns() {
+        use crate::Dialect;
+
+        let parse = |s| Expressions::parse_with(Dialect::Extended, s).unwrap();
+        assert!(parse("a=~\"x.*\",b").equivalent(&parse("b,a=~\"x.*\",a=~\"x.*\"")));
+        assert!(!parse("a=~\"x.*\"").equivalent(&parse("a=~\"x.+\"")));
+        assert!(parse("a=~\"x.*\",a in (xy,z)").equivalent(&parse("a=xy")));
+        assert_eq!(
+            parse("a=~\"x.*\",a,a=~\"x.*\",a!~\"y\"").normalize(),
+            parse("a=~\"x.*\",a!~\"y\"")
+        );
+
+        for selector in ["a=~\"^prod$\"", "a=~\"x.*\",a!=y", "a=~\"^[0-9]+$\",a>2"] {
+            let expressions = parse(selector);
+            let labels = expressions.example_match().unwrap();
+            assert!(expressions.matches(&labels), "{selector}: {labels:?}");
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "kube-runtime")]
+use std::{hash::Hash, sync::Arc};
+
+use kube::Resource;
+use kube::api::ListParams;
+#[cfg(feature = "kube-runtime")]
+use kube::runtime::{reflector::Store, watcher};
+
+use crate::{ConversionError, Expressions};
+
+impl Expressions {
+    /// List parameters selecting by these requirements
+    pub fn to_list_params(&self) -> Result<ListParams, ConversionError> {
+        let mut params = ListParams::default();
+        self.apply_to(&mut params)?;
+        Ok(params)
+    }
+
+    /// Set the label selector of the list parameters to the canonical form of
+    /// these requirements, see [`Expressions::to_canonical`].
+    ///
+    /// The parameters are left untouched if the requirements can't be sent to
+    /// the API server.
+    pub fn apply_to(&self, params: &mut ListParams) -> Result<(), ConversionError> {
+        params.label_selector = Some(self.to_canonical().to_selector()?.to_string());
+        Ok(())
+    }
+
+    /// Check whether the labels of the object match, where objects without
+    /// labels are treated as having none
+    pub fn matches_resource<K: Resource>(&self, obj: &K) -> bool {
+        match &obj.meta().labels {
+            Some(labels) => self.matches(labels),
+            None => self.matches(&BTreeMap::<String, String>::new()),
+        }
+    }
+
+    /// Cached objects of the store whose labels match
+    #[cfg(feature = "kube-runtime")]
+    pub fn filter_store<K>(&self, store: &Store<K>) -> Vec<Arc<K>>
+    where
+        K: Resource + Clone + 'static,
+        K::DynamicType: Clone + Hash + Eq,
+    {
+        store.state_filter(|obj| self.matches_resource(obj))
+    }
+
+    /// Iterate over a snapshot of the cached objects whose labels match
+    #[cfg(feature = "kube-runtime")]
+    pub fn filter_store_iter<'a, K>(&'a self, store: &Store<K>) -> impl Iterator<Item = Arc<K>> + 'a
+    where
+        K: Resource + Clone + 'static,
+        K::DynamicType: Clone + Hash + Eq,
+    {
+        store
+            .state()
+            .into_iter()
+            .filter(|obj| self.matches_resource(obj.as_ref()))
+    }
+
+    /// Watcher configuration selecting by these requirements
+    #[cfg(feature = "kube-runtime")]
+    pub fn to_watcher_config(&self) -> Result<watcher::Config, ConversionError> {
+        Ok(watcher::Config::default().labels_from(&self.to_selector()?))
+    }
+
+    /// Add these requirements to the label selector of the watcher
+    /// configuration, keeping the requirements it already has
+    #[cfg(feature = "kube-runtime")]
+    pub fn merge_into(&self, config: &mut watcher::Config) -> Result<(), ConversionError> {
+        let selector = self.to_selector()?.to_string();
+        let existing = config.label_selector.as_deref().filter(|s| !s.is_empty());
+        config.label_selector = match (existing, selector.is_empty()) {
+            (Some(_), true) => return Ok(()),
+            (Some(existing), false) => Some(format!("{existing},{selector}")),
+            (None, _) => Some(selector),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kube::api::ListParams;
+
+    use crate::{ConversionError, Expression, Expressions};
+
+    #[test]
+    fn list_params() {
+        let expressions = Expressions::try_from("app=web, env in (b,a),!canary").unwrap();
+        assert_eq!(
+            expressions
+                .to_list_params()
+                .unwrap()
+                .label_selector
+                .as_deref(),
+            Some("app=web,!canary,env in (a,b)")
+        );
+
+        let mut params = ListParams::default().limit(10);
+        expressions.apply_to(&mut params).unwrap();
+        assert_eq!(params.limit, Some(10));
+        assert_eq!(
+            params.label_selector.as_deref(),
+            Some("app=web,!canary,env in (a,b)")
+        );
+        assert_eq!(
+            Expressions::try_from("!canary,env in (a,b),app=web,app=web")
+                .unwrap()
+                .to_list_params()
+                .unwrap()
+                .label_selector,
+            params.label_selector
+        );
+
+        let mut params = ListParams::default().labels("a");
+        assert_eq!(
+            Expressions::from(Expression::less_than("b", 1)).apply_to(&mut params),
+            Err(ConversionError::Unsupported(
+                Expression::less_than("b", 1).into()
+            ))
+        );
+        assert_eq!(params.label_selector.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn matches_resource() {
+        use k8s_openapi::api::core::v1::Pod;
+        use kube::api::{ApiResource, DynamicObject, GroupVersionKind};
+
+        let web = Expressions::try_from("app=web,!canary").unwrap();
+        let absent = Expressions::try_from("!app").unwrap();
+
+        let mut pod = Pod::default();
+        assert!(!web.matches_resource(&pod));
+        assert!(absent.matches_resource(&pod));
+
+        pod.metadata.labels = Some([("app".into(), "web".into())].into());
+        assert!(web.matches_resource(&pod));
+        assert!(!absent.matches_resource(&pod));
+
+        let resource = ApiResource::from_gvk(&GroupVersionKind::gvk("", "v1", "ConfigMap"));
+        let mut object = DynamicObject::new("config", &resource);
+        object.metadata.labels = Some([("app".into(), "web".into())].into());
+        assert!(web.matches_resource(&object));
+    }
+
+    #[cfg(feature = "kube-runtime")]
+    #[test]
+    fn watcher_config() {
+        use kube::runtime::watcher;
+
+        let expressions = Expressions::try_from("app=web,!canary").unwrap();
+        assert_eq!(
+            expressions
+                .to_watcher_config()
+                .unwrap()
+                .label_selector
+                .as_deref(),
+            Some("app=web,!canary")
+        );
+
+        let mut config = watcher::Config::default().labels("team").timeout(30);
+        expressions.merge_into(&mut config).unwrap();
+        assert_eq!(
+            config.label_selector.as_deref(),
+            Some("team,app=web,!canary")
+        );
+        assert_eq!(config.timeout, Some(30));
+
+        Expressions::default().merge_into(&mut config).unwrap();
+        assert_eq!(
+            config.label_selector.as_deref(),
+            Some("team,app=web,!canary")
+        );
+
+        let mut config = watcher::Config::default();
+        expressions.merge_into(&mut config).unwrap();
+        assert_eq!(config.label_selector.as_deref(), Some("app=web,!canary"));
+    }
+
+    #[cfg(feature = "kube-runtime")]
+    #[test]
+    fn filter_store() {
+        use k8s_openapi::api::core::v1::Pod;
+        use kube::runtime::{reflector, watcher::Event};
+
+        let (store, mut writer) = reflector::store::<Pod>();
+        for (name, app) in [("a", Some("web")), ("b", Some("db")), ("c", None)] {
+            let mut pod = Pod::default();
+            pod.metadata.name = Some(name.into());
+            pod.metadata.namespace = Some("default".into());
+            pod.metadata.labels = app.map(|app| [("app".into(), app.into())].into());
+            writer.apply_watcher_event(&Event::Apply(pod));
+        }
+
+        let names = |pods: Vec<std::sync::Arc<Pod>>| {
+            let mut names: Vec<_> = pods
+                .iter()
+                .filter_map(|p| p.metadata.name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+        let web = Expressions::try_from("app=web").unwrap();
+        assert_eq!(names(web.filter_store(&store)), ["a"]);
+        assert_eq!(names(web.filter_store_iter(&store).collect()), ["a"]);
+
+        let not_db = Expressions::try_from("app!=db").unwrap();
+        assert_eq!(names(not_db.filter_store(&store)), ["a", "c"]);
+    }
+}
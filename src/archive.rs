@@ -0,0 +1,143 @@
+#[cfg(any(feature = "regex", feature = "extended"))]
+use rkyv::{
+    Place, SerializeUnsized,
+    rancor::{Fallible, Source},
+    string::{ArchivedString, StringResolver},
+    with::{ArchiveWith, DeserializeWith, SerializeWith},
+};
+
+use crate::{ArchivedExpression, ArchivedExpressions, ArchivedParsedExpression};
+
+impl ArchivedExpressions {
+    /// Number of archived expressions
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Indicates whether there are no archived expressions
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over archived expressions in order, without deserializing them
+    pub fn iter(&self) -> impl Iterator<Item = &ArchivedExpression> {
+        self.0
+            .iter()
+            .map(|ArchivedParsedExpression::Expression(expression)| expression)
+    }
+}
+
+impl ArchivedExpression {
+    /// Key the archived expression applies to
+    pub fn key(&self) -> &str {
+        match self {
+            ArchivedExpression::In(key, _)
+            | ArchivedExpression::NotIn(key, _)
+            | ArchivedExpression::Equal(key, _)
+            | ArchivedExpression::NotEqual(key, _)
+            | ArchivedExpression::Exists(key)
+            | ArchivedExpression::DoesNotExist(key)
+            | ArchivedExpression::GreaterThan(key, _)
+            | ArchivedExpression::LessThan(key, _) => key,
+            #[cfg(feature = "regex")]
+            ArchivedExpression::Matches(key, _) | ArchivedExpression::NotMatches(key, _) => key,
+            #[cfg(feature = "extended")]
+            ArchivedExpression::Glob(key, _) => key,
+        }
+    }
+}
+
+/// Archives patterns as their source, compiling them again when deserialized
+#[cfg(any(feature = "regex", feature = "extended"))]
+pub struct AsSource;
+
+#[cfg(any(feature = "regex", feature = "extended"))]
+macro_rules! as_source {
+    ($pattern:ty) => {
+        impl ArchiveWith<$pattern> for AsSource {
+            type Archived = ArchivedString;
+            type Resolver = StringResolver;
+
+            fn resolve_with(
+                field: &$pattern,
+                resolver: Self::Resolver,
+                out: Place<Self::Archived>,
+            ) {
+                ArchivedString::resolve_from_str(field.as_str(), resolver, out);
+            }
+        }
+
+        impl<S> SerializeWith<$pattern, S> for AsSource
+        where
+            S: Fallible + ?Sized,
+            S::Error: Source,
+            str: SerializeUnsized<S>,
+        {
+            fn serialize_with(
+                field: &$pattern,
+                serializer: &mut S,
+            ) -> Result<Self::Resolver, S::Error> {
+                ArchivedString::serialize_from_str(field.as_str(), serializer)
+            }
+        }
+
+        impl<D> DeserializeWith<ArchivedString, $pattern, D> for AsSource
+        where
+            D: Fallible + ?Sized,
+            D::Error: Source,
+        {
+            fn deserialize_with(field: &ArchivedString, _: &mut D) -> Result<$pattern, D::Error> {
+                <$pattern>::new(field.as_str()).map_err(D::Error::new)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "regex")]
+as_source!(crate::Pattern);
+#[cfg(feature = "extended")]
+as_source!(crate::GlobPattern);
+
+#[cfg(test)]
+mod tests {
+    use rkyv::rancor::Error;
+
+    use crate::{ArchivedExpression, ArchivedExpressions, Expressions};
+
+    #[test]
+    fn round_trip() {
+        let expressions = Expressions::try_from("app=web,env in (a,b),!canary,cpu>2").unwrap();
+        let bytes = rkyv::to_bytes::<Error>(&expressions).unwrap();
+
+        let archived = rkyv::access::<ArchivedExpressions, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 4);
+        assert_eq!(
+            archived.iter().map(|e| e.key()).collect::<Vec<_>>(),
+            ["app", "env", "canary", "cpu"]
+        );
+        match archived.iter().next() {
+            Some(ArchivedExpression::Equal(key, value)) => {
+                assert_eq!((key.as_str(), value.as_str()), ("app", "web"))
+            }
+            _ => panic!("expected an equality requirement"),
+        }
+
+        let deserialized = rkyv::deserialize::<Expressions, Error>(archived).unwrap();
+        assert_eq!(deserialized, expressions);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn patterns() {
+        use crate::{Expression, Pattern};
+
+        let expressions: Expressions =
+            Expression::matching("env", Pattern::new("prod|stage").unwrap()).into();
+        let bytes = rkyv::to_bytes::<Error>(&expressions).unwrap();
+        let archived = rkyv::access::<ArchivedExpressions, Error>(&bytes).unwrap();
+        assert_eq!(
+            rkyv::deserialize::<Expressions, Error>(archived).unwrap(),
+            expressions
+        );
+    }
+}
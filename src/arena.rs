@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+use crate::{Dialect, ExpressionsRef, Result};
+
+/// Storage for parsing many selectors at once, such as when importing a
+/// snapshot.
+///
+/// Selectors are copied into one buffer, which the parsed requirements
+/// borrow their keys and values from, so parsing allocates no string per
+/// key or value. Only values with backslash escapes are copied, to resolve
+/// the escapes.
+#[derive(Clone, Debug, Default)]
+pub struct SelectorArena {
+    text: String,
+    spans: Vec<Range<usize>>,
+}
+
+impl SelectorArena {
+    /// Create an empty arena
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an arena for the number of selectors with the total length
+    /// in bytes, without reallocating while they are added
+    pub fn with_capacity(selectors: usize, bytes: usize) -> Self {
+        Self {
+            text: String::with_capacity(bytes),
+            spans: Vec::with_capacity(selectors),
+        }
+    }
+
+    /// Copy a selector into the arena, returning its index
+    pub fn push(&mut self, selector: &str) -> usize {
+        let start = self.text.len();
+        self.text.push_str(selector);
+        self.spans.push(start..self.text.len());
+        self.spans.len() - 1
+    }
+
+    /// Selector added at the index
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.spans.get(index).map(|span| &self.text[span.clone()])
+    }
+
+    /// Number of selectors
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Indicates whether no selectors were added
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Drop all selectors, keeping the allocated storage for reuse
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.spans.clear();
+    }
+
+    /// Iterate over the selectors in the order they were added
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.spans.iter().map(|span| &self.text[span.clone()])
+    }
+
+    /// Parse every selector in the given dialect, in the order they were
+    /// added, borrowing keys and values from the arena
+    pub fn parse_all(&self, dialect: impl Into<Dialect>) -> Vec<Result<ExpressionsRef<'_>>> {
+        let options = dialect.into().options();
+        self.iter()
+            .map(|selector| ExpressionsRef::parse_with_options(selector, options))
+            .collect()
+    }
+}
+
+impl<S: AsRef<str>> Extend<S> for SelectorArena {
+    fn extend<T: IntoIterator<Item = S>>(&mut self, iter: T) {
+        for selector in iter {
+            self.push(selector.as_ref());
+        }
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for SelectorArena {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        let mut arena = Self::new();
+        arena.extend(iter);
+        arena
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{Dialect, ExpressionRef, Expressions, ParseError, SelectorArena};
+
+    #[test]
+    fn bulk_parse() {
+        let selectors = ["app=web", "tier in (a,b),!canary", "a=(", ""];
+        let arena: SelectorArena = selectors.iter().collect();
+        assert_eq!(arena.len(), 4);
+        assert_eq!(arena.get(1), Some("tier in (a,b),!canary"));
+        assert_eq!(arena.get(4), None);
+
+        let parsed = arena.parse_all(Dialect::Kubernetes);
+        assert!(matches!(
+            parsed[0].as_ref().unwrap().iter().next(),
+            Some(ExpressionRef::Equal(
+                Cow::Borrowed("app"),
+                Cow::Borrowed("web")
+            ))
+        ));
+        assert_eq!(
+            parsed[2],
+            Err(ParseError::UnbalancedParens("(".into(), 2..3))
+        );
+        for (selector, parsed) in selectors.iter().zip(parsed) {
+            assert_eq!(
+                parsed.map(|parsed| parsed.into_owned()),
+                Expressions::try_from(*selector),
+                "{selector}"
+            );
+        }
+
+        let mut arena = arena;
+        arena.clear();
+        assert!(arena.is_empty());
+        assert_eq!(arena.push("a"), 0);
+        assert_eq!(arena.iter().collect::<Vec<_>>(), ["a"]);
+    }
+}
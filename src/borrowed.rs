@@ -0,0 +1,326 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use logos::Logos;
+
+#[cfg(feature = "extended")]
+use crate::GlobPattern;
+#[cfg(feature = "regex")]
+use crate::Pattern;
+use crate::{
+    Dialect, Expression, Expressions, FromParts, Lexed, ParseOptions, Result, check_length,
+    check_lexed, diagnose, parse_requirement, requirement_lexer, too_many_requirements,
+};
+
+/// A requirement borrowing its key and values from the selector it was
+/// parsed from, unless they had to be unescaped.
+///
+/// Mirrors [`Expression`], see [`ExpressionsRef`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpressionRef<'a> {
+    /// Key exists and in set
+    In(Cow<'a, str>, BTreeSet<Cow<'a, str>>),
+
+    /// Key does not exists or not in set
+    NotIn(Cow<'a, str>, BTreeSet<Cow<'a, str>>),
+
+    /// Key exists and is equal
+    Equal(Cow<'a, str>, Cow<'a, str>),
+
+    /// Key does not exists or is not equal
+    NotEqual(Cow<'a, str>, Cow<'a, str>),
+
+    /// Key exists
+    Exists(Cow<'a, str>),
+
+    /// Key does not exist
+    DoesNotExist(Cow<'a, str>),
+
+    /// Key exists and is an integer greater than the value
+    GreaterThan(Cow<'a, str>, Cow<'a, str>),
+
+    /// Key exists and is an integer less than the value
+    LessThan(Cow<'a, str>, Cow<'a, str>),
+
+    /// Key exists and the value matches the pattern
+    #[cfg(feature = "regex")]
+    Matches(Cow<'a, str>, Pattern),
+
+    /// Key does not exist or the value does not match the pattern
+    #[cfg(feature = "regex")]
+    NotMatches(Cow<'a, str>, Pattern),
+
+    /// Key exists and the value matches the wildcard pattern
+    #[cfg(feature = "extended")]
+    Glob(Cow<'a, str>, GlobPattern),
+}
+
+impl ExpressionRef<'_> {
+    /// Key the expression applies to
+    pub fn key(&self) -> &str {
+        match self {
+            ExpressionRef::In(key, _)
+            | ExpressionRef::NotIn(key, _)
+            | ExpressionRef::Equal(key, _)
+            | ExpressionRef::NotEqual(key, _)
+            | ExpressionRef::Exists(key)
+            | ExpressionRef::DoesNotExist(key)
+            | ExpressionRef::GreaterThan(key, _)
+            | ExpressionRef::LessThan(key, _) => key,
+            #[cfg(feature = "regex")]
+            ExpressionRef::Matches(key, _) | ExpressionRef::NotMatches(key, _) => key,
+            #[cfg(feature = "extended")]
+            ExpressionRef::Glob(key, _) => key,
+        }
+    }
+
+    /// Copy the borrowed key and values into an owned expression
+    pub fn into_owned(self) -> Expression {
+        fn set(values: BTreeSet<Cow<'_, str>>) -> BTreeSet<String> {
+            values.into_iter().map(Cow::into_owned).collect()
+        }
+
+        match self {
+            ExpressionRef::In(key, values) => Expression::In(key.into(), set(values)),
+            ExpressionRef::NotIn(key, values) => Expression::NotIn(key.into(), set(values)),
+            ExpressionRef::Equal(key, value) => Expression::Equal(key.into(), value.into()),
+            ExpressionRef::NotEqual(key, value) => Expression::NotEqual(key.into(), value.into()),
+            ExpressionRef::Exists(key) => Expression::Exists(key.into()),
+            ExpressionRef::DoesNotExist(key) => Expression::DoesNotExist(key.into()),
+            ExpressionRef::GreaterThan(key, value) => {
+                Expression::GreaterThan(key.into(), value.into())
+            }
+            ExpressionRef::LessThan(key, value) => Expression::LessThan(key.into(), value.into()),
+            #[cfg(feature = "regex")]
+            ExpressionRef::Matches(key, pattern) => Expression::Matches(key.into(), pattern),
+            #[cfg(feature = "regex")]
+            ExpressionRef::NotMatches(key, pattern) => Expression::NotMatches(key.into(), pattern),
+            #[cfg(feature = "extended")]
+            ExpressionRef::Glob(key, pattern) => Expression::Glob(key.into(), pattern),
+        }
+    }
+
+    fn lexed(&self) -> Lexed<'_> {
+        match self {
+            ExpressionRef::Equal(_, value) | ExpressionRef::NotEqual(_, value) => {
+                Lexed::Equality(value)
+            }
+            ExpressionRef::In(key, values) => Lexed::In(key, values.len()),
+            ExpressionRef::NotIn(_, values) => Lexed::NotIn(values.len()),
+            _ => Lexed::Other,
+        }
+    }
+}
+
+impl<'a> FromParts<'a> for ExpressionRef<'a> {
+    fn set(key: Cow<'a, str>, negated: bool, values: Vec<Cow<'a, str>>) -> Self {
+        let values = values.into_iter().collect();
+        match negated {
+            false => ExpressionRef::In(key, values),
+            true => ExpressionRef::NotIn(key, values),
+        }
+    }
+}
+
+impl<'a> From<&'a Expression> for ExpressionRef<'a> {
+    fn from(expression: &'a Expression) -> Self {
+        fn set(values: &BTreeSet<String>) -> BTreeSet<Cow<'_, str>> {
+            values.iter().map(|value| value.as_str().into()).collect()
+        }
+
+        match expression {
+            Expression::In(key, values) => ExpressionRef::In(key.into(), set(values)),
+            Expression::NotIn(key, values) => ExpressionRef::NotIn(key.into(), set(values)),
+            Expression::Equal(key, value) => ExpressionRef::Equal(key.into(), value.into()),
+            Expression::NotEqual(key, value) => ExpressionRef::NotEqual(key.into(), value.into()),
+            Expression::Exists(key) => ExpressionRef::Exists(key.into()),
+            Expression::DoesNotExist(key) => ExpressionRef::DoesNotExist(key.into()),
+            Expression::GreaterThan(key, value) => {
+                ExpressionRef::GreaterThan(key.into(), value.into())
+            }
+            Expression::LessThan(key, value) => ExpressionRef::LessThan(key.into(), value.into()),
+            #[cfg(feature = "regex")]
+            Expression::Matches(key, pattern) => {
+                ExpressionRef::Matches(key.into(), pattern.clone())
+            }
+            #[cfg(feature = "regex")]
+            Expression::NotMatches(key, pattern) => {
+                ExpressionRef::NotMatches(key.into(), pattern.clone())
+            }
+            #[cfg(feature = "extended")]
+            Expression::Glob(key, pattern) => ExpressionRef::Glob(key.into(), pattern.clone()),
+        }
+    }
+}
+
+impl From<ExpressionRef<'_>> for Expression {
+    fn from(expression: ExpressionRef<'_>) -> Self {
+        expression.into_owned()
+    }
+}
+
+/// Requirements parsed without copying keys and values out of the selector,
+/// for inputs which outlive the parsed requirements.
+///
+/// Accepts exactly the selectors [`Expressions`] does, reporting the same
+/// errors.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpressionsRef<'a>(Vec<ExpressionRef<'a>>);
+
+impl<'a> ExpressionsRef<'a> {
+    /// Parse a selector in the Kubernetes grammar
+    pub fn parse(selector: &'a str) -> Result<Self> {
+        Self::parse_with_options(selector, ParseOptions::strict())
+    }
+
+    /// Parse a selector in the given dialect
+    pub fn parse_with(dialect: impl Into<Dialect>, selector: &'a str) -> Result<Self> {
+        Self::parse_with_options(selector, dialect.into().options())
+    }
+
+    /// Parse a selector, accepting the grammar extensions enabled in the options
+    pub fn parse_with_options(selector: &'a str, options: ParseOptions) -> Result<Self> {
+        check_length(selector, &options)?;
+        let mut lexer = BorrowedExpression::lexer_with_extras(selector, options);
+        let mut expressions = vec![];
+        while let Some(token) = lexer.next() {
+            let Ok(BorrowedExpression::Expression(expression)) = token else {
+                return Err(diagnose(&lexer));
+            };
+            if let Some(error) = check_lexed(&lexer, expression.lexed())
+                .or_else(|| too_many_requirements(&lexer, expressions.len()))
+            {
+                return Err(error);
+            }
+            expressions.push(expression);
+        }
+        Ok(Self(expressions))
+    }
+
+    /// Number of expressions
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Indicates whether there are no expressions, matching everything
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over expressions in order
+    pub fn iter(&self) -> std::slice::Iter<'_, ExpressionRef<'a>> {
+        self.0.iter()
+    }
+
+    /// Copy the borrowed keys and values into owned expressions
+    pub fn into_owned(self) -> Expressions {
+        self.0.into_iter().map(ExpressionRef::into_owned).collect()
+    }
+}
+
+impl<'a> From<&'a Expressions> for ExpressionsRef<'a> {
+    fn from(expressions: &'a Expressions) -> Self {
+        Self(expressions.iter().map(ExpressionRef::from).collect())
+    }
+}
+
+impl From<ExpressionsRef<'_>> for Expressions {
+    fn from(expressions: ExpressionsRef<'_>) -> Self {
+        expressions.into_owned()
+    }
+}
+
+impl<'a> FromIterator<ExpressionRef<'a>> for ExpressionsRef<'a> {
+    fn from_iter<T: IntoIterator<Item = ExpressionRef<'a>>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for ExpressionsRef<'a> {
+    type Item = ExpressionRef<'a>;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s ExpressionsRef<'a> {
+    type Item = &'s ExpressionRef<'a>;
+    type IntoIter = std::slice::Iter<'s, ExpressionRef<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+requirement_lexer! {
+    /// Lexer of [`crate::ParsedExpression`] producing borrowed requirements
+    #[derive(Logos)]
+    enum BorrowedExpression<'a> {
+        Expression(ExpressionRef<'a>),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        Dialect, ExpressionRef, Expressions, ExpressionsRef, Limit, ParseError, ParseOptions,
+    };
+
+    #[test]
+    fn borrows_input() {
+        let selector = r"app=web,tier in (a, b),!canary,path=a\/b".to_owned();
+        let parsed = ExpressionsRef::parse_with(Dialect::Extended, &selector).unwrap();
+        let Some(ExpressionRef::Equal(Cow::Borrowed(key), Cow::Borrowed(value))) =
+            parsed.iter().next()
+        else {
+            panic!("expected borrowed equality, got {parsed:?}");
+        };
+        assert_eq!((*key, *value), ("app", "web"));
+        assert!(matches!(
+            parsed.iter().last(),
+            Some(ExpressionRef::Equal(_, Cow::Owned(value))) if value == "a/b"
+        ));
+
+        let owned = parsed.clone().into_owned();
+        assert_eq!(
+            owned,
+            Expressions::parse_with(Dialect::Extended, &selector).unwrap()
+        );
+        assert_eq!(ExpressionsRef::from(&owned), parsed);
+    }
+
+    #[test]
+    fn same_errors() {
+        for selector in [
+            "a, =b",
+            "a=!b",
+            "a not in (b)",
+            "a in b",
+            "a=(",
+            "a in (b c)",
+        ] {
+            assert_eq!(
+                ExpressionsRef::parse(selector).map(ExpressionsRef::into_owned),
+                Expressions::try_from(selector),
+                "{selector}"
+            );
+        }
+
+        let options = ParseOptions {
+            max_values: Some(1),
+            ..ParseOptions::strict()
+        };
+        assert_eq!(
+            ExpressionsRef::parse_with_options("a in (b,c)", options),
+            Err(ParseError::LimitExceeded(
+                "a in (b,c)".into(),
+                0..10,
+                Limit::Values(1)
+            ))
+        );
+    }
+}
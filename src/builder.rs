@@ -0,0 +1,208 @@
+use thiserror::Error;
+
+use crate::{Expression, Expressions, Selector, is_key, is_value};
+
+/// Indicates failure to build a selector from requirements
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BuildError {
+    #[error("invalid key: '{0}'")]
+    InvalidKey(String),
+
+    #[error("invalid value for key '{0}': '{1}'")]
+    InvalidValue(String, String),
+
+    #[error("no values for set based requirement on key '{0}'")]
+    EmptyValues(String),
+}
+
+/// Builder for selectors, validating requirements on [`build`](Self::build)
+///
+/// ```
+/// use unselector::Selector;
+///
+/// let selector = Selector::builder()
+///     .eq("app", "web")
+///     .exists("team")
+///     .not_in("env", ["dev", "test"])
+///     .build()?;
+/// assert_eq!(selector.to_string(), "app=web,team,env notin (dev,test)");
+/// # Ok::<(), unselector::BuildError>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SelectorBuilder {
+    requirements: Vec<Expression>,
+}
+
+impl Selector {
+    /// Start building a selector from requirements
+    pub fn builder() -> SelectorBuilder {
+        SelectorBuilder::default()
+    }
+}
+
+impl SelectorBuilder {
+    /// Require the key to be set to the value
+    pub fn eq(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.with(Expression::equal(key, value))
+    }
+
+    /// Require the key to be unset or set to a different value
+    pub fn ne(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.with(Expression::not_equal(key, value))
+    }
+
+    /// Require the key to be set to one of the values
+    pub fn r#in(
+        self,
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.with(Expression::r#in(key, values))
+    }
+
+    /// Require the key to be unset or set to none of the values
+    pub fn not_in(
+        self,
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.with(Expression::not_in(key, values))
+    }
+
+    /// Require the key to be set
+    pub fn exists(self, key: impl Into<String>) -> Self {
+        self.with(Expression::exists(key))
+    }
+
+    /// Require the key to be unset
+    pub fn does_not_exist(self, key: impl Into<String>) -> Self {
+        self.with(Expression::does_not_exist(key))
+    }
+
+    /// Require the key to be set to an integer greater than the value
+    pub fn gt(self, key: impl Into<String>, value: i64) -> Self {
+        self.with(Expression::greater_than(key, value))
+    }
+
+    /// Require the key to be set to an integer less than the value
+    pub fn lt(self, key: impl Into<String>, value: i64) -> Self {
+        self.with(Expression::less_than(key, value))
+    }
+
+    /// Add an arbitrary requirement
+    pub fn with(mut self, requirement: Expression) -> Self {
+        self.requirements.push(requirement);
+        self
+    }
+
+    /// Validate the requirements, failing on the first invalid one
+    pub fn build(self) -> Result<Expressions, BuildError> {
+        for requirement in &self.requirements {
+            validate(requirement)?;
+        }
+
+        Ok(self.requirements.into_iter().collect())
+    }
+}
+
+/// Check that the requirement can be written in selector syntax
+fn validate(requirement: &Expression) -> Result<(), BuildError> {
+    let key = requirement.key();
+    if !is_key(key) {
+        return Err(BuildError::InvalidKey(key.to_owned()));
+    }
+
+    let values = match requirement {
+        Expression::In(_, values) | Expression::NotIn(_, values) if values.is_empty() => {
+            return Err(BuildError::EmptyValues(key.to_owned()));
+        }
+        Expression::In(_, values) | Expression::NotIn(_, values) => values.iter().collect(),
+        Expression::Equal(_, value) | Expression::NotEqual(_, value) if value.is_empty() => vec![],
+        Expression::Equal(_, value) | Expression::NotEqual(_, value) => vec![value],
+        Expression::GreaterThan(_, value) | Expression::LessThan(_, value)
+            if value.parse::<i64>().is_err() =>
+        {
+            return Err(BuildError::InvalidValue(key.to_owned(), value.to_owned()));
+        }
+        Expression::GreaterThan(_, value) | Expression::LessThan(_, value) => vec![value],
+        #[cfg(feature = "regex")]
+        Expression::Matches(..) | Expression::NotMatches(..) => vec![],
+        #[cfg(feature = "extended")]
+        Expression::Glob(..) => vec![],
+        Expression::Exists(_) | Expression::DoesNotExist(_) => vec![],
+    };
+    match values.into_iter().find(|value| !is_value(value)) {
+        Some(value) => Err(BuildError::InvalidValue(key.to_owned(), value.to_owned())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BuildError, Expression, Expressions, Selector};
+
+    #[test]
+    fn build() {
+        let selector = Selector::builder()
+            .eq("app", "web")
+            .ne("tier", "db")
+            .r#in("env", ["prod", "stage"])
+            .not_in("region", ["us"])
+            .exists("example.com/team")
+            .does_not_exist("canary")
+            .gt("replicas", 1)
+            .lt("replicas", 10)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            selector,
+            Expressions::try_from(
+                "app=web,tier!=db,env in (prod,stage),region notin (us),example.com/team,!canary,\
+                 replicas>1,replicas<10"
+            )
+            .unwrap()
+        );
+        assert_eq!(Selector::builder().build(), Ok(Expressions::default()));
+        assert_eq!(
+            Selector::builder().eq("app", "").build(),
+            Ok(Expressions::try_from("app=").unwrap())
+        );
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(
+            Selector::builder().eq("", "web").build(),
+            Err(BuildError::InvalidKey("".into()))
+        );
+        assert_eq!(
+            Selector::builder().exists("a b").build(),
+            Err(BuildError::InvalidKey("a b".into()))
+        );
+        assert_eq!(
+            Selector::builder().eq("app", "web,api").build(),
+            Err(BuildError::InvalidValue("app".into(), "web,api".into()))
+        );
+        assert_eq!(
+            Selector::builder().r#in("env", ["prod", "a/b"]).build(),
+            Err(BuildError::InvalidValue("env".into(), "a/b".into()))
+        );
+        assert_eq!(
+            Selector::builder().eq("team", "café").build(),
+            Err(BuildError::InvalidValue("team".into(), "café".into()))
+        );
+        assert_eq!(
+            Selector::builder()
+                .not_in("env", Vec::<String>::new())
+                .build(),
+            Err(BuildError::EmptyValues("env".into()))
+        );
+        assert_eq!(
+            Selector::builder()
+                .with(Expression::GreaterThan("replicas".into(), "one".into()))
+                .build(),
+            Err(BuildError::InvalidValue("replicas".into(), "one".into()))
+        );
+    }
+}
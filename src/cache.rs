@@ -0,0 +1,146 @@
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+
+use crate::{LabelSet, Matcher, lru::Lru};
+
+/// Which cached outcome to drop when a [`CachedMatcher`] is full
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Eviction {
+    /// Drop the least recently used outcome
+    #[default]
+    Lru,
+
+    /// Drop the oldest outcome, regardless of use
+    Fifo,
+}
+
+/// A [`Matcher`] memoizing outcomes for recently seen label sets.
+///
+/// Only labels the selector refers to are taken into account, so label sets
+/// differing in unrelated labels share a cached outcome.
+#[derive(Clone, Debug)]
+pub struct CachedMatcher {
+    matcher: Matcher,
+    keys: Vec<Box<str>>,
+    hasher: RandomState,
+    cache: Lru<u64, Entry>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    values: Box<[Option<Box<str>>]>,
+    matches: bool,
+}
+
+impl CachedMatcher {
+    /// Cache up to `capacity` outcomes of the matcher, evicting the least
+    /// recently used ones
+    pub fn new(matcher: Matcher, capacity: usize) -> Self {
+        Self::with_eviction(matcher, capacity, Eviction::default())
+    }
+
+    /// Cache up to `capacity` outcomes of the matcher with the eviction policy
+    pub fn with_eviction(matcher: Matcher, capacity: usize, eviction: Eviction) -> Self {
+        Self {
+            keys: matcher.keys().map(Box::from).collect(),
+            matcher,
+            hasher: RandomState::new(),
+            cache: Lru::new(capacity, eviction == Eviction::Lru),
+        }
+    }
+
+    /// Check whether the selector matches the given labels, reusing the
+    /// outcome for an equivalent label set if it is cached
+    pub fn matches(&mut self, labels: &impl LabelSet) -> bool {
+        let mut hasher = self.hasher.build_hasher();
+        for key in &self.keys {
+            labels.get(key).hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+
+        if let Some(entry) = self.cache.get(&hash) {
+            let hit = entry
+                .values
+                .iter()
+                .zip(&self.keys)
+                .all(|(value, key)| value.as_deref() == labels.get(key));
+            if hit {
+                return entry.matches;
+            }
+        }
+
+        let matches = self.matcher.matches(labels);
+        let values = self.keys.iter().map(|key| labels.get(key).map(Box::from));
+        self.cache.insert(
+            hash,
+            Entry {
+                values: values.collect(),
+                matches,
+            },
+        );
+        matches
+    }
+
+    /// Number of cached outcomes
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Indicates whether no outcomes are cached
+    pub fn is_empty(&self) -> bool {
+        self.cache.len() == 0
+    }
+
+    /// Maximum number of cached outcomes
+    pub fn capacity(&self) -> usize {
+        self.cache.capacity()
+    }
+
+    /// Drop all cached outcomes
+    pub fn clear(&mut self) {
+        self.cache.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{CachedMatcher, Eviction, Expressions};
+
+    #[test]
+    fn memoizes_relevant_labels() {
+        let selector = Expressions::try_from("app=web,!canary").unwrap();
+        let mut cached = CachedMatcher::new(selector.compile(), 2);
+
+        assert!(cached.matches(&BTreeMap::from([("app", "web"), ("pod", "a")])));
+        assert!(cached.matches(&BTreeMap::from([("app", "web"), ("pod", "b")])));
+        assert_eq!(cached.len(), 1);
+
+        assert!(!cached.matches(&BTreeMap::from([("app", "web"), ("canary", "")])));
+        assert!(!cached.matches(&BTreeMap::from([("app", "api")])));
+        assert_eq!(cached.len(), 2);
+        assert!(cached.matches(&BTreeMap::from([("app", "web")])));
+        assert_eq!(cached.len(), 2);
+
+        cached.clear();
+        assert!(cached.is_empty());
+    }
+
+    #[test]
+    fn agrees_with_matcher() {
+        let label_sets = [
+            BTreeMap::from([("app", "web")]),
+            BTreeMap::from([("app", "web"), ("env", "dev")]),
+            BTreeMap::from([("env", "prod")]),
+            BTreeMap::new(),
+        ];
+        let selector = Expressions::try_from("app,env notin (dev)").unwrap();
+        let mut cached = CachedMatcher::with_eviction(selector.compile(), 1, Eviction::Fifo);
+
+        for _ in 0..3 {
+            for labels in &label_sets {
+                assert_eq!(cached.matches(labels), selector.matches(labels));
+            }
+        }
+    }
+}
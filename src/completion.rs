@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use logos::Span;
+
+use crate::{Token, TokenKind, tokenize};
+
+/// Known label keys and their values, for suggesting completions
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Catalog {
+    labels: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Catalog {
+    /// Add a key without known values
+    pub fn add_key(&mut self, key: impl Into<String>) {
+        self.labels.entry(key.into()).or_default();
+    }
+
+    /// Add a value of a key
+    pub fn add(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.labels
+            .entry(key.into())
+            .or_default()
+            .insert(value.into());
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Catalog {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(labels: T) -> Self {
+        let mut catalog = Catalog::default();
+        for (key, value) in labels {
+            catalog.add(key, value);
+        }
+        catalog
+    }
+}
+
+/// Kind of text a [`Completion`] inserts
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompletionKind {
+    /// Label key starting a requirement
+    Key,
+
+    /// Operator following a key, or `(` following `in` and `notin`
+    Operator,
+
+    /// Value of the key the requirement applies to
+    Value,
+}
+
+/// A suggested replacement for part of a selector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    /// Kind of the inserted text
+    pub kind: CompletionKind,
+
+    /// Text to insert
+    pub text: String,
+
+    /// Part of the input replaced by the text, which is the partially typed
+    /// word before the cursor, or empty at the cursor
+    pub replace: Span,
+}
+
+const OPERATORS: [&str; 4] = ["=", "!=", "in", "notin"];
+
+/// Suggest keys, operators or values which may be written at the byte offset
+/// of the input, using the catalog for keys and values
+pub fn complete(input: &str, offset: usize, catalog: Option<&Catalog>) -> Vec<Completion> {
+    let offset = offset.min(input.len());
+    let mut tokens: Vec<_> = tokenize(input.get(..offset).unwrap_or_default()).collect();
+    let partial = match tokens.last() {
+        Some(token)
+            if token.span.end == offset
+                && matches!(
+                    token.kind,
+                    TokenKind::Key | TokenKind::Value | TokenKind::Unknown
+                ) =>
+        {
+            tokens.pop()
+        }
+        _ => None,
+    };
+    let (prefix, replace) = match &partial {
+        Some(token) => (token.text, token.span.clone()),
+        None => ("", offset..offset),
+    };
+    let suggest = |kind, candidates: &mut dyn Iterator<Item = &str>| {
+        candidates
+            .filter(|candidate| candidate.starts_with(prefix))
+            .map(|text| Completion {
+                kind,
+                text: text.to_owned(),
+                replace: replace.clone(),
+            })
+            .collect()
+    };
+    let values = |key: &str, listed: BTreeSet<&str>| {
+        let values = catalog.and_then(|catalog| catalog.labels.get(key));
+        let values = values.into_iter().flatten().map(String::as_str);
+        suggest(
+            CompletionKind::Value,
+            &mut values.filter(|value| !listed.contains(value)),
+        )
+    };
+
+    let list = tokens
+        .iter()
+        .rposition(|token| token.kind == TokenKind::OpenParen)
+        .filter(|&open| {
+            tokens[open..]
+                .iter()
+                .all(|t| t.kind != TokenKind::CloseParen)
+        });
+    if let Some(open) = list {
+        let listed = tokens[open..]
+            .iter()
+            .filter(|token| token.kind == TokenKind::Value)
+            .map(|token| token.text)
+            .collect();
+        return values(key_of(&tokens[..open]), listed);
+    }
+
+    match tokens.last() {
+        None => suggest(CompletionKind::Key, &mut keys(catalog)),
+        Some(token) => match (token.kind, token.text) {
+            (TokenKind::Separator, _) | (TokenKind::Operator, "!") => {
+                suggest(CompletionKind::Key, &mut keys(catalog))
+            }
+            (TokenKind::Key, _) => suggest(CompletionKind::Operator, &mut OPERATORS.into_iter()),
+            (TokenKind::Operator, "=" | "==" | "!=") => values(key_of(&tokens), BTreeSet::new()),
+            (TokenKind::Operator, keyword)
+                if keyword.eq_ignore_ascii_case("in") || keyword.eq_ignore_ascii_case("notin") =>
+            {
+                suggest(CompletionKind::Operator, &mut ["("].into_iter())
+            }
+            _ => vec![],
+        },
+    }
+}
+
+fn keys(catalog: Option<&Catalog>) -> impl Iterator<Item = &str> {
+    catalog
+        .into_iter()
+        .flat_map(|catalog| catalog.labels.keys())
+        .map(String::as_str)
+}
+
+/// Key of the requirement the tokens end in
+fn key_of<'a>(tokens: &[Token<'a>]) -> &'a str {
+    tokens
+        .iter()
+        .rev()
+        .find(|token| token.kind == TokenKind::Key)
+        .map_or("", |token| token.text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Catalog, Completion, CompletionKind, complete};
+
+    fn texts(completions: Vec<Completion>) -> Vec<String> {
+        completions.into_iter().map(|c| c.text).collect()
+    }
+
+    #[test]
+    fn completions() {
+        let catalog: Catalog = [
+            ("app", "web"),
+            ("app", "api"),
+            ("env", "prod"),
+            ("env", "stage"),
+            ("tier", "db"),
+        ]
+        .into_iter()
+        .collect();
+        let catalog = Some(&catalog);
+
+        assert_eq!(texts(complete("", 0, catalog)), ["app", "env", "tier"]);
+        assert_eq!(texts(complete("a=b, e", 6, catalog)), ["env"]);
+        assert_eq!(texts(complete("a=b,!t", 6, catalog)), ["tier"]);
+        assert_eq!(
+            texts(complete("env ", 4, catalog)),
+            ["=", "!=", "in", "notin"]
+        );
+        assert_eq!(texts(complete("env n", 5, catalog)), ["notin"]);
+        assert_eq!(texts(complete("env !", 5, catalog)), ["!="]);
+        assert_eq!(texts(complete("app=", 4, catalog)), ["api", "web"]);
+        assert_eq!(texts(complete("app != w", 8, catalog)), ["web"]);
+        assert_eq!(texts(complete("env in", 6, catalog)), ["("]);
+        assert_eq!(texts(complete("env in (prod, ", 14, catalog)), ["stage"]);
+        assert_eq!(
+            texts(complete("env in (prod) ", 14, catalog)),
+            Vec::<String>::new()
+        );
+        assert_eq!(texts(complete("app=web", 3, catalog)), ["app"]);
+        assert!(complete("", 0, None).is_empty());
+
+        assert_eq!(
+            complete("tier=d, x", 6, catalog),
+            [Completion {
+                kind: CompletionKind::Value,
+                text: "db".into(),
+                replace: 5..6,
+            }]
+        );
+    }
+}
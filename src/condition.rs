@@ -0,0 +1,440 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::FromStr;
+use std::vec;
+
+use logos::{Logos, Span};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Dialect, Expression, Expressions, LabelSet, ParseError, ParseOptions, ParsedExpression, Result,
+    SelectorDnf, check_length, parse_expression, too_many_requirements,
+};
+
+/// A boolean combination of requirements.
+///
+/// Parsed from an extension of the Kubernetes grammar, where requirements are
+/// grouped with parentheses and groups are negated with `!(...)`. Negation
+/// binds tightest, followed by conjunction with `,` and disjunction with `||`.
+/// The empty group `()` matches everything, while `!()` matches nothing.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Condition {
+    /// A single requirement
+    Requirement(Expression),
+
+    /// All conditions have to match, which is true for none
+    All(Vec<Condition>),
+
+    /// Any condition has to match, which is false for none
+    Any(Vec<Condition>),
+
+    /// The condition must not match
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Parse a condition in the given dialect
+    pub fn parse_with(dialect: impl Into<Dialect>, selector: &str) -> Result<Self> {
+        Self::parse_with_options(selector, dialect.into().options())
+    }
+
+    /// Parse a condition, accepting the grammar extensions enabled in the options
+    pub fn parse_with_options(selector: &str, options: ParseOptions) -> Result<Self> {
+        let mut parser = Parser {
+            source: selector,
+            tokens: tokens(selector, options)?.into_iter().peekable(),
+        };
+        if parser.tokens.peek().is_none() {
+            return Ok(Condition::All(vec![]));
+        }
+
+        let condition = parser.any()?;
+        match parser.tokens.next() {
+            Some((_, span)) => Err(parser.error(Some(span))),
+            None => Ok(condition),
+        }
+    }
+
+    /// Check whether the condition holds for the given labels
+    pub fn matches(&self, labels: &impl LabelSet) -> bool {
+        match self {
+            Condition::Requirement(expression) => expression.matches(labels),
+            Condition::All(conditions) => conditions.iter().all(|c| c.matches(labels)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.matches(labels)),
+            Condition::Not(condition) => !condition.matches(labels),
+        }
+    }
+
+    /// Negate the condition
+    pub fn negate(self) -> Self {
+        match self {
+            Condition::Not(condition) => *condition,
+            condition => Condition::Not(condition.into()),
+        }
+    }
+}
+
+impl From<Expression> for Condition {
+    fn from(expression: Expression) -> Self {
+        Condition::Requirement(expression)
+    }
+}
+
+impl From<Expressions> for Condition {
+    fn from(expressions: Expressions) -> Self {
+        Condition::All(
+            expressions
+                .into_iter()
+                .map(|ParsedExpression::Expression(e)| e.into())
+                .collect(),
+        )
+    }
+}
+
+impl From<SelectorDnf> for Condition {
+    fn from(dnf: SelectorDnf) -> Self {
+        Condition::Any(
+            dnf.into_alternatives()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Requirement(expression) => write!(f, "{expression}"),
+            Condition::All(conditions) => join(f, conditions, ",", |c| {
+                matches!(c, Condition::Any(_) | Condition::All(_))
+            }),
+            Condition::Any(conditions) if conditions.is_empty() => f.write_str("!()"),
+            Condition::Any(conditions) => join(f, conditions, " || ", |c| match c {
+                Condition::All(conditions) => conditions.is_empty(),
+                c => matches!(c, Condition::Any(_)),
+            }),
+            Condition::Not(condition) => match &**condition {
+                Condition::All(conditions) if conditions.is_empty() => f.write_str("!(())"),
+                condition => write!(f, "!({condition})"),
+            },
+        }
+    }
+}
+
+/// Write conditions with a separator, parenthesizing those which need grouping
+fn join(
+    f: &mut fmt::Formatter<'_>,
+    conditions: &[Condition],
+    separator: &str,
+    group: fn(&Condition) -> bool,
+) -> fmt::Result {
+    for (i, condition) in conditions.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        if group(condition) {
+            write!(f, "({condition})")?;
+        } else {
+            write!(f, "{condition}")?;
+        }
+    }
+    Ok(())
+}
+
+impl TryFrom<&str> for Condition {
+    type Error = ParseError;
+
+    fn try_from(selector: &str) -> Result<Self> {
+        Self::parse_with_options(selector, ParseOptions::strict())
+    }
+}
+
+impl FromStr for Condition {
+    type Err = ParseError;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::try_from(selector)
+    }
+}
+
+enum Token {
+    Requirement(Expression),
+    Open,
+    NotOpen,
+    Close,
+    Or,
+}
+
+/// Split the selector into requirements and grouping tokens, applying the
+/// limits of the options to the whole selector
+fn tokens(selector: &str, options: ParseOptions) -> Result<Vec<(Token, Span)>> {
+    check_length(selector, &options)?;
+    let mut lexer = ParsedExpression::lexer_with_extras(selector, options);
+    let mut tokens = vec![];
+    let mut parsed = 0;
+    loop {
+        let token = match parse_expression(&mut lexer) {
+            Ok(Some(ParsedExpression::Expression(expression))) => {
+                if let Some(error) = too_many_requirements(&lexer, parsed) {
+                    return Err(error);
+                }
+                parsed += 1;
+                Token::Requirement(expression)
+            }
+            Ok(None) => break,
+            Err(error) => {
+                let remainder = lexer.remainder();
+                match lexer.slice() {
+                    "(" => Token::Open,
+                    ")" => Token::Close,
+                    "!" if remainder.starts_with('(') => {
+                        lexer.bump(1);
+                        Token::NotOpen
+                    }
+                    "|" if remainder.starts_with('|') => {
+                        lexer.bump(1);
+                        Token::Or
+                    }
+                    _ => return Err(error),
+                }
+            }
+        };
+        tokens.push((token, lexer.span()));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Peekable<vec::IntoIter<(Token, Span)>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, span: Option<Span>) -> ParseError {
+        let span = span.unwrap_or(self.source.len()..self.source.len());
+        ParseError::StringParse(self.source[span.clone()].to_owned(), span)
+    }
+
+    /// Disjunction of conjunctions
+    fn any(&mut self) -> Result<Condition> {
+        let mut alternatives = vec![self.all()?];
+        while self
+            .tokens
+            .next_if(|(token, _)| matches!(token, Token::Or))
+            .is_some()
+        {
+            alternatives.push(self.all()?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Condition::Any(alternatives),
+        })
+    }
+
+    /// Conjunction of one or more groups or requirements
+    fn all(&mut self) -> Result<Condition> {
+        let mut conditions = vec![self.unary()?];
+        while let Some((Token::Requirement(_) | Token::Open | Token::NotOpen, _)) =
+            self.tokens.peek()
+        {
+            conditions.push(self.unary()?);
+        }
+        Ok(match conditions.len() {
+            1 => conditions.remove(0),
+            _ => Condition::All(conditions),
+        })
+    }
+
+    /// A requirement, or a possibly negated group
+    fn unary(&mut self) -> Result<Condition> {
+        let negate = match self.tokens.next() {
+            Some((Token::Requirement(expression), _)) => {
+                return Ok(Condition::Requirement(expression));
+            }
+            Some((Token::Open, _)) => false,
+            Some((Token::NotOpen, _)) => true,
+            Some((Token::Close | Token::Or, span)) => return Err(self.error(Some(span))),
+            None => return Err(self.error(None)),
+        };
+        if self
+            .tokens
+            .next_if(|(token, _)| matches!(token, Token::Close))
+            .is_some()
+        {
+            return Ok(if negate {
+                Condition::Any(vec![])
+            } else {
+                Condition::All(vec![])
+            });
+        }
+
+        let condition = self.any()?;
+        match self.tokens.next() {
+            Some((Token::Close, _)) => {}
+            token => return Err(self.error(token.map(|(_, span)| span))),
+        }
+        Ok(if negate {
+            Condition::Not(condition.into())
+        } else {
+            condition
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{Condition, Expressions, Limit, ParseError, ParseOptions};
+
+    fn requirement(selector: &str) -> Condition {
+        let expressions = Expressions::try_from(selector).unwrap();
+        Condition::Requirement(expressions.iter().next().unwrap().clone())
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            "a, b || c".parse(),
+            Ok(Condition::Any(vec![
+                Condition::All(vec![requirement("a"), requirement("b")]),
+                requirement("c"),
+            ]))
+        );
+        assert_eq!(
+            "!(env=prod, region in (us, eu)) || (a || !b), c".parse(),
+            Ok(Condition::Any(vec![
+                Condition::Not(
+                    Condition::All(vec![
+                        requirement("env=prod"),
+                        requirement("region in (eu,us)")
+                    ])
+                    .into()
+                ),
+                Condition::All(vec![
+                    Condition::Any(vec![requirement("a"), requirement("!b")]),
+                    requirement("c"),
+                ]),
+            ]))
+        );
+        assert_eq!("".parse(), Ok(Condition::All(vec![])));
+        assert_eq!("((a))".parse(), Ok(requirement("a")));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str, span| Err(ParseError::StringParse(text.into(), span));
+        assert_eq!(Condition::try_from("(a"), error("", 2..2));
+        assert_eq!(Condition::try_from("a)"), error(")", 1..2));
+        assert_eq!(Condition::try_from("(||)"), error("||", 1..3));
+        assert_eq!(Condition::try_from("a ||"), error("", 4..4));
+        assert_eq!(Condition::try_from("a | b"), error("|", 2..3));
+
+        for selector in ["env In (a)", "(a=!b)", "a || !(c not in (d))"] {
+            let condition = Condition::try_from(selector);
+            assert!(
+                matches!(condition, Err(ParseError::MisspelledOperator(..))),
+                "{selector}: {condition:?}"
+            );
+        }
+        assert_eq!(
+            Condition::try_from("a, (=b)"),
+            Err(ParseError::EmptyKey("=".into(), 4..5))
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let limited = |options| Condition::parse_with_options("a,b,c in (x,y) || d,e,f", options);
+        assert_eq!(
+            limited(ParseOptions {
+                max_length: Some(10),
+                ..ParseOptions::strict()
+            }),
+            Err(ParseError::LimitExceeded(
+                String::new(),
+                10..23,
+                Limit::Length(10)
+            ))
+        );
+        assert_eq!(
+            limited(ParseOptions {
+                max_requirements: Some(5),
+                ..ParseOptions::strict()
+            }),
+            Err(ParseError::LimitExceeded(
+                "f".into(),
+                22..23,
+                Limit::Requirements(5)
+            ))
+        );
+        assert_eq!(
+            limited(ParseOptions {
+                max_values: Some(1),
+                ..ParseOptions::strict()
+            }),
+            Err(ParseError::LimitExceeded(
+                "c in (x,y)".into(),
+                4..14,
+                Limit::Values(1)
+            ))
+        );
+        assert!(
+            limited(ParseOptions {
+                max_length: Some(23),
+                max_requirements: Some(6),
+                max_values: Some(2),
+                ..ParseOptions::strict()
+            })
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for selector in [
+            "a,b || c",
+            "!(env=prod,region in (eu,us)) || (a || !b),c",
+            "a,(b,c)",
+            "!(!(a))",
+        ] {
+            let condition: Condition = selector.parse().unwrap();
+            assert_eq!(condition.to_string(), selector);
+            assert_eq!(condition.to_string().parse(), Ok(condition));
+        }
+
+        let a = || requirement("a");
+        for (condition, selector) in [
+            (Condition::Any(vec![]), "!()"),
+            (Condition::All(vec![a(), Condition::All(vec![])]), "a,()"),
+            (Condition::All(vec![a(), Condition::Any(vec![])]), "a,(!())"),
+            (Condition::Any(vec![a(), Condition::All(vec![])]), "a || ()"),
+            (
+                Condition::Any(vec![a(), Condition::Any(vec![])]),
+                "a || (!())",
+            ),
+            (Condition::Not(Condition::All(vec![]).into()), "!(())"),
+            (Condition::Not(Condition::Any(vec![]).into()), "!(!())"),
+        ] {
+            assert_eq!(condition.to_string(), selector);
+            assert_eq!(selector.parse(), Ok(condition));
+        }
+    }
+
+    #[test]
+    fn matches() {
+        let condition: Condition = "!(env=prod, region=us) || admin".parse().unwrap();
+        assert!(condition.matches(&BTreeMap::from([("env", "prod"), ("region", "eu")])));
+        assert!(!condition.matches(&BTreeMap::from([("env", "prod"), ("region", "us")])));
+        assert!(condition.matches(&BTreeMap::from([
+            ("env", "prod"),
+            ("region", "us"),
+            ("admin", "")
+        ])));
+        assert!(Condition::All(vec![]).matches(&BTreeMap::<String, String>::new()));
+        assert!(!Condition::Any(vec![]).matches(&BTreeMap::<String, String>::new()));
+    }
+}
@@ -0,0 +1,197 @@
+//! Lossless parse trees of selectors, for editing a selector while keeping
+//! its formatting.
+//!
+//! ```
+//! use unselector::Expression;
+//! use unselector::cst::SelectorTree;
+//!
+//! let mut tree: SelectorTree = "app = web,  tier in (a, b)".parse()?;
+//! tree.set(0, Expression::equal("app", "api"));
+//! tree.push(Expression::does_not_exist("canary"));
+//! assert_eq!(tree.to_string(), "app=api,  tier in (a, b),  !canary");
+//! # Ok::<(), unselector::ParseError>(())
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Dialect, Expression, Expressions, ParseError, Result};
+
+/// A selector with the original text of every requirement, and the
+/// whitespace and separators between them
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SelectorTree {
+    leading: String,
+    requirements: Vec<Requirement>,
+}
+
+/// A requirement of a [`SelectorTree`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Requirement {
+    expression: Expression,
+    text: String,
+    trailing: String,
+}
+
+impl Requirement {
+    /// Parsed requirement
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// Text of the requirement as written
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whitespace and separators following the requirement
+    pub fn trailing(&self) -> &str {
+        &self.trailing
+    }
+}
+
+impl SelectorTree {
+    /// Parse a selector in the given dialect
+    pub fn parse(dialect: impl Into<Dialect>, selector: &str) -> Result<Self> {
+        let spanned = Expressions::parse_spanned_with(dialect, selector)?;
+        let leading = match spanned.first() {
+            Some(first) => selector[..first.span.start].to_owned(),
+            None => selector.to_owned(),
+        };
+        let ends = spanned
+            .iter()
+            .skip(1)
+            .map(|next| next.span.start)
+            .chain([selector.len()]);
+        let requirements = spanned
+            .iter()
+            .zip(ends)
+            .map(|(expression, end)| Requirement {
+                expression: expression.value.clone(),
+                text: selector[expression.span.clone()].to_owned(),
+                trailing: selector[expression.span.end..end].to_owned(),
+            })
+            .collect();
+        Ok(SelectorTree {
+            leading,
+            requirements,
+        })
+    }
+
+    /// Requirements in order
+    pub fn requirements(&self) -> &[Requirement] {
+        &self.requirements
+    }
+
+    /// Parsed requirements, without formatting
+    pub fn to_expressions(&self) -> Expressions {
+        self.requirements
+            .iter()
+            .map(|requirement| requirement.expression.clone())
+            .collect()
+    }
+
+    /// Replace the requirement at the index, keeping the surrounding
+    /// formatting
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, index: usize, expression: Expression) {
+        let requirement = &mut self.requirements[index];
+        requirement.text = expression.to_string();
+        requirement.expression = expression;
+    }
+
+    /// Remove the requirement at the index along with its separator
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Expression {
+        let removed = self.requirements.remove(index);
+        if index == self.requirements.len()
+            && let Some(last) = self.requirements.last_mut()
+        {
+            last.trailing = removed.trailing;
+        }
+        removed.expression
+    }
+
+    /// Append a requirement, separated like the existing ones
+    pub fn push(&mut self, expression: Expression) {
+        let separator = match self.requirements.as_slice() {
+            [first, _, ..] => first.trailing.clone(),
+            _ => ",".into(),
+        };
+        let trailing = match self.requirements.last_mut() {
+            Some(last) => std::mem::replace(&mut last.trailing, separator),
+            None => String::new(),
+        };
+        self.requirements.push(Requirement {
+            text: expression.to_string(),
+            expression,
+            trailing,
+        });
+    }
+}
+
+impl fmt::Display for SelectorTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.leading)?;
+        for requirement in &self.requirements {
+            write!(f, "{}{}", requirement.text, requirement.trailing)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SelectorTree {
+    type Err = ParseError;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::parse(Dialect::Kubernetes, selector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SelectorTree;
+    use crate::{Dialect, Expression, Expressions};
+
+    #[test]
+    fn lossless() {
+        for selector in [
+            "",
+            "  ",
+            " app = web ,tier in ( a,b ),\t!canary  ",
+            "a,,b",
+            "a=b,\nc",
+        ] {
+            let tree: SelectorTree = selector.parse().unwrap();
+            assert_eq!(tree.to_string(), selector);
+            assert_eq!(
+                tree.to_expressions(),
+                Expressions::try_from(selector).unwrap()
+            );
+        }
+        let tree = SelectorTree::parse(Dialect::Extended, "a = 'b c' ").unwrap();
+        assert_eq!(tree.requirements()[0].text(), "a = 'b c'");
+        assert_eq!(tree.requirements()[0].trailing(), " ");
+        assert!("a in b".parse::<SelectorTree>().is_err());
+    }
+
+    #[test]
+    fn edit() {
+        let mut tree: SelectorTree = " app = web , tier in (a,b) , !canary\n".parse().unwrap();
+        assert_eq!(tree.remove(2), Expression::does_not_exist("canary"));
+        assert_eq!(tree.to_string(), " app = web , tier in (a,b)\n");
+        tree.push(Expression::exists("team"));
+        assert_eq!(tree.to_string(), " app = web , tier in (a,b) , team\n");
+        tree.remove(0);
+        assert_eq!(tree.to_string(), " tier in (a,b) , team\n");
+        tree.set(1, Expression::not_equal("team", "x"));
+        assert_eq!(tree.to_string(), " tier in (a,b) , team!=x\n");
+
+        let mut tree = SelectorTree::default();
+        tree.push(Expression::exists("a"));
+        tree.push(Expression::exists("b"));
+        assert_eq!(tree.to_string(), "a,b");
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::Display;
+use std::iter;
+
+use miette::{Diagnostic, LabeledSpan, Report};
+
+use crate::ParseError;
+
+impl ParseError {
+    /// Attach the parsed selector, so reports can show the offending span
+    pub fn into_report(self, selector: impl Into<String>) -> Report {
+        Report::new(self).with_source_code(selector.into())
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            _ if self.text().is_empty() => "unexpected end of input",
+            ParseError::StringParse(..) => "unexpected input",
+            ParseError::EmptyKey(..) => "key expected before this",
+            ParseError::InvalidValue(..) => "invalid key or value",
+            ParseError::UnbalancedParens(..) => "unmatched parenthesis",
+            ParseError::MissingValuesList(..) => "list of values expected after this",
+            ParseError::UnknownOperator(..) => "unsupported operator",
+            ParseError::MisspelledOperator(..) => "misspelled operator",
+            ParseError::LimitExceeded(..) => "limit exceeded",
+            ParseError::UnresolvedVariable(..) => "undefined variable",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        match self {
+            ParseError::StringParse(..) => {
+                "requirements look like `key=value`, `key in (a,b)`, `key` or `!key`"
+            }
+            ParseError::EmptyKey(..) => "every requirement starts with a label key",
+            ParseError::InvalidValue(..) => {
+                "keys and values consist of alphanumerics, `-`, `_` and `.`, and keys may contain `/`"
+            }
+            ParseError::UnbalancedParens(..) => {
+                "parentheses only enclose the values of `in` and `notin`"
+            }
+            ParseError::MissingValuesList(..) => {
+                "write the values in parentheses, such as `in (a,b)`"
+            }
+            ParseError::UnknownOperator(..) => {
+                "supported operators are `=`, `==`, `!=`, `in`, `notin`, `>` and `<`"
+            }
+            ParseError::MisspelledOperator(..) => {
+                "operators are written without spaces, in lowercase"
+            }
+            ParseError::LimitExceeded(..) => "the selector is larger than the parser accepts",
+            ParseError::UnresolvedVariable(..) => "pass a value for every `${NAME}` placeholder",
+        }
+    }
+}
+
+impl Diagnostic for ParseError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!("unselector::{}", ParseError::code(self))))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            ParseError::MisspelledOperator(_, _, suggestion) => {
+                Some(Box::new(format!("did you mean `{suggestion}`?")))
+            }
+            _ => Some(Box::new(ParseError::help(self))),
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(iter::once(LabeledSpan::new_with_span(
+            Some(self.label().into()),
+            self.span(),
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::Diagnostic;
+
+    use crate::Expressions;
+
+    #[test]
+    fn diagnostic() {
+        let error = Expressions::try_from("app=web, tier in db").unwrap_err();
+        assert_eq!(
+            Diagnostic::code(&error).unwrap().to_string(),
+            "unselector::missing_values_list"
+        );
+        assert!(
+            Diagnostic::help(&error)
+                .unwrap()
+                .to_string()
+                .contains("in (a,b)")
+        );
+
+        let labels: Vec<_> = error.labels().unwrap().collect();
+        assert_eq!(labels.len(), 1);
+        assert_eq!((labels[0].offset(), labels[0].len()), (14, 2));
+        assert_eq!(
+            labels[0].label(),
+            Some("list of values expected after this")
+        );
+
+        let report = error.into_report("app=web, tier in db");
+        assert!(report.source_code().is_some());
+        assert_eq!(
+            report.code().unwrap().to_string(),
+            "unselector::missing_values_list"
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use crate::{Expression, Expressions};
+
+/// Changes between two selectors by key, see [`Expressions::diff`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SelectorDiff {
+    /// Requirements on keys only the new selector refers to
+    pub added: Vec<Expression>,
+
+    /// Requirements on keys only the old selector refers to
+    pub removed: Vec<Expression>,
+
+    /// Keys both selectors refer to with requirements which are not
+    /// equivalent
+    pub changed: Vec<KeyChange>,
+}
+
+/// Requirements on a key before and after a change
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyChange {
+    /// Key the requirements apply to
+    pub key: String,
+
+    /// Requirements in the old selector
+    pub before: Expressions,
+
+    /// Requirements in the new selector
+    pub after: Expressions,
+}
+
+impl SelectorDiff {
+    /// Indicates whether both selectors place equivalent requirements on
+    /// every key
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// One line per change, prefixed with `+`, `-` or `~`
+impl fmt::Display for SelectorDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for expression in &self.removed {
+            writeln!(f, "- {expression}")?;
+        }
+        for change in &self.changed {
+            writeln!(f, "~ {} -> {}", change.before, change.after)?;
+        }
+        for expression in &self.added {
+            writeln!(f, "+ {expression}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Expressions {
+    /// Changes turning these expressions into the other ones, comparing the
+    /// requirements on each key semantically, so `a=b` and `a in (b)` are the
+    /// same
+    pub fn diff(&self, other: &Expressions) -> SelectorDiff {
+        let mut diff = SelectorDiff::default();
+        for key in self.keys() {
+            let before: Expressions = self.get(key).cloned().collect();
+            let after: Expressions = other.get(key).cloned().collect();
+            if after.is_empty() {
+                diff.removed.extend(before.iter().cloned());
+            } else if !before.equivalent(&after) {
+                diff.changed.push(KeyChange {
+                    key: key.into(),
+                    before,
+                    after,
+                });
+            }
+        }
+        for key in other.keys() {
+            if self.get(key).next().is_none() {
+                diff.added.extend(other.get(key).cloned());
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, Expressions, SelectorDiff};
+
+    fn diff(a: &str, b: &str) -> SelectorDiff {
+        Expressions::try_from(a)
+            .unwrap()
+            .diff(&Expressions::try_from(b).unwrap())
+    }
+
+    #[test]
+    fn changes() {
+        let changes = diff(
+            "app=web,!canary,tier in (db),env notin (dev)",
+            "tier=db,app in (web,api),team=a,env!=dev,env",
+        );
+        assert_eq!(changes.removed, [Expression::does_not_exist("canary")]);
+        assert_eq!(changes.added, [Expression::equal("team", "a")]);
+        assert_eq!(
+            changes.changed.iter().map(|c| &*c.key).collect::<Vec<_>>(),
+            ["app", "env"]
+        );
+        assert_eq!(
+            changes.to_string(),
+            concat!(
+                "- !canary\n",
+                "~ app=web -> app in (api,web)\n",
+                "~ env notin (dev) -> env!=dev,env\n",
+                "+ team=a\n",
+            )
+        );
+    }
+
+    #[test]
+    fn unchanged() {
+        let changes = diff("a=b,c,a in (b,d)", "c,a=b");
+        assert!(changes.is_empty(), "{changes}");
+        assert_eq!(changes.to_string(), "");
+    }
+}
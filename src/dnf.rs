@@ -0,0 +1,325 @@
+use std::fmt;
+use std::str::FromStr;
+
+use logos::Logos;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ConversionError, Dialect, Expression, Expressions, LabelSet, ParseError, ParseOptions,
+    ParsedExpression, Result, check_length, parse_expression, too_many_requirements,
+};
+
+/// Spelling of the selector without alternatives, which matches nothing
+const NOTHING: &str = "!()";
+
+/// Selector alternatives joined with `||`, matching if any of them matches.
+///
+/// This is an extension of the Kubernetes grammar, where each alternative is
+/// a regular comma separated selector. Without alternatives nothing matches,
+/// which is written as `!()` to tell it apart from the empty selector
+/// matching everything.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct SelectorDnf(Vec<Expressions>);
+
+impl SelectorDnf {
+    /// Combine the alternatives, of which at least one has to match
+    pub fn new(alternatives: impl IntoIterator<Item = Expressions>) -> Self {
+        Self(alternatives.into_iter().collect())
+    }
+
+    /// Parse alternatives in the given dialect
+    pub fn parse_with(dialect: impl Into<Dialect>, selector: &str) -> Result<Self> {
+        Self::parse_with_options(selector, dialect.into().options())
+    }
+
+    /// Parse alternatives, accepting the grammar extensions enabled in the options.
+    ///
+    /// The limits of the options apply to the whole selector, so
+    /// `max_requirements` counts the requirements of every alternative.
+    pub fn parse_with_options(selector: &str, options: ParseOptions) -> Result<Self> {
+        check_length(selector, &options)?;
+        if selector.trim() == NOTHING {
+            return Ok(Self(vec![]));
+        }
+        let mut lexer = ParsedExpression::lexer_with_extras(selector, options);
+        let mut alternatives = vec![];
+        let mut expressions = vec![];
+        let mut parsed = 0;
+        let mut or = None;
+        loop {
+            match parse_expression(&mut lexer) {
+                Ok(Some(expression)) => {
+                    if let Some(error) = too_many_requirements(&lexer, parsed) {
+                        return Err(error);
+                    }
+                    parsed += 1;
+                    expressions.push(expression);
+                }
+                Ok(None) => break,
+                Err(_) if lexer.slice() == "|" && lexer.remainder().starts_with('|') => {
+                    lexer.bump(1);
+                    // An empty alternative would match everything
+                    if expressions.is_empty() {
+                        return Err(ParseError::StringParse("||".into(), lexer.span()));
+                    }
+                    alternatives.push(Expressions(std::mem::take(&mut expressions)));
+                    or = Some(lexer.span());
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        if let (Some(span), true) = (or, expressions.is_empty()) {
+            return Err(ParseError::StringParse("||".into(), span));
+        }
+        alternatives.push(Expressions(expressions));
+
+        Ok(Self(alternatives))
+    }
+
+    /// Check whether any alternative matches the given labels
+    pub fn matches(&self, labels: &impl LabelSet) -> bool {
+        self.0.iter().any(|alternative| alternative.matches(labels))
+    }
+
+    /// Alternatives of the selector, in parse order
+    pub fn alternatives(&self) -> &[Expressions] {
+        &self.0
+    }
+
+    /// Unwrap the alternatives
+    pub fn into_alternatives(self) -> Vec<Expressions> {
+        self.0
+    }
+}
+
+impl Expressions {
+    /// Selector alternatives matching exactly the label sets these
+    /// expressions don't match, with one alternative per negated
+    /// requirement. The complement of the empty selector has no
+    /// alternatives and is written as `!()`.
+    ///
+    /// Integer comparisons and wildcard patterns have no negation in the
+    /// grammar, since a value which is not an integer fails both `>` and
+    /// `<`, so those are reported as unsupported.
+    pub fn complement(&self) -> std::result::Result<SelectorDnf, ConversionError> {
+        self.iter()
+            .map(|expression| negate(expression).map(Expressions::from))
+            .collect()
+    }
+}
+
+fn negate(expression: &Expression) -> std::result::Result<Expression, ConversionError> {
+    Ok(match expression {
+        Expression::In(key, values) => Expression::NotIn(key.clone(), values.clone()),
+        Expression::NotIn(key, values) => Expression::In(key.clone(), values.clone()),
+        Expression::Equal(key, value) => Expression::NotEqual(key.clone(), value.clone()),
+        Expression::NotEqual(key, value) => Expression::Equal(key.clone(), value.clone()),
+        Expression::Exists(key) => Expression::DoesNotExist(key.clone()),
+        Expression::DoesNotExist(key) => Expression::Exists(key.clone()),
+        #[cfg(feature = "regex")]
+        Expression::Matches(key, pattern) => Expression::NotMatches(key.clone(), pattern.clone()),
+        #[cfg(feature = "regex")]
+        Expression::NotMatches(key, pattern) => Expression::Matches(key.clone(), pattern.clone()),
+        _ => return Err(ConversionError::Unsupported(expression.clone().into())),
+    })
+}
+
+impl From<Expressions> for SelectorDnf {
+    fn from(expressions: Expressions) -> Self {
+        Self(vec![expressions])
+    }
+}
+
+impl FromIterator<Expressions> for SelectorDnf {
+    fn from_iter<T: IntoIterator<Item = Expressions>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl fmt::Display for SelectorDnf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut iter = self.0.iter();
+        let Some(first) = iter.next() else {
+            return f.write_str(NOTHING);
+        };
+        write!(f, "{first}")?;
+        for alternative in iter {
+            write!(f, " || {alternative}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for SelectorDnf {
+    type Error = ParseError;
+
+    fn try_from(selector: &str) -> Result<Self> {
+        Self::parse_with_options(selector, ParseOptions::strict())
+    }
+}
+
+impl FromStr for SelectorDnf {
+    type Err = ParseError;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::try_from(selector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        ConversionError, Expression, Expressions, Limit, ParseError, ParseOptions, SelectorDnf,
+    };
+
+    #[test]
+    fn parse() {
+        let dnf = SelectorDnf::try_from("app=web,!canary || app=api||tier in (a, b)").unwrap();
+        assert_eq!(
+            dnf.alternatives(),
+            [
+                Expressions::try_from("app=web,!canary").unwrap(),
+                Expressions::try_from("app=api").unwrap(),
+                Expressions::try_from("tier in (a,b)").unwrap(),
+            ]
+        );
+        assert_eq!(
+            dnf.to_string(),
+            "app=web,!canary || app=api || tier in (a,b)"
+        );
+        assert_eq!(dnf.to_string().parse(), Ok(dnf));
+
+        assert_eq!(
+            SelectorDnf::try_from("a").unwrap(),
+            Expressions::try_from("a").unwrap().into()
+        );
+        assert_eq!(
+            SelectorDnf::try_from("a | b"),
+            Err(ParseError::StringParse("|".into(), 2..3))
+        );
+        assert_eq!(
+            SelectorDnf::try_from("|| a"),
+            Err(ParseError::StringParse("||".into(), 0..2))
+        );
+        assert_eq!(
+            SelectorDnf::try_from("a ||, "),
+            Err(ParseError::StringParse("||".into(), 2..4))
+        );
+    }
+
+    #[test]
+    fn shared_checks() {
+        for selector in ["a=!b", "a In (b) || c", "c || a not in (b)"] {
+            let dnf = SelectorDnf::try_from(selector);
+            assert!(
+                matches!(dnf, Err(ParseError::MisspelledOperator(..))),
+                "{selector}: {dnf:?}"
+            );
+        }
+        assert_eq!(
+            SelectorDnf::try_from("a || =b"),
+            Err(ParseError::EmptyKey("=".into(), 5..6))
+        );
+
+        let limited = |options| SelectorDnf::parse_with_options("a,b,c in (x,y) || d,e,f", options);
+        assert_eq!(
+            limited(ParseOptions {
+                max_length: Some(10),
+                ..ParseOptions::strict()
+            }),
+            Err(ParseError::LimitExceeded(
+                String::new(),
+                10..23,
+                Limit::Length(10)
+            ))
+        );
+        assert_eq!(
+            limited(ParseOptions {
+                max_requirements: Some(5),
+                ..ParseOptions::strict()
+            }),
+            Err(ParseError::LimitExceeded(
+                "f".into(),
+                22..23,
+                Limit::Requirements(5)
+            ))
+        );
+        assert_eq!(
+            limited(ParseOptions {
+                max_values: Some(1),
+                ..ParseOptions::strict()
+            }),
+            Err(ParseError::LimitExceeded(
+                "c in (x,y)".into(),
+                4..14,
+                Limit::Values(1)
+            ))
+        );
+        assert!(
+            limited(ParseOptions {
+                max_length: Some(23),
+                max_requirements: Some(6),
+                max_values: Some(2),
+                ..ParseOptions::strict()
+            })
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn matches() {
+        let dnf = SelectorDnf::try_from("app=web,!canary || app=api").unwrap();
+        assert!(dnf.matches(&BTreeMap::from([("app", "web")])));
+        assert!(dnf.matches(&BTreeMap::from([("app", "api"), ("canary", "true")])));
+        assert!(!dnf.matches(&BTreeMap::from([("app", "web"), ("canary", "true")])));
+        assert!(!dnf.matches(&BTreeMap::from([("app", "db")])));
+        assert!(!SelectorDnf::new([]).matches(&BTreeMap::from([("app", "web")])));
+    }
+
+    #[test]
+    fn complement() {
+        let selector = Expressions::try_from("app in (web,api),!canary,tier!=db").unwrap();
+        let complement = selector.complement().unwrap();
+        assert_eq!(
+            complement.to_string(),
+            "app notin (api,web) || canary || tier=db"
+        );
+        for labels in [
+            BTreeMap::from([("app", "web")]),
+            BTreeMap::from([("app", "web"), ("canary", "true")]),
+            BTreeMap::from([("app", "db"), ("tier", "web")]),
+            BTreeMap::from([("app", "api"), ("tier", "db")]),
+            BTreeMap::new(),
+        ] {
+            assert_ne!(complement.matches(&labels), selector.matches(&labels));
+        }
+
+        let nothing = Expressions::default().complement().unwrap();
+        assert!(nothing.alternatives().is_empty());
+        assert!(!nothing.matches(&BTreeMap::<String, String>::new()));
+        assert_eq!(nothing.to_string(), "!()");
+        assert_eq!(nothing.to_string().parse(), Ok(nothing));
+        assert_eq!(
+            SelectorDnf::from(Expressions::default())
+                .to_string()
+                .parse(),
+            Ok(SelectorDnf::from(Expressions::default()))
+        );
+        assert_eq!(
+            SelectorDnf::try_from("a || !()"),
+            Err(ParseError::EmptyKey("!".into(), 5..6))
+        );
+
+        assert_eq!(
+            Expressions::try_from("a,b>1").unwrap().complement(),
+            Err(ConversionError::Unsupported(
+                Expression::greater_than("b", 1).into()
+            ))
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{Expression, Expressions, ParseError, Result, is_key};
+
+impl Expressions {
+    /// Parse a Docker label filter, such as `label=key=value` or `label!=key`
+    pub fn parse_docker(filter: &str) -> Result<Self> {
+        Self::parse_docker_filters([filter])
+    }
+
+    /// Parse repeated Docker label filters, as passed with `--filter`.
+    ///
+    /// Docker treats label values as opaque, so any value is accepted, while
+    /// keys must be valid label keys. Filters other than `label` are rejected.
+    pub fn parse_docker_filters<'a>(filters: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        filters.into_iter().map(parse_filter).collect()
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<Expression> {
+    let error = || ParseError::StringParse(filter.to_owned(), 0..filter.len());
+    let (not, requirement) = if let Some(requirement) = filter.strip_prefix("label!=") {
+        (true, requirement)
+    } else if let Some(requirement) = filter.strip_prefix("label=") {
+        (false, requirement)
+    } else {
+        return Err(error());
+    };
+
+    let (key, value) = match requirement.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (requirement, None),
+    };
+    if !is_key(key) {
+        return Err(error());
+    }
+
+    Ok(match (not, value) {
+        (false, None) => Expression::exists(key),
+        (true, None) => Expression::does_not_exist(key),
+        (false, Some(value)) => Expression::equal(key, value),
+        (true, Some(value)) => Expression::not_equal(key, value),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, Expressions, ParseError};
+
+    #[test]
+    fn filters() {
+        assert_eq!(
+            Expressions::parse_docker_filters([
+                "label=com.example.tier=web",
+                "label!=com.example.canary",
+                "label=release",
+                "label!=env=dev",
+                "label=version=1.0 beta",
+            ]),
+            Ok(Expression::equal("com.example.tier", "web")
+                .and(Expression::does_not_exist("com.example.canary"))
+                .and(Expression::exists("release"))
+                .and(Expression::not_equal("env", "dev"))
+                .and(Expression::equal("version", "1.0 beta")))
+        );
+        assert_eq!(
+            Expressions::parse_docker("label=a="),
+            Ok(Expression::equal("a", "").into())
+        );
+        assert_eq!(
+            Expressions::parse_docker_filters([]),
+            Ok(Expressions::default())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str| Err(ParseError::StringParse(text.into(), 0..text.len()));
+        assert_eq!(
+            Expressions::parse_docker("status=running"),
+            error("status=running")
+        );
+        assert_eq!(Expressions::parse_docker("label="), error("label="));
+        assert_eq!(Expressions::parse_docker("label=a b"), error("label=a b"));
+        assert_eq!(
+            Expressions::parse_docker_filters(["label=a", "labels=b"]),
+            error("labels=b")
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use crate::{
+    Dialect, Expressions, Limit, ParseError, ParseOptions, ParsedExpression, Result, check_length,
+};
+
+impl Expressions {
+    /// Parse a selector document, such as a config file holding one
+    /// requirement or selector per line, where `#` starts a comment
+    pub fn parse_document(document: &str) -> Result<Self> {
+        Self::parse_document_with(Dialect::Kubernetes, document)
+    }
+
+    /// Parse a selector document in the given dialect.
+    ///
+    /// The requirements of all lines are combined, and error spans refer to
+    /// positions in the whole document. Length and requirement limits apply
+    /// to the whole document.
+    pub fn parse_document_with(dialect: impl Into<Dialect>, document: &str) -> Result<Self> {
+        let options = dialect.into().options();
+        check_length(document, &options)?;
+        let line_options = ParseOptions {
+            max_length: None,
+            max_requirements: None,
+            ..options
+        };
+
+        let mut expressions = vec![];
+        let mut start = 0;
+        for line in document.split('\n') {
+            let selector = strip_comment(line).trim_end_matches('\r');
+            let parsed = Expressions::parse_spanned_with(line_options, selector)
+                .map_err(|error| error.offset(start))?;
+            for expression in parsed {
+                match options.max_requirements {
+                    Some(max) if expressions.len() >= max => {
+                        let span = expression.span.start + start..expression.span.end + start;
+                        return Err(ParseError::LimitExceeded(
+                            document[span.clone()].to_owned(),
+                            span,
+                            Limit::Requirements(max),
+                        ));
+                    }
+                    _ => expressions.push(ParsedExpression::Expression(expression.value)),
+                }
+            }
+            start += line.len() + 1;
+        }
+        Ok(Expressions(dedup(expressions)))
+    }
+}
+
+/// Drop repeated requirements, keeping the first of each, comparing only
+/// requirements on the same key
+fn dedup(expressions: Vec<ParsedExpression>) -> Vec<ParsedExpression> {
+    let mut kept: Vec<ParsedExpression> = Vec::with_capacity(expressions.len());
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for expression in expressions {
+        let same_key = by_key.entry(expression.key().to_owned()).or_default();
+        if same_key.iter().all(|&i| kept[i] != expression) {
+            same_key.push(kept.len());
+            kept.push(expression);
+        }
+    }
+    kept
+}
+
+/// Cut the line at the first `#` outside of quotes
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, _) if escaped => escaped = false,
+            (None, '\\') => escaped = true,
+            (None, '#') => return &line[..i],
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expressions, Limit, ParseError, ParseOptions};
+
+    #[test]
+    fn document() {
+        let document = "# workloads to gather\napp=web\r\ntier in (a, b), !canary # stable only\n\n  env!=dev\n";
+        assert_eq!(
+            Expressions::parse_document(document),
+            Ok(Expressions::try_from("app=web,tier in (a,b),!canary,env!=dev").unwrap())
+        );
+        assert_eq!(
+            Expressions::parse_document("# nothing\n"),
+            Ok(Expressions::default())
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        assert_eq!(
+            Expressions::parse_document("a=b,c\nc,a=b\na!=b\n"),
+            Ok(Expressions::try_from("a=b,c,a!=b").unwrap())
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let options = ParseOptions {
+            max_length: Some(12),
+            max_requirements: Some(3),
+            ..ParseOptions::strict()
+        };
+        assert_eq!(
+            Expressions::parse_document_with(options, "a\nb\nc\nd\n"),
+            Err(ParseError::LimitExceeded(
+                "d".into(),
+                6..7,
+                Limit::Requirements(3)
+            ))
+        );
+        assert_eq!(
+            Expressions::parse_document_with(options, "# comment\na,b\n"),
+            Err(ParseError::LimitExceeded(
+                String::new(),
+                12..14,
+                Limit::Length(12)
+            ))
+        );
+        assert_eq!(
+            Expressions::parse_document_with(options, "a\nb,a\nc"),
+            Err(ParseError::LimitExceeded(
+                "c".into(),
+                6..7,
+                Limit::Requirements(3)
+            ))
+        );
+        assert!(Expressions::parse_document_with(options, "a\nb\nc\n").is_ok());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Expressions::parse_document("app=web\n# comment\ntier in a\n"),
+            Err(ParseError::MissingValuesList("in".into(), 23..25))
+        );
+    }
+
+    #[test]
+    fn quoted_comments() {
+        use crate::{Dialect, Expression};
+
+        assert_eq!(
+            Expressions::parse_document_with(Dialect::Extended, "a='#1' # b\nc=\"x#\",d=e\\#f"),
+            Ok(Expression::equal("a", "#1")
+                .and(Expression::equal("c", "x#"))
+                .and(Expression::equal("d", "e#f")))
+        );
+    }
+}
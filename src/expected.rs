@@ -0,0 +1,148 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{ParseError, is_key_char};
+
+/// Kind of token the parser would have accepted where an error occurred
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Expected {
+    /// Label key starting a requirement
+    Key,
+
+    /// `!` negating an existence requirement
+    Not,
+
+    /// Operator following a key, such as `=` or `in`
+    Operator,
+
+    /// `(` opening a list of values
+    OpenParen,
+
+    /// Value following an operator or within a list of values
+    Value,
+
+    /// `)` closing a list of values
+    CloseParen,
+
+    /// `,` separating requirements or values
+    Comma,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Expected::Key => "a key",
+            Expected::Not => "'!'",
+            Expected::Operator => "an operator",
+            Expected::OpenParen => "'('",
+            Expected::Value => "a value",
+            Expected::CloseParen => "')'",
+            Expected::Comma => "','",
+        })
+    }
+}
+
+impl ParseError {
+    /// Tokens which would have been accepted at the error, derived from the
+    /// source preceding it
+    pub fn expected(&self, source: &str) -> Vec<Expected> {
+        match self {
+            ParseError::EmptyKey(..) => vec![Expected::Key],
+            ParseError::InvalidValue(..) => vec![Expected::Value],
+            ParseError::MissingValuesList(..) => vec![Expected::OpenParen],
+            ParseError::UnknownOperator(..) | ParseError::MisspelledOperator(..) => {
+                vec![Expected::Operator]
+            }
+            ParseError::LimitExceeded(..) | ParseError::UnresolvedVariable(..) => vec![],
+            ParseError::StringParse(..) | ParseError::UnbalancedParens(..) => {
+                let start = self.span().start.min(source.len());
+                expected_after(source.get(..start).unwrap_or_default())
+            }
+        }
+    }
+
+    /// Message of the error followed by the tokens which would have been
+    /// accepted, such as `..., expected a key or '!'`
+    pub fn hint(&self, source: &str) -> String {
+        let expected = self.expected(source);
+        let mut hint = self.to_string();
+        for (i, token) in expected.iter().enumerate() {
+            hint.push_str(match i {
+                0 => ", expected ",
+                _ if i + 1 == expected.len() => " or ",
+                _ => ", ",
+            });
+            hint.push_str(&token.to_string());
+        }
+        hint
+    }
+}
+
+/// Tokens accepted after the given part of a selector
+fn expected_after(before: &str) -> Vec<Expected> {
+    let requirement = before.rsplit(',').next().unwrap_or_default();
+    let open = requirement.rfind('(');
+    if open.is_some() && open > requirement.rfind(')') {
+        return vec![Expected::Value, Expected::Comma, Expected::CloseParen];
+    }
+
+    let trimmed = requirement.trim_end();
+    let keyword = trimmed
+        .rsplit(|c: char| !is_key_char(c))
+        .next()
+        .unwrap_or_default();
+    match trimmed.chars().next_back() {
+        None => vec![Expected::Key, Expected::Not],
+        Some('!') if trimmed.trim_start() == "!" => vec![Expected::Key],
+        Some('=' | '!' | '<' | '>' | '~') => vec![Expected::Value],
+        Some(')') => vec![Expected::Comma],
+        _ if trimmed.len() > keyword.len()
+            && (keyword.eq_ignore_ascii_case("in") || keyword.eq_ignore_ascii_case("notin")) =>
+        {
+            vec![Expected::OpenParen]
+        }
+        _ if trimmed.contains(['=', '<', '>', '~']) => vec![Expected::Comma],
+        _ => vec![Expected::Operator, Expected::Comma],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expected, Expressions};
+
+    #[test]
+    fn expected() {
+        for (selector, expected) in [
+            ("a=b, (", vec![Expected::Key, Expected::Not]),
+            ("!(", vec![Expected::Key]),
+            ("a=*", vec![Expected::Value]),
+            ("a in (b, *)", vec![Expected::Value]),
+            ("a in (b) (", vec![Expected::Comma]),
+            ("a=b *", vec![Expected::Comma]),
+            ("a *", vec![Expected::Operator, Expected::Comma]),
+            ("a in db", vec![Expected::OpenParen]),
+            ("=a", vec![Expected::Key]),
+        ] {
+            let error = Expressions::try_from(selector).unwrap_err();
+            assert_eq!(error.expected(selector), expected, "{selector}");
+        }
+    }
+
+    #[test]
+    fn hint() {
+        let selector = "app=web, (";
+        let error = Expressions::try_from(selector).unwrap_err();
+        assert_eq!(
+            error.hint(selector),
+            format!("{error}, expected a key or '!'")
+        );
+        assert_eq!(
+            super::expected_after("a in (b"),
+            [Expected::Value, Expected::Comma, Expected::CloseParen]
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{LabelSet, ParseError, Result};
+
+/// A field selector requirement, such as `status.phase!=Running`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FieldExpression {
+    /// Field is equal to the value
+    Equal(String, String),
+
+    /// Field is not equal to the value
+    NotEqual(String, String),
+}
+
+impl FieldExpression {
+    /// Dotted path of the field the requirement applies to
+    pub fn field(&self) -> &str {
+        match self {
+            FieldExpression::Equal(field, _) | FieldExpression::NotEqual(field, _) => field,
+        }
+    }
+
+    /// Check whether the requirement is satisfied by the given field values
+    pub fn matches(&self, fields: &impl LabelSet) -> bool {
+        match self {
+            FieldExpression::Equal(field, value) => fields.get(field) == Some(value),
+            FieldExpression::NotEqual(field, value) => fields.get(field) != Some(value),
+        }
+    }
+}
+
+impl fmt::Display for FieldExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldExpression::Equal(field, value) => write!(f, "{field}={}", escape(value)),
+            FieldExpression::NotEqual(field, value) => write!(f, "{field}!={}", escape(value)),
+        }
+    }
+}
+
+/// A field selector, as accepted by `kubectl --field-selector`.
+///
+/// Kept apart from label selectors, as fields only support equality and are
+/// addressed by dotted paths such as `metadata.name`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct FieldExpressions(Vec<FieldExpression>);
+
+impl FieldExpressions {
+    /// Number of requirements
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Indicates whether there are no requirements
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the requirements
+    pub fn iter(&self) -> std::slice::Iter<'_, FieldExpression> {
+        self.0.iter()
+    }
+
+    /// Check whether all requirements are satisfied by the given field values
+    pub fn matches(&self, fields: &impl LabelSet) -> bool {
+        self.0.iter().all(|expression| expression.matches(fields))
+    }
+}
+
+impl IntoIterator for FieldExpressions {
+    type Item = FieldExpression;
+    type IntoIter = std::vec::IntoIter<FieldExpression>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl FromIterator<FieldExpression> for FieldExpressions {
+    fn from_iter<T: IntoIterator<Item = FieldExpression>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl fmt::Display for FieldExpressions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut iter = self.0.iter();
+        if let Some(first) = iter.next() {
+            write!(f, "{first}")?;
+            for expression in iter {
+                write!(f, ",{expression}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for FieldExpressions {
+    type Error = ParseError;
+
+    fn try_from(selector: &str) -> Result<Self> {
+        let mut expressions = vec![];
+        let mut start = 0;
+        for end in separators(selector).chain([selector.len()]) {
+            let requirement = &selector[start..end];
+            if !requirement.trim().is_empty() {
+                expressions.push(
+                    parse_requirement(requirement).ok_or_else(|| {
+                        ParseError::StringParse(requirement.to_owned(), start..end)
+                    })?,
+                );
+            }
+            start = end + 1;
+        }
+
+        Ok(Self(expressions))
+    }
+}
+
+impl FromStr for FieldExpressions {
+    type Err = ParseError;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::try_from(selector)
+    }
+}
+
+/// Positions of commas which are not escaped
+fn separators(selector: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut escaped = false;
+    selector.char_indices().filter_map(move |(i, c)| {
+        let separator = c == ',' && !escaped;
+        escaped = c == '\\' && !escaped;
+        separator.then_some(i)
+    })
+}
+
+fn parse_requirement(requirement: &str) -> Option<FieldExpression> {
+    let split = requirement.find(['=', '!'])?;
+    let field = requirement[..split].trim();
+    let (not, rest) = match &requirement[split..] {
+        rest if rest.starts_with("!=") => (true, &rest[2..]),
+        rest if rest.starts_with("==") => (false, &rest[2..]),
+        rest if rest.starts_with('=') => (false, &rest[1..]),
+        _ => return None,
+    };
+
+    let valid = field.split('.').all(|part| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    });
+    if !valid {
+        return None;
+    }
+
+    let value = unescape(rest.trim())?;
+    Some(match not {
+        true => FieldExpression::NotEqual(field.to_owned(), value),
+        false => FieldExpression::Equal(field.to_owned(), value),
+    })
+}
+
+/// Resolve escaped separators, rejecting unescaped operators in the value
+fn unescape(value: &str) -> Option<String> {
+    let mut chars = value.chars();
+    let mut unescaped = String::with_capacity(value.len());
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => chars
+                .next()
+                .filter(|c| matches!(c, '\\' | ',' | '=' | '!'))?,
+            '=' | '!' => return None,
+            c => c,
+        });
+    }
+    Some(unescaped)
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ',' | '=' | '!') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{FieldExpression, FieldExpressions, ParseError};
+
+    #[test]
+    fn parse() {
+        let fields: FieldExpressions =
+            "metadata.name=foo, status.phase!=Running,spec.nodeName==,a=b\\,c"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            fields.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                FieldExpression::Equal("metadata.name".into(), "foo".into()),
+                FieldExpression::NotEqual("status.phase".into(), "Running".into()),
+                FieldExpression::Equal("spec.nodeName".into(), "".into()),
+                FieldExpression::Equal("a".into(), "b,c".into()),
+            ]
+        );
+        assert_eq!(
+            fields.to_string(),
+            "metadata.name=foo,status.phase!=Running,spec.nodeName=,a=b\\,c"
+        );
+        assert_eq!(fields.to_string().parse(), Ok(fields));
+        assert_eq!("".parse(), Ok(FieldExpressions::default()));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str, span| Err(ParseError::StringParse(text.into(), span));
+        assert_eq!(FieldExpressions::try_from("a=b,c"), error("c", 4..5));
+        assert_eq!(
+            FieldExpressions::try_from("a in (b)"),
+            error("a in (b)", 0..8)
+        );
+        assert_eq!(FieldExpressions::try_from("a..b=c"), error("a..b=c", 0..6));
+        assert_eq!(FieldExpressions::try_from("!a"), error("!a", 0..2));
+        assert_eq!(FieldExpressions::try_from("a=b=c"), error("a=b=c", 0..5));
+    }
+
+    #[test]
+    fn matches() {
+        let fields: FieldExpressions = "metadata.name=web,status.phase!=Failed".parse().unwrap();
+        assert!(fields.matches(&BTreeMap::from([
+            ("metadata.name", "web"),
+            ("status.phase", "Running")
+        ])));
+        assert!(!fields.matches(&BTreeMap::from([
+            ("metadata.name", "web"),
+            ("status.phase", "Failed")
+        ])));
+    }
+}
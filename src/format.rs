@@ -0,0 +1,110 @@
+use crate::{Expression, Expressions, escape_key};
+
+/// Style of selector strings written by [`Formatter::format`].
+///
+/// The default style is the compact one [`Expressions`] display in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Formatter {
+    /// Surround binary operators with spaces, such as `app = web`
+    pub spaces_around_operators: bool,
+
+    /// Write requirements in canonical order, without duplicates
+    pub sort: bool,
+
+    /// Start every requirement on a new line
+    pub one_per_line: bool,
+
+    /// End the last requirement with a comma
+    pub trailing_comma: bool,
+}
+
+impl Formatter {
+    /// Style for selectors kept in config files, with one sorted requirement
+    /// per line
+    pub fn multiline() -> Self {
+        Formatter {
+            spaces_around_operators: true,
+            sort: true,
+            one_per_line: true,
+            trailing_comma: true,
+        }
+    }
+
+    /// Write the expressions as a selector string in this style, which
+    /// parses back into the same requirements
+    pub fn format(&self, expressions: &Expressions) -> String {
+        let sorted;
+        let expressions = match self.sort {
+            true => {
+                sorted = expressions.to_canonical();
+                &sorted
+            }
+            false => expressions,
+        };
+        let separator = if self.one_per_line { ",\n" } else { "," };
+        let mut selector = expressions
+            .iter()
+            .map(|expression| self.requirement(expression))
+            .collect::<Vec<_>>()
+            .join(separator);
+        if self.trailing_comma && !expressions.is_empty() {
+            selector.push(',');
+        }
+        selector
+    }
+
+    fn requirement(&self, expression: &Expression) -> String {
+        let display = expression.to_string();
+        let operator = match expression {
+            _ if !self.spaces_around_operators => return display,
+            Expression::Equal(..) => "=",
+            Expression::NotEqual(..) => "!=",
+            Expression::GreaterThan(..) => ">",
+            Expression::LessThan(..) => "<",
+            #[cfg(feature = "regex")]
+            Expression::Matches(..) => "=~",
+            #[cfg(feature = "regex")]
+            Expression::NotMatches(..) => "!~",
+            #[cfg(feature = "extended")]
+            Expression::Glob(..) => "~",
+            Expression::In(..)
+            | Expression::NotIn(..)
+            | Expression::Exists(_)
+            | Expression::DoesNotExist(_) => return display,
+        };
+        let (key, rest) = display.split_at(escape_key(expression.key()).len());
+        format!("{key} {operator} {}", &rest[operator.len()..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expressions, Formatter};
+
+    #[test]
+    fn styles() {
+        let expressions =
+            Expressions::try_from("tier in (db,web),app=web,!canary,n>1,app=web").unwrap();
+        assert_eq!(
+            Formatter::default().format(&expressions),
+            expressions.to_string()
+        );
+
+        let formatted = Formatter::multiline().format(&expressions);
+        assert_eq!(formatted, "app = web,\n!canary,\nn > 1,\ntier in (db,web),");
+        assert_eq!(
+            Expressions::try_from(formatted.as_str()).unwrap(),
+            expressions.to_canonical()
+        );
+
+        let formatter = Formatter {
+            spaces_around_operators: true,
+            ..Formatter::default()
+        };
+        assert_eq!(
+            formatter.format(&Expressions::try_from("a!=b,c").unwrap()),
+            "a != b,c"
+        );
+        assert_eq!(Formatter::multiline().format(&Expressions::default()), "");
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use globset::{Glob, GlobMatcher};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A shell-style wildcard pattern which has to match label values as a whole.
+///
+/// Patterns compare and serialize by their source.
+#[derive(Clone, Debug)]
+pub struct GlobPattern {
+    source: String,
+    matcher: GlobMatcher,
+}
+
+impl GlobPattern {
+    /// Compile the wildcard pattern
+    pub fn new(source: impl Into<String>) -> Result<Self, globset::Error> {
+        let source = source.into();
+        let matcher = Glob::new(&source)?.compile_matcher();
+        Ok(GlobPattern { source, matcher })
+    }
+
+    /// Check whether the value matches the pattern
+    pub fn is_match(&self, value: &str) -> bool {
+        self.matcher.is_match(value)
+    }
+
+    /// Source the pattern was compiled from
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl PartialEq for GlobPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for GlobPattern {}
+
+impl Hash for GlobPattern {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+    }
+}
+
+impl fmt::Display for GlobPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl TryFrom<&str> for GlobPattern {
+    type Error = globset::Error;
+
+    fn try_from(source: &str) -> Result<Self, globset::Error> {
+        GlobPattern::new(source)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for GlobPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for GlobPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        GlobPattern::new(source).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for GlobPattern {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "GlobPattern".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "Wildcard pattern matching whole values",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlobPattern;
+
+    #[test]
+    fn wildcards() {
+        let pattern = GlobPattern::new("web-*").unwrap();
+        assert!(pattern.is_match("web-1"));
+        assert!(pattern.is_match("web-"));
+        assert!(!pattern.is_match("a-web-1"));
+        assert!(GlobPattern::new("v1.[0-9]?").unwrap().is_match("v1.2a"));
+        assert!(GlobPattern::new("[").is_err());
+    }
+}
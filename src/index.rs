@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::{Expressions, LabelSet, Matcher, requirements};
+
+/// An index over many selectors, answering which of them match a label set.
+///
+/// Every selector is anchored on one requirement a matching label set must
+/// satisfy positively: a set of allowed values for a key, or just an existing
+/// key. A query only verifies selectors anchored on labels present in the
+/// label set, plus the selectors with no such requirement at all, such as
+/// `!canary` or the empty selector.
+#[derive(Clone, Debug)]
+pub struct SelectorIndex<Id> {
+    selectors: HashMap<Id, Entry>,
+    by_value: HashMap<String, HashMap<String, HashSet<Id>>>,
+    by_key: HashMap<String, HashSet<Id>>,
+    unanchored: HashSet<Id>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    expressions: Expressions,
+    matcher: Matcher,
+    anchor: Anchor,
+}
+
+#[derive(Clone, Debug)]
+enum Anchor {
+    /// Key must be set to one of the values
+    Values(String, Vec<String>),
+
+    /// Key must be set
+    Key(String),
+
+    /// No positive requirement
+    None,
+
+    /// Selector can't match anything
+    Never,
+}
+
+impl Anchor {
+    fn of(expressions: &Expressions) -> Self {
+        let keys = requirements::by_key(expressions.iter());
+        if keys.values().any(|key| !key.satisfiable()) {
+            return Anchor::Never;
+        }
+
+        let values = keys
+            .iter()
+            .filter_map(|(key, requirements)| Some((key, requirements.permitted()?)))
+            .min_by_key(|(_, permitted)| permitted.len());
+        if let Some((key, permitted)) = values {
+            return Anchor::Values(
+                key.to_string(),
+                permitted.into_iter().map(str::to_owned).collect(),
+            );
+        }
+
+        match keys.iter().find(|(_, requirements)| requirements.exists) {
+            Some((key, _)) => Anchor::Key(key.to_string()),
+            None => Anchor::None,
+        }
+    }
+}
+
+impl<Id> Default for SelectorIndex<Id> {
+    fn default() -> Self {
+        Self {
+            selectors: HashMap::default(),
+            by_value: HashMap::default(),
+            by_key: HashMap::default(),
+            unanchored: HashSet::default(),
+        }
+    }
+}
+
+impl<Id: Clone + Eq + Hash> SelectorIndex<Id> {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of indexed selectors
+    pub fn len(&self) -> usize {
+        self.selectors.len()
+    }
+
+    /// Indicates whether the index holds no selectors
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
+
+    /// Get the selector inserted under the id
+    pub fn get(&self, id: &Id) -> Option<&Expressions> {
+        self.selectors.get(id).map(|entry| &entry.expressions)
+    }
+
+    /// Insert a selector, returning the one previously stored under the id
+    pub fn insert(&mut self, id: Id, expressions: Expressions) -> Option<Expressions> {
+        let previous = self.remove(&id);
+
+        let anchor = Anchor::of(&expressions);
+        match &anchor {
+            Anchor::Values(key, values) => {
+                let by_value = self.by_value.entry(key.clone()).or_default();
+                for value in values {
+                    by_value
+                        .entry(value.clone())
+                        .or_default()
+                        .insert(id.clone());
+                }
+            }
+            Anchor::Key(key) => {
+                self.by_key
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(id.clone());
+            }
+            Anchor::None => {
+                self.unanchored.insert(id.clone());
+            }
+            Anchor::Never => {}
+        }
+
+        let matcher = expressions.compile();
+        self.selectors.insert(
+            id,
+            Entry {
+                expressions,
+                matcher,
+                anchor,
+            },
+        );
+
+        previous
+    }
+
+    /// Remove the selector stored under the id
+    pub fn remove(&mut self, id: &Id) -> Option<Expressions> {
+        let entry = self.selectors.remove(id)?;
+        match &entry.anchor {
+            Anchor::Values(key, values) => {
+                if let Some(by_value) = self.by_value.get_mut(key) {
+                    for value in values {
+                        if let Some(ids) = by_value.get_mut(value) {
+                            ids.remove(id);
+                            if ids.is_empty() {
+                                by_value.remove(value);
+                            }
+                        }
+                    }
+                    if by_value.is_empty() {
+                        self.by_value.remove(key);
+                    }
+                }
+            }
+            Anchor::Key(key) => {
+                if let Some(ids) = self.by_key.get_mut(key) {
+                    ids.remove(id);
+                    if ids.is_empty() {
+                        self.by_key.remove(key);
+                    }
+                }
+            }
+            Anchor::None => {
+                self.unanchored.remove(id);
+            }
+            Anchor::Never => {}
+        }
+
+        Some(entry.expressions)
+    }
+
+    /// Ids of all selectors matching the labels, in no particular order
+    pub fn query(&self, labels: &impl LabelSet) -> Vec<&Id> {
+        let anchored = labels.iter().flat_map(|(key, value)| {
+            let by_value = self.by_value.get(key).and_then(|values| values.get(value));
+            by_value.into_iter().chain(self.by_key.get(key)).flatten()
+        });
+
+        anchored
+            .chain(&self.unanchored)
+            .filter_map(|id| self.selectors.get_key_value(id))
+            .filter(|(_, entry)| entry.matcher.matches(labels))
+            .map(|(id, _)| id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::Expressions;
+
+    use super::SelectorIndex;
+
+    fn index(selectors: &[(&'static str, &str)]) -> SelectorIndex<&'static str> {
+        let mut index = SelectorIndex::new();
+        for (id, selector) in selectors {
+            index.insert(*id, Expressions::try_from(*selector).unwrap());
+        }
+        index
+    }
+
+    fn query(index: &SelectorIndex<&'static str>, labels: &[(&str, &str)]) -> Vec<&'static str> {
+        let labels: BTreeMap<_, _> = labels.iter().copied().collect();
+        let mut ids: Vec<_> = index.query(&labels).into_iter().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn query_matching_selectors() {
+        let index = index(&[
+            ("all", ""),
+            ("web", "app=web"),
+            ("web-prod", "app=web,env in (prod,stage)"),
+            ("has-team", "team"),
+            ("not-canary", "!canary"),
+            ("not-dev", "env notin (dev)"),
+            ("never", "app=web,app=api"),
+        ]);
+
+        assert_eq!(query(&index, &[]), ["all", "not-canary", "not-dev"]);
+        assert_eq!(
+            query(&index, &[("app", "web"), ("env", "prod")]),
+            ["all", "not-canary", "not-dev", "web", "web-prod"]
+        );
+        assert_eq!(
+            query(&index, &[("app", "web"), ("env", "dev"), ("team", "a")]),
+            ["all", "has-team", "not-canary", "web"]
+        );
+        assert_eq!(
+            query(&index, &[("app", "api"), ("canary", "true")]),
+            ["all", "not-dev"]
+        );
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut index = index(&[("web", "app=web"), ("team", "team")]);
+        assert_eq!(index.len(), 2);
+
+        let previous = index.insert("web", Expressions::try_from("app=api").unwrap());
+        assert_eq!(previous, Some(Expressions::try_from("app=web").unwrap()));
+        assert_eq!(query(&index, &[("app", "web")]), Vec::<&str>::new());
+        assert_eq!(query(&index, &[("app", "api")]), ["web"]);
+
+        assert!(index.remove(&"team").is_some());
+        assert!(index.remove(&"team").is_none());
+        assert_eq!(query(&index, &[("team", "a")]), Vec::<&str>::new());
+
+        index.remove(&"web");
+        assert!(index.is_empty());
+        assert!(index.by_value.is_empty());
+        assert!(index.by_key.is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{Dialect, Expressions, Matcher, Result};
+
+/// Key cache of compiled matchers, so [`Matcher`]s compiled by long-running
+/// processes for many selectors keep one copy of each key.
+///
+/// Only matchers share the interned keys, parsed [`Expressions`] own theirs.
+///
+/// Cloning the interner shares the storage, so a handle can be passed to
+/// every thread compiling selectors. Keys stay stored until removed with
+/// [`Interner::retain`] or [`Interner::clear`].
+#[derive(Clone, Debug, Default)]
+pub struct Interner(Arc<Mutex<HashSet<Arc<str>>>>);
+
+impl Interner {
+    /// Create an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shared copy of the key, stored on first use
+    pub fn intern(&self, key: &str) -> Arc<str> {
+        let mut keys = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(key) = keys.get(key) {
+            return key.clone();
+        }
+        let key: Arc<str> = key.into();
+        keys.insert(key.clone());
+        key
+    }
+
+    /// Number of distinct keys stored
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Indicates whether no key was stored yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keep only the keys for which the predicate holds.
+    ///
+    /// Matchers keep their copy of removed keys, while matchers compiled
+    /// later store a new one. Keys used by no matcher are dropped with
+    /// `interner.retain(|key| Arc::strong_count(key) > 1)`.
+    pub fn retain(&self, f: impl FnMut(&Arc<str>) -> bool) {
+        (self.0.lock().unwrap_or_else(PoisonError::into_inner)).retain(f);
+    }
+
+    /// Remove all keys, see [`Interner::retain`]
+    pub fn clear(&self) {
+        self.retain(|_| false);
+    }
+
+    /// Parse a selector in the given dialect and compile it with interned
+    /// keys
+    pub fn compile(&self, dialect: impl Into<Dialect>, selector: &str) -> Result<Matcher> {
+        Expressions::parse_with(dialect, selector).map(|expressions| expressions.compile_with(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use crate::{Dialect, Interner, Matcher};
+
+    #[test]
+    fn shared_keys() {
+        let interner = Interner::new();
+        let a = interner
+            .compile(Dialect::Kubernetes, "app=web,tier")
+            .unwrap();
+        let b = interner
+            .clone()
+            .compile(Dialect::Kubernetes, "app in (api)")
+            .unwrap();
+        assert_eq!(interner.len(), 2);
+
+        let key = |matcher: &Matcher| matcher.keys().find(|key| *key == "app").unwrap().as_ptr();
+        assert_eq!(key(&a), key(&b));
+        assert!(a.matches(&BTreeMap::from([("app", "web"), ("tier", "1")])));
+        assert!(!b.matches(&BTreeMap::from([("app", "web")])));
+
+        assert!(interner.compile(Dialect::Kubernetes, "a=(").is_err());
+        assert_eq!(interner.len(), 2);
+
+        drop(a);
+        interner.retain(|key| Arc::strong_count(key) > 1);
+        assert_eq!(interner.len(), 1);
+        let c = interner.compile(Dialect::Kubernetes, "app").unwrap();
+        assert_eq!(key(&b), key(&c));
+
+        interner.clear();
+        assert!(interner.is_empty());
+        assert!(b.matches(&BTreeMap::from([("app", "api")])));
+    }
+}
@@ -0,0 +1,463 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{ConversionError, Expression, Expressions, ParsedExpression};
+
+/// A label selector as found in Kubernetes manifests
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct LabelSelector {
+    /// Labels which have to be set to the exact values
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub match_labels: BTreeMap<String, String>,
+
+    /// Requirements which all have to match
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub match_expressions: Vec<LabelSelectorRequirement>,
+}
+
+/// A set based requirement of a [`LabelSelector`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct LabelSelectorRequirement {
+    /// Key the requirement applies to
+    pub key: String,
+
+    /// One of `In`, `NotIn`, `Exists` or `DoesNotExist`
+    pub operator: String,
+
+    /// Values for `In` and `NotIn`, which must be empty otherwise
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub values: Vec<String>,
+}
+
+/// A selector written either as a string or as a [`LabelSelector`] object,
+/// for configuration accepting both shapes
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum AnySelector {
+    /// Selector string, such as `app=web,!canary`
+    String(
+        #[serde(with = "crate::serde_string")]
+        #[cfg_attr(
+            feature = "schemars",
+            schemars(schema_with = "crate::serde_string::json_schema")
+        )]
+        Expressions,
+    ),
+
+    /// Object with `matchLabels` and `matchExpressions`
+    LabelSelector(LabelSelector),
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AnySelector> for Expressions {
+    type Error = ConversionError;
+
+    fn try_from(selector: AnySelector) -> Result<Self, ConversionError> {
+        match selector {
+            AnySelector::String(expressions) => Ok(expressions),
+            AnySelector::LabelSelector(selector) => selector.try_into(),
+        }
+    }
+}
+
+impl LabelSelectorRequirement {
+    fn new(key: String, operator: &str, values: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            key,
+            operator: operator.into(),
+            values: values.into_iter().collect(),
+        }
+    }
+}
+
+/// Written as the key and operator, followed by any values in parentheses
+impl fmt::Display for LabelSelectorRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.key, self.operator)?;
+        if !self.values.is_empty() {
+            write!(f, " ({})", self.values.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<LabelSelectorRequirement> for Expression {
+    type Error = ConversionError;
+
+    fn try_from(requirement: LabelSelectorRequirement) -> Result<Self, ConversionError> {
+        let LabelSelectorRequirement {
+            key,
+            operator,
+            values,
+        } = &requirement;
+        Ok(match (operator.as_str(), values.is_empty()) {
+            _ if key.is_empty() => {
+                return Err(ConversionError::InvalidRequirement(requirement.into()));
+            }
+            ("In", false) => Expression::r#in(key, values),
+            ("NotIn", false) => Expression::not_in(key, values),
+            ("Exists", true) => Expression::exists(key),
+            ("DoesNotExist", true) => Expression::does_not_exist(key),
+            _ => return Err(ConversionError::InvalidRequirement(requirement.into())),
+        })
+    }
+}
+
+/// Equality requirements become single valued `In` and `NotIn` requirements
+impl TryFrom<Expression> for LabelSelectorRequirement {
+    type Error = ConversionError;
+
+    fn try_from(expression: Expression) -> Result<Self, ConversionError> {
+        Ok(match expression {
+            Expression::Equal(key, value) => LabelSelectorRequirement::new(key, "In", [value]),
+            Expression::NotEqual(key, value) => {
+                LabelSelectorRequirement::new(key, "NotIn", [value])
+            }
+            Expression::In(key, values) => LabelSelectorRequirement::new(key, "In", values),
+            Expression::NotIn(key, values) => LabelSelectorRequirement::new(key, "NotIn", values),
+            Expression::Exists(key) => LabelSelectorRequirement::new(key, "Exists", []),
+            Expression::DoesNotExist(key) => LabelSelectorRequirement::new(key, "DoesNotExist", []),
+            expression => return Err(ConversionError::Unsupported(expression.into())),
+        })
+    }
+}
+
+impl TryFrom<LabelSelector> for Expressions {
+    type Error = ConversionError;
+
+    fn try_from(selector: LabelSelector) -> Result<Self, ConversionError> {
+        let mut expressions = Expressions::from_match_labels(&selector.match_labels);
+        for requirement in selector.match_expressions {
+            expressions.push(requirement.try_into()?);
+        }
+        Ok(expressions)
+    }
+}
+
+impl TryFrom<Expressions> for LabelSelector {
+    type Error = ConversionError;
+
+    /// Split into exact labels and set based requirements, where repeated
+    /// equality requirements on a key are kept as requirements
+    fn try_from(expressions: Expressions) -> Result<Self, ConversionError> {
+        let mut selector = LabelSelector::default();
+        for ParsedExpression::Expression(expression) in expressions {
+            let requirement = match expression {
+                Expression::Equal(key, value) => match selector.match_labels.entry(key) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(value);
+                        continue;
+                    }
+                    Entry::Occupied(entry) => {
+                        LabelSelectorRequirement::new(entry.key().clone(), "In", [value])
+                    }
+                },
+                expression => expression.try_into()?,
+            };
+            selector.match_expressions.push(requirement);
+        }
+        Ok(selector)
+    }
+}
+
+#[cfg(feature = "k8s-openapi")]
+mod openapi {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
+
+    use super::{LabelSelector, LabelSelectorRequirement};
+    use crate::{ConversionError, Expressions};
+
+    impl From<meta::LabelSelector> for LabelSelector {
+        fn from(selector: meta::LabelSelector) -> Self {
+            Self {
+                match_labels: selector.match_labels.unwrap_or_default(),
+                match_expressions: selector
+                    .match_expressions
+                    .into_iter()
+                    .flatten()
+                    .map(|requirement| LabelSelectorRequirement {
+                        key: requirement.key,
+                        operator: requirement.operator,
+                        values: requirement.values.unwrap_or_default(),
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    /// Empty parts are left unset, as the API server does for `Exists` and
+    /// `DoesNotExist` values
+    impl From<LabelSelector> for meta::LabelSelector {
+        fn from(selector: LabelSelector) -> Self {
+            let requirements = selector.match_expressions.into_iter().map(|requirement| {
+                meta::LabelSelectorRequirement {
+                    key: requirement.key,
+                    operator: requirement.operator,
+                    values: (!requirement.values.is_empty()).then_some(requirement.values),
+                }
+            });
+            Self {
+                match_labels: (!selector.match_labels.is_empty()).then_some(selector.match_labels),
+                match_expressions: Some(requirements.collect::<Vec<_>>())
+                    .filter(|requirements| !requirements.is_empty()),
+            }
+        }
+    }
+
+    impl TryFrom<meta::LabelSelector> for Expressions {
+        type Error = ConversionError;
+
+        fn try_from(selector: meta::LabelSelector) -> Result<Self, ConversionError> {
+            LabelSelector::from(selector).try_into()
+        }
+    }
+
+    impl TryFrom<Expressions> for meta::LabelSelector {
+        type Error = ConversionError;
+
+        fn try_from(expressions: Expressions) -> Result<Self, ConversionError> {
+            Ok(LabelSelector::try_from(expressions)?.into())
+        }
+    }
+}
+
+#[cfg(feature = "k8s-pb")]
+mod protobuf {
+    use k8s_pb::apimachinery::pkg::apis::meta::v1 as meta;
+
+    use super::{LabelSelector, LabelSelectorRequirement};
+    use crate::{ConversionError, Expressions};
+
+    /// Unset keys and operators are read as empty, which fails conversion
+    /// into expressions
+    impl From<meta::LabelSelector> for LabelSelector {
+        fn from(selector: meta::LabelSelector) -> Self {
+            Self {
+                match_labels: selector.match_labels,
+                match_expressions: selector
+                    .match_expressions
+                    .into_iter()
+                    .map(|requirement| LabelSelectorRequirement {
+                        key: requirement.key.unwrap_or_default(),
+                        operator: requirement.operator.unwrap_or_default(),
+                        values: requirement.values,
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<LabelSelector> for meta::LabelSelector {
+        fn from(selector: LabelSelector) -> Self {
+            Self {
+                match_labels: selector.match_labels,
+                match_expressions: selector
+                    .match_expressions
+                    .into_iter()
+                    .map(|requirement| meta::LabelSelectorRequirement {
+                        key: Some(requirement.key),
+                        operator: Some(requirement.operator),
+                        values: requirement.values,
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl TryFrom<meta::LabelSelector> for Expressions {
+        type Error = ConversionError;
+
+        fn try_from(selector: meta::LabelSelector) -> Result<Self, ConversionError> {
+            LabelSelector::from(selector).try_into()
+        }
+    }
+
+    impl TryFrom<Expressions> for meta::LabelSelector {
+        type Error = ConversionError;
+
+        fn try_from(expressions: Expressions) -> Result<Self, ConversionError> {
+            Ok(LabelSelector::try_from(expressions)?.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ConversionError, Expression, Expressions, LabelSelector, LabelSelectorRequirement,
+    };
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize() {
+        let selector: LabelSelector = serde_json::from_str(
+            r#"{
+                "matchLabels": {"app": "web"},
+                "matchExpressions": [
+                    {"key": "env", "operator": "In", "values": ["prod", "stage"]},
+                    {"key": "canary", "operator": "DoesNotExist"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Expressions::try_from(selector.clone()),
+            Ok(Expressions::try_from("app=web,env in (prod,stage),!canary").unwrap())
+        );
+        assert_eq!(
+            serde_json::to_string(&LabelSelector::default()).unwrap(),
+            "{}"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn any_selector() {
+        use crate::AnySelector;
+
+        let expected = Expressions::try_from("app=web,!canary").unwrap();
+        for selector in [
+            r#""app=web, !canary""#,
+            r#"{"matchLabels": {"app": "web"},
+                "matchExpressions": [{"key": "canary", "operator": "DoesNotExist"}]}"#,
+        ] {
+            let selector: AnySelector = serde_json::from_str(selector).unwrap();
+            assert_eq!(Expressions::try_from(selector), Ok(expected.clone()));
+        }
+        assert!(serde_json::from_str::<AnySelector>(r#""a in b""#).is_err());
+        assert_eq!(
+            serde_json::to_string(&AnySelector::String(expected)).unwrap(),
+            r#""app=web,!canary""#
+        );
+    }
+
+    #[test]
+    fn split() {
+        let expressions = Expressions::try_from("app=web,app=api,tier!=db,team").unwrap();
+        let selector = LabelSelector::try_from(expressions.clone()).unwrap();
+        assert_eq!(selector.match_labels.len(), 1);
+        assert_eq!(
+            selector.match_expressions,
+            [
+                LabelSelectorRequirement::new("app".into(), "In", ["api".into()]),
+                LabelSelectorRequirement::new("tier".into(), "NotIn", ["db".into()]),
+                LabelSelectorRequirement::new("team".into(), "Exists", []),
+            ]
+        );
+        assert_eq!(
+            Expressions::try_from(selector).unwrap().to_string(),
+            "app=web,app in (api),tier notin (db),team"
+        );
+
+        assert_eq!(
+            LabelSelector::try_from(Expressions::from(Expression::greater_than("a", 1))),
+            Err(ConversionError::Unsupported(
+                Expression::greater_than("a", 1).into()
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_requirements() {
+        for (operator, values, message) in [
+            ("In", vec![], "'a In'"),
+            ("Exists", vec!["b".into(), "c".into()], "'a Exists (b,c)'"),
+            ("Gt", vec![], "'a Gt'"),
+        ] {
+            let requirement = LabelSelectorRequirement::new("a".into(), operator, values);
+            let error = Expression::try_from(requirement.clone());
+            assert_eq!(
+                error,
+                Err(ConversionError::InvalidRequirement(requirement.into()))
+            );
+            assert_eq!(
+                error.unwrap_err().to_string(),
+                format!("label selector requirement is invalid: {message}")
+            );
+        }
+    }
+
+    #[cfg(feature = "k8s-openapi")]
+    #[test]
+    fn openapi() {
+        use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
+
+        let requirement =
+            |key: &str, operator: &str, values: Option<Vec<&str>>| meta::LabelSelectorRequirement {
+                key: key.into(),
+                operator: operator.into(),
+                values: values.map(|values| values.into_iter().map(Into::into).collect()),
+            };
+        let selector = meta::LabelSelector {
+            match_labels: Some([("app".into(), "web".into())].into()),
+            match_expressions: Some(vec![
+                requirement("env", "In", Some(vec!["a", "b"])),
+                requirement("team", "Exists", None),
+                requirement("canary", "DoesNotExist", Some(vec![])),
+            ]),
+        };
+        let expressions = Expressions::try_from(selector).unwrap();
+        assert_eq!(expressions.to_string(), "app=web,env in (a,b),team,!canary");
+
+        let selector = meta::LabelSelector::try_from(expressions.clone()).unwrap();
+        assert_eq!(
+            selector.match_expressions.as_ref().unwrap()[2],
+            requirement("canary", "DoesNotExist", None)
+        );
+        assert_eq!(Expressions::try_from(selector), Ok(expressions));
+        assert_eq!(
+            meta::LabelSelector::try_from(Expressions::default()),
+            Ok(meta::LabelSelector::default())
+        );
+    }
+
+    #[cfg(feature = "k8s-pb")]
+    #[test]
+    fn protobuf() {
+        use k8s_pb::apimachinery::pkg::apis::meta::v1 as meta;
+        use prost::Message;
+
+        let expressions = Expressions::try_from("app=web,env notin (dev),!canary").unwrap();
+        let encoded = meta::LabelSelector::try_from(expressions.clone())
+            .unwrap()
+            .encode_to_vec();
+        let decoded = meta::LabelSelector::decode(encoded.as_slice()).unwrap();
+        assert_eq!(Expressions::try_from(decoded), Ok(expressions));
+
+        let unkeyed = meta::LabelSelector {
+            match_expressions: vec![meta::LabelSelectorRequirement {
+                operator: Some("Exists".into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            Expressions::try_from(unkeyed),
+            Err(ConversionError::InvalidRequirement(
+                LabelSelectorRequirement::new("".into(), "Exists", []).into()
+            ))
+        );
+    }
+}
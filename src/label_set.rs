@@ -0,0 +1,85 @@
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+/// A set of labels a selector can be matched against.
+///
+/// Implemented for the std map types keyed and valued by anything string-like,
+/// so `BTreeMap<String, String>`, `HashMap<&str, &str>` and friends can be
+/// matched without copying.
+pub trait LabelSet {
+    /// Get the value of a label, if it is set
+    fn get(&self, key: &str) -> Option<&str>;
+
+    /// Iterate over all labels in the set
+    fn iter(&self) -> impl Iterator<Item = (&str, &str)>;
+
+    /// Check whether a label is set
+    fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<T: LabelSet + ?Sized> LabelSet for &T {
+    fn get(&self, key: &str) -> Option<&str> {
+        (**self).get(key)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        (**self).iter()
+    }
+
+    fn contains_key(&self, key: &str) -> bool {
+        (**self).contains_key(key)
+    }
+}
+
+impl<K, V> LabelSet for BTreeMap<K, V>
+where
+    K: Borrow<str> + Ord,
+    V: AsRef<str>,
+{
+    fn get(&self, key: &str) -> Option<&str> {
+        BTreeMap::get(self, key).map(AsRef::as_ref)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        BTreeMap::iter(self).map(|(k, v)| (k.borrow(), v.as_ref()))
+    }
+}
+
+impl<K, V, S> LabelSet for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<str>,
+    S: BuildHasher,
+{
+    fn get(&self, key: &str) -> Option<&str> {
+        HashMap::get(self, key).map(AsRef::as_ref)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        HashMap::iter(self).map(|(k, v)| (k.borrow(), v.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::LabelSet;
+
+    #[test]
+    fn std_maps() {
+        let owned = BTreeMap::from([("app".to_string(), "web".to_string())]);
+        let borrowed = BTreeMap::from([("app", "web")]);
+        let hashed: HashMap<&str, String> = HashMap::from([("app", "web".to_string())]);
+
+        assert_eq!(LabelSet::get(&owned, "app"), Some("web"));
+        assert_eq!(LabelSet::get(&borrowed, "app"), Some("web"));
+        assert_eq!(LabelSet::get(&hashed, "app"), Some("web"));
+        assert_eq!(LabelSet::get(&hashed, "env"), None);
+        assert!(LabelSet::contains_key(&borrowed, "app"));
+        assert_eq!(LabelSet::iter(&owned).collect::<Vec<_>>(), [("app", "web")]);
+    }
+}
@@ -1,14 +1,142 @@
-use std::collections::BTreeSet;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
-use std::ops::Deref;
+use std::ops::{BitAnd, Deref};
+use std::str::FromStr;
 
 use logos::{Lexer, Logos, Span};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use thiserror::Error;
 
+// Lets macro expansions refer to `::unselector` inside the crate itself
+extern crate self as unselector;
+
+mod admission;
+mod analysis;
+#[cfg(feature = "kube-rs")]
+mod api;
+#[cfg(feature = "rkyv")]
+mod archive;
+mod arena;
+mod borrowed;
+mod builder;
+mod cache;
+mod completion;
+mod condition;
+pub mod cst;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+mod diff;
+mod dnf;
+mod docker;
+mod document;
+mod expected;
+mod field;
+mod format;
+#[cfg(feature = "extended")]
+mod glob;
+mod index;
+mod intern;
+mod label_selector;
+mod label_set;
+mod location;
+mod lru;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+mod macros;
+mod matcher;
+mod matching;
+mod merge;
+#[cfg(feature = "utoipa")]
+mod openapi;
+mod options;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "rayon")]
+use parallel::parse_all;
+#[cfg(feature = "parser-cache")]
+mod parser_cache;
+mod partial;
+#[cfg(feature = "regex")]
+mod pattern;
+mod prometheus;
+mod query;
+mod report;
+mod requirements;
+mod resource;
+#[cfg(feature = "k8s-openapi")]
+mod scheduling;
+mod selector;
+#[cfg(feature = "serde")]
+pub mod serde_requirement;
+#[cfg(feature = "serde")]
+pub mod serde_string;
+#[cfg(feature = "serde")]
+pub mod serde_values;
+mod spanned;
+mod stream;
+mod taint;
+#[cfg(feature = "testing")]
+mod testing;
+mod tokens;
+mod transform;
+mod validation;
+mod vars;
+mod visit;
+
+pub use admission::{AdmissionFailure, validate_for_admission};
+pub use arena::SelectorArena;
+pub use borrowed::{ExpressionRef, ExpressionsRef};
+pub use builder::{BuildError, SelectorBuilder};
+pub use cache::{CachedMatcher, Eviction};
+pub use completion::{Catalog, Completion, CompletionKind, complete};
+pub use condition::Condition;
+pub use diff::{KeyChange, SelectorDiff};
+pub use dnf::SelectorDnf;
+pub use expected::Expected;
+pub use field::{FieldExpression, FieldExpressions};
+pub use format::Formatter;
+#[cfg(feature = "extended")]
+pub use glob::GlobPattern;
+pub use index::SelectorIndex;
+pub use intern::Interner;
+#[cfg(feature = "serde")]
+pub use label_selector::AnySelector;
+pub use label_selector::{LabelSelector, LabelSelectorRequirement};
+pub use label_set::LabelSet;
+pub use location::Location;
+pub use matcher::Matcher;
+pub use merge::{MergeError, MergePolicy};
+pub use options::{Dialect, Limit, ParseOptions};
+#[cfg(feature = "parser-cache")]
+pub use parser_cache::ParserCache;
+pub use partial::{LabelState, PartialLabelSet, PartialLabels, PartialMatch};
+#[cfg(feature = "regex")]
+pub use pattern::Pattern;
+#[cfg(feature = "serde")]
+pub use query::SelectorQuery;
+pub use report::ErrorReport;
+pub use resource::ResourceFilter;
+pub use selector::Selector;
+pub use spanned::Spanned;
+pub use stream::{ParseIter, parse_iter, parse_iter_with};
+pub use taint::{Taint, TaintEffect, Toleration};
+pub use tokens::{Token, TokenKind, Tokens, tokenize};
+#[cfg(feature = "macros")]
+pub use unselector_macros::selector_str;
+pub use validation::ValidationError;
+pub use visit::{ExpressionVisitor, walk_requirement};
+
 /// A selector expression with existing operations
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub enum Expression {
     /// Key exists and in set
     In(String, BTreeSet<String>),
@@ -27,31 +155,212 @@ pub enum Expression {
 
     /// Key does not exist
     DoesNotExist(String),
+
+    /// Key exists and is an integer greater than the value
+    GreaterThan(String, String),
+
+    /// Key exists and is an integer less than the value
+    LessThan(String, String),
+
+    /// Key exists and the value matches the pattern
+    #[cfg(feature = "regex")]
+    Matches(
+        String,
+        #[cfg_attr(feature = "rkyv", rkyv(with = archive::AsSource))] Pattern,
+    ),
+
+    /// Key does not exist or the value does not match the pattern
+    #[cfg(feature = "regex")]
+    NotMatches(
+        String,
+        #[cfg_attr(feature = "rkyv", rkyv(with = archive::AsSource))] Pattern,
+    ),
+
+    /// Key exists and the value matches the wildcard pattern
+    #[cfg(feature = "extended")]
+    Glob(
+        String,
+        #[cfg_attr(feature = "rkyv", rkyv(with = archive::AsSource))] GlobPattern,
+    ),
+}
+
+impl Expression {
+    /// Require the key to be set to one of the values
+    pub fn r#in(
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Expression::In(key.into(), values.into_iter().map(Into::into).collect())
+    }
+
+    /// Require the key to be unset or set to none of the values
+    pub fn not_in(
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Expression::NotIn(key.into(), values.into_iter().map(Into::into).collect())
+    }
+
+    /// Require the key to be set to the value
+    pub fn equal(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Expression::Equal(key.into(), value.into())
+    }
+
+    /// Require the key to be unset or set to a different value
+    pub fn not_equal(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Expression::NotEqual(key.into(), value.into())
+    }
+
+    /// Require the key to be set
+    pub fn exists(key: impl Into<String>) -> Self {
+        Expression::Exists(key.into())
+    }
+
+    /// Require the key to be unset
+    pub fn does_not_exist(key: impl Into<String>) -> Self {
+        Expression::DoesNotExist(key.into())
+    }
+
+    /// Require the key to be set to an integer greater than the value
+    pub fn greater_than(key: impl Into<String>, value: i64) -> Self {
+        Expression::GreaterThan(key.into(), value.to_string())
+    }
+
+    /// Require the key to be set to an integer less than the value
+    pub fn less_than(key: impl Into<String>, value: i64) -> Self {
+        Expression::LessThan(key.into(), value.to_string())
+    }
+
+    /// Require the key to be set to a value matching the pattern
+    #[cfg(feature = "regex")]
+    pub fn matching(key: impl Into<String>, pattern: Pattern) -> Self {
+        Expression::Matches(key.into(), pattern)
+    }
+
+    /// Require the key to be unset or set to a value not matching the pattern
+    #[cfg(feature = "regex")]
+    pub fn not_matching(key: impl Into<String>, pattern: Pattern) -> Self {
+        Expression::NotMatches(key.into(), pattern)
+    }
+
+    /// Require the key to be set to a value matching the wildcard pattern
+    #[cfg(feature = "extended")]
+    pub fn glob(key: impl Into<String>, pattern: GlobPattern) -> Self {
+        Expression::Glob(key.into(), pattern)
+    }
+
+    /// Combine with other requirements, all of which have to match
+    pub fn and(self, other: impl Into<Expressions>) -> Expressions {
+        Expressions::from(self).and(other)
+    }
+
+    /// Position of the operator in canonical order
+    fn operator_rank(&self) -> u8 {
+        match self {
+            Expression::In(..) => 0,
+            Expression::NotIn(..) => 1,
+            Expression::Equal(..) => 2,
+            Expression::NotEqual(..) => 3,
+            Expression::Exists(..) => 4,
+            Expression::DoesNotExist(..) => 5,
+            Expression::GreaterThan(..) => 6,
+            Expression::LessThan(..) => 7,
+            #[cfg(feature = "regex")]
+            Expression::Matches(..) => 8,
+            #[cfg(feature = "regex")]
+            Expression::NotMatches(..) => 9,
+            #[cfg(feature = "extended")]
+            Expression::Glob(..) => 10,
+        }
+    }
+
+    /// Key the expression applies to
+    pub fn key(&self) -> &str {
+        match self {
+            Expression::In(key, _)
+            | Expression::NotIn(key, _)
+            | Expression::Equal(key, _)
+            | Expression::NotEqual(key, _)
+            | Expression::Exists(key)
+            | Expression::DoesNotExist(key)
+            | Expression::GreaterThan(key, _)
+            | Expression::LessThan(key, _) => key,
+            #[cfg(feature = "regex")]
+            Expression::Matches(key, _) | Expression::NotMatches(key, _) => key,
+            #[cfg(feature = "extended")]
+            Expression::Glob(key, _) => key,
+        }
+    }
+}
+
+/// Indicates failure of conversion from Expression into another representation
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConversionError {
+    #[error("expression has no equivalent in the target representation: '{0}'")]
+    Unsupported(Box<Expression>),
+
+    #[error("label selector requirement is invalid: '{0}'")]
+    InvalidRequirement(Box<LabelSelectorRequirement>),
 }
 
 #[cfg(feature = "kube-rs")]
-impl From<Expression> for kube::core::Expression {
-    fn from(val: Expression) -> Self {
-        match val {
+impl TryFrom<Expression> for kube::core::Expression {
+    type Error = ConversionError;
+
+    fn try_from(val: Expression) -> std::result::Result<Self, ConversionError> {
+        Ok(match val {
             Expression::In(key, btree_set) => kube::core::Expression::In(key, btree_set),
             Expression::NotIn(key, btree_set) => kube::core::Expression::NotIn(key, btree_set),
             Expression::Equal(key, value) => kube::core::Expression::Equal(key, value),
             Expression::NotEqual(key, value) => kube::core::Expression::NotEqual(key, value),
             Expression::Exists(key) => kube::core::Expression::Exists(key),
             Expression::DoesNotExist(key) => kube::core::Expression::DoesNotExist(key),
-        }
+            Expression::GreaterThan(..) | Expression::LessThan(..) => {
+                return Err(ConversionError::Unsupported(val.into()));
+            }
+            #[cfg(feature = "regex")]
+            Expression::Matches(..) | Expression::NotMatches(..) => {
+                return Err(ConversionError::Unsupported(val.into()));
+            }
+            #[cfg(feature = "extended")]
+            Expression::Glob(..) => return Err(ConversionError::Unsupported(val.into())),
+        })
     }
 }
 
 #[cfg(feature = "kube-rs")]
-impl From<Expressions> for kube::core::Selector {
-    fn from(val: Expressions) -> Self {
+impl TryFrom<Expressions> for kube::core::Selector {
+    type Error = ConversionError;
+
+    fn try_from(val: Expressions) -> std::result::Result<Self, ConversionError> {
         val.into_iter()
-            .map(|ParsedExpression::Expression(e)| kube::core::Expression::from(e))
+            .map(|ParsedExpression::Expression(e)| kube::core::Expression::try_from(e))
             .collect()
     }
 }
 
+#[cfg(feature = "kube-rs")]
+impl From<kube::core::Expression> for Expression {
+    fn from(val: kube::core::Expression) -> Self {
+        match val {
+            kube::core::Expression::In(key, values) => Expression::In(key, values),
+            kube::core::Expression::NotIn(key, values) => Expression::NotIn(key, values),
+            kube::core::Expression::Equal(key, value) => Expression::Equal(key, value),
+            kube::core::Expression::NotEqual(key, value) => Expression::NotEqual(key, value),
+            kube::core::Expression::Exists(key) => Expression::Exists(key),
+            kube::core::Expression::DoesNotExist(key) => Expression::DoesNotExist(key),
+        }
+    }
+}
+
+/// Every kube-rs selector is representable, so the conversion cannot fail
+#[cfg(feature = "kube-rs")]
+impl From<kube::core::Selector> for Expressions {
+    fn from(val: kube::core::Selector) -> Self {
+        val.into_iter().map(Expression::from).collect()
+    }
+}
+
 impl IntoIterator for Expression {
     type IntoIter = std::option::IntoIter<Self::Item>;
     type Item = Self;
@@ -61,45 +370,280 @@ impl IntoIterator for Expression {
     }
 }
 
-#[cfg(feature = "kube-rs")]
-impl fmt::Display for Expression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&kube::core::Expression::from(self.clone()), f)
-    }
-}
-
-#[cfg(not(feature = "kube-rs"))]
 impl fmt::Display for Expression {
+    /// Format the expression in the selector syntax it is parsed from
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = escape_key(self.key());
         match self {
-            Expression::In(key, values) => {
-                let vals: Vec<&str> = values.iter().map(String::as_str).collect();
-                write!(f, "{} in ({})", key, vals.join(","))
-            }
-            Expression::NotIn(key, values) => {
-                let vals: Vec<&str> = values.iter().map(String::as_str).collect();
-                write!(f, "{} notin ({})", key, vals.join(","))
+            Expression::In(_, values) => write!(f, "{key} in ({})", join(values)),
+            Expression::NotIn(_, values) => write!(f, "{key} notin ({})", join(values)),
+            Expression::Equal(_, value) => write!(f, "{key}={}", escape_value(value)),
+            Expression::NotEqual(_, value) => write!(f, "{key}!={}", escape_value(value)),
+            Expression::Exists(_) => write!(f, "{key}"),
+            Expression::DoesNotExist(_) => write!(f, "!{key}"),
+            Expression::GreaterThan(_, value) => write!(f, "{key}>{}", escape_value(value)),
+            Expression::LessThan(_, value) => write!(f, "{key}<{}", escape_value(value)),
+            #[cfg(feature = "regex")]
+            Expression::Matches(_, pattern) => write!(f, "{key}=~{}", quote(pattern.as_str())),
+            #[cfg(feature = "regex")]
+            Expression::NotMatches(_, pattern) => write!(f, "{key}!~{}", quote(pattern.as_str())),
+            #[cfg(feature = "extended")]
+            Expression::Glob(_, pattern) if pattern.as_str().chars().all(is_glob_char) => {
+                write!(f, "{key}~{pattern}")
             }
-            Expression::Equal(key, val) => write!(f, "{}={}", key, val),
-            Expression::NotEqual(key, val) => write!(f, "{}!={}", key, val),
-            Expression::Exists(key) => write!(f, "{}", key),
-            Expression::DoesNotExist(key) => write!(f, "!{}", key),
+            #[cfg(feature = "extended")]
+            Expression::Glob(_, pattern) => write!(f, "{key}~{}", quote(pattern.as_str())),
         }
     }
 }
 
+/// Quote a value if possible, or escape it otherwise
+#[cfg(any(feature = "regex", feature = "extended"))]
+fn quote(value: &str) -> Cow<'_, str> {
+    if !value.contains('"') {
+        format!("\"{value}\"").into()
+    } else if !value.contains('\'') {
+        format!("'{value}'").into()
+    } else {
+        escape_value(value)
+    }
+}
+
+fn join(values: &BTreeSet<String>) -> String {
+    values
+        .iter()
+        .map(|value| escape_value(value))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// Indicates failure of conversion to Expression
 #[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseError {
     #[error("failed to parse value as expression: '{0}' at {1:?}")]
     StringParse(String, Span),
+
+    #[error("requirement is missing a key: '{0}' at {1:?}")]
+    EmptyKey(String, Span),
+
+    #[error("requirement has an invalid key or value: '{0}' at {1:?}")]
+    InvalidValue(String, Span),
+
+    #[error("parenthesis is not part of a list of values: '{0}' at {1:?}")]
+    UnbalancedParens(String, Span),
+
+    #[error("set operator is not followed by a list of values: '{0}' at {1:?}")]
+    MissingValuesList(String, Span),
+
+    #[error("operator is not supported: '{0}' at {1:?}")]
+    UnknownOperator(String, Span),
+
+    #[error("operator is misspelled: '{0}' at {1:?}, did you mean '{2}'?")]
+    MisspelledOperator(String, Span, &'static str),
+
+    #[error("{2}: '{0}' at {1:?}")]
+    LimitExceeded(String, Span, Limit),
+
+    #[error("variable is not defined: '{0}' at {1:?}")]
+    UnresolvedVariable(String, Span),
+}
+
+impl ParseError {
+    /// Stable identifier of the kind of error, for matching without relying
+    /// on the message
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::StringParse(..) => "string_parse",
+            ParseError::EmptyKey(..) => "empty_key",
+            ParseError::InvalidValue(..) => "invalid_value",
+            ParseError::UnbalancedParens(..) => "unbalanced_parens",
+            ParseError::MissingValuesList(..) => "missing_values_list",
+            ParseError::UnknownOperator(..) => "unknown_operator",
+            ParseError::MisspelledOperator(..) => "misspelled_operator",
+            ParseError::LimitExceeded(..) => "limit_exceeded",
+            ParseError::UnresolvedVariable(..) => "unresolved_variable",
+        }
+    }
+
+    /// Offending part of the input
+    pub fn text(&self) -> &str {
+        match self {
+            ParseError::StringParse(text, _)
+            | ParseError::EmptyKey(text, _)
+            | ParseError::InvalidValue(text, _)
+            | ParseError::UnbalancedParens(text, _)
+            | ParseError::MissingValuesList(text, _)
+            | ParseError::UnknownOperator(text, _)
+            | ParseError::MisspelledOperator(text, _, _)
+            | ParseError::LimitExceeded(text, _, _)
+            | ParseError::UnresolvedVariable(text, _) => text,
+        }
+    }
+
+    /// Position of the offending part in the input
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::StringParse(_, span)
+            | ParseError::EmptyKey(_, span)
+            | ParseError::InvalidValue(_, span)
+            | ParseError::UnbalancedParens(_, span)
+            | ParseError::MissingValuesList(_, span)
+            | ParseError::UnknownOperator(_, span)
+            | ParseError::MisspelledOperator(_, span, _)
+            | ParseError::LimitExceeded(_, span, _)
+            | ParseError::UnresolvedVariable(_, span) => span.clone(),
+        }
+    }
+
+    /// Shift the span by an offset, for input embedded in a larger document
+    pub(crate) fn offset(self, offset: usize) -> Self {
+        self.map_span(|span| span.start + offset..span.end + offset)
+    }
+
+    /// Replace the span, for input derived from another source
+    pub(crate) fn map_span(mut self, f: impl FnOnce(Span) -> Span) -> Self {
+        match &mut self {
+            ParseError::StringParse(_, span)
+            | ParseError::EmptyKey(_, span)
+            | ParseError::InvalidValue(_, span)
+            | ParseError::UnbalancedParens(_, span)
+            | ParseError::MissingValuesList(_, span)
+            | ParseError::UnknownOperator(_, span)
+            | ParseError::MisspelledOperator(_, span, _)
+            | ParseError::LimitExceeded(_, span, _)
+            | ParseError::UnresolvedVariable(_, span) => *span = f(span.clone()),
+        }
+        self
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ParseError>;
 
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Expressions(Vec<ParsedExpression>);
 
+impl Expressions {
+    /// Number of expressions
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Indicates whether there are no expressions, matching everything
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Equality requirements for each entry of a `matchLabels` map
+    pub fn from_match_labels(map: &BTreeMap<String, String>) -> Self {
+        map.iter()
+            .map(|(key, value)| Expression::equal(key, value))
+            .collect()
+    }
+
+    /// Append an expression
+    pub fn push(&mut self, expression: Expression) {
+        self.0.push(ParsedExpression::Expression(expression));
+    }
+
+    /// Iterate over expressions in order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter())
+    }
+
+    /// Combine with other requirements, all of which have to match.
+    ///
+    /// Requirements already present are not added again.
+    pub fn and(mut self, other: impl Into<Expressions>) -> Expressions {
+        for expression in other.into() {
+            if !self.0.contains(&expression) {
+                self.0.push(expression);
+            }
+        }
+        self
+    }
+
+    /// Distinct keys the expressions refer to, in order of first appearance
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        let mut seen = BTreeSet::new();
+        self.iter()
+            .map(Expression::key)
+            .filter(move |key| seen.insert(*key))
+    }
+
+    /// Expressions referring to the key
+    pub fn get<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a Expression> {
+        self.iter()
+            .filter(move |expression| expression.key() == key)
+    }
+
+    /// Values the key may be set to for the expressions to match, or `None`
+    /// if the expressions don't restrict the key to specific values.
+    ///
+    /// An empty set means no value of the key can match.
+    pub fn values_for(&self, key: &str) -> Option<BTreeSet<&str>> {
+        requirements::by_key(self.iter().filter(|expression| expression.key() == key))
+            .remove(key)?
+            .permitted()
+    }
+
+    /// Convert into a kube-rs selector, for passing to kube APIs
+    #[cfg(feature = "kube-rs")]
+    pub fn to_selector(&self) -> std::result::Result<kube::core::Selector, ConversionError> {
+        self.clone().try_into()
+    }
+
+    /// Requirements sorted by key, operator and values, without duplicates,
+    /// so equivalent selectors serialize and display identically
+    pub fn to_canonical(&self) -> Expressions {
+        let mut expressions = self.0.clone();
+        expressions.sort_by_cached_key(|expression| {
+            (
+                expression.key().to_owned(),
+                expression.operator_rank(),
+                expression.to_string(),
+            )
+        });
+        expressions.dedup();
+        Expressions(expressions)
+    }
+
+    /// View the parsed expressions as a slice
+    pub fn as_slice(&self) -> &[ParsedExpression] {
+        &self.0
+    }
+}
+
+/// Iterator over borrowed expressions of [`Expressions`]
+#[derive(Clone, Debug)]
+pub struct Iter<'a>(std::slice::Iter<'a, ParsedExpression>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Expression;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Deref::deref)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(Deref::deref)
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
 impl IntoIterator for Expressions {
     type Item = ParsedExpression;
     type IntoIter = std::vec::IntoIter<Self::Item>;
@@ -109,6 +653,50 @@ impl IntoIterator for Expressions {
     }
 }
 
+impl<T: Into<Expressions>> BitAnd<T> for Expressions {
+    type Output = Expressions;
+
+    fn bitand(self, other: T) -> Expressions {
+        self.and(other)
+    }
+}
+
+impl<T: Into<Expressions>> BitAnd<T> for Expression {
+    type Output = Expressions;
+
+    fn bitand(self, other: T) -> Expressions {
+        self.and(other)
+    }
+}
+
+impl From<Expression> for Expressions {
+    fn from(expression: Expression) -> Self {
+        Expressions(vec![ParsedExpression::Expression(expression)])
+    }
+}
+
+impl<'a> IntoIterator for &'a Expressions {
+    type Item = &'a Expression;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Expression> for Expressions {
+    fn from_iter<T: IntoIterator<Item = Expression>>(iter: T) -> Self {
+        Expressions(iter.into_iter().map(ParsedExpression::Expression).collect())
+    }
+}
+
+impl Extend<Expression> for Expressions {
+    fn extend<T: IntoIterator<Item = Expression>>(&mut self, iter: T) {
+        self.0
+            .extend(iter.into_iter().map(ParsedExpression::Expression));
+    }
+}
+
 impl fmt::Display for Expressions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut iter = self.0.iter();
@@ -122,17 +710,43 @@ impl fmt::Display for Expressions {
     }
 }
 
-#[derive(Logos, Clone, Debug, PartialEq, Serialize, Deserialize, derive_more::Deref)]
-#[logos(skip r"[, \t\n\f]+")]
-pub enum ParsedExpression {
-    #[regex(r"[-./\w]+\s+in\s+\([-.\w\s,]+\)", |lex| parse_set(lex.slice()))]
-    #[regex(r"[-./\w]+\s+notin\s+\([-.\w\s,]+\)", |lex| parse_set(lex.slice()))]
-    #[regex(r"\![-./\w]+", |lex| parse_set(lex.slice()))]
-    #[regex(r"[-./\w]+", |lex| parse_set(lex.slice()))]
-    #[regex(r"[-./\w]+\s*=\s*[-.\w]+", |lex| parse_equality(lex.slice()))]
-    #[regex(r"[-./\w]+\s*==\s*[-.\w]+", |lex| parse_equality(lex.slice()))]
-    #[regex(r"[-./\w]+\s*!=\s*[-.\w]+", |lex| parse_equality(lex.slice()))]
-    Expression(#[deref] Expression),
+/// Declare a lexer of requirements over the selector grammar, so the owned
+/// [`ParsedExpression`] and the borrowed lexer share a single definition.
+macro_rules! requirement_lexer {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident$(<$lifetime:lifetime>)? {
+            $variant:ident($(#[$field:meta])* $requirement:ty)$(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[logos(skip r"[, \t\n\f]+")]
+        #[logos(extras = ParseOptions)]
+        #[logos(subpattern key = r"([-./\w]|\\.)+")]
+        #[logos(subpattern value = r"([-.\w]|\\.)+")]
+        #[logos(subpattern quoted = r#""[^"]*"|'[^']*'"#)]
+        #[logos(subpattern glob = r"([-./\w*?\[\]]|\\.)+")]
+        #[logos(subpattern values = r"\(([-.\w\s,]|\\.|(?&quoted))+\)")]
+        #[logos(subpattern requirement = r"\!(?&key)|(?&key)(\s+(?i:in|notin)\s+(?&values)|\s*(=|==|!=)\s*((?&value)|(?&quoted))?|\s*(=~|!~)\s*((?&value)|(?&quoted))|\s*~\s*((?&glob)|(?&quoted))|\s*[<>]\s*(?&value))?")]
+        $vis enum $name$(<$lifetime>)? {
+            #[regex(r"(?&requirement)", |lex| parse_requirement(lex.slice(), &lex.extras))]
+            $variant($(#[$field])* $requirement),
+        }
+    };
+}
+use requirement_lexer;
+
+requirement_lexer! {
+    #[derive(Logos, Clone, Debug, PartialEq, derive_more::Deref)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+    #[cfg_attr(
+        feature = "rkyv",
+        derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+    )]
+    pub enum ParsedExpression {
+        Expression(#[deref] Expression),
+    }
 }
 
 impl fmt::Display for ParsedExpression {
@@ -141,56 +755,185 @@ impl fmt::Display for ParsedExpression {
     }
 }
 
-impl TryFrom<&str> for Expressions {
-    type Error = ParseError;
+impl Expressions {
+    /// Parse a selector in the given dialect
+    pub fn parse_with(dialect: impl Into<Dialect>, selector: &str) -> Result<Self> {
+        Self::parse_with_options(selector, dialect.into().options())
+    }
 
-    fn try_from(selector: &str) -> Result<Self> {
-        let mut lexer = ParsedExpression::lexer(selector);
-        let mut expressions = vec![];
-        while let Some(value) = parse_expression(&mut lexer)? {
-            expressions.push(value);
+    /// Parse a selector, accepting the grammar extensions enabled in the options
+    pub fn parse_with_options(selector: &str, options: ParseOptions) -> Result<Self> {
+        Self::parse_spanned_with(options, selector).map(Into::into)
+    }
+
+    /// Parse every selector, in order, such as the selectors of all
+    /// instances of a custom resource
+    pub fn parse_batch<S: AsRef<str> + Sync>(selectors: &[S]) -> Vec<Result<Self>> {
+        Self::parse_batch_with(Dialect::Kubernetes, selectors)
+    }
+
+    /// Parse every selector in the given dialect, in order.
+    ///
+    /// With the `rayon` feature the selectors are parsed in parallel.
+    pub fn parse_batch_with<S: AsRef<str> + Sync>(
+        dialect: impl Into<Dialect>,
+        selectors: &[S],
+    ) -> Vec<Result<Self>> {
+        parse_all(selectors, dialect.into().options())
+    }
+
+    /// Parse a selector, reporting every invalid requirement instead of
+    /// stopping at the first one
+    pub fn try_from_all(selector: &str) -> std::result::Result<Self, Vec<ParseError>> {
+        match Self::parse_skipping(selector, ParseOptions::strict()) {
+            (expressions, skipped) if skipped.is_empty() => Ok(expressions),
+            (_, skipped) => Err(skipped.into_iter().map(|warning| warning.error).collect()),
         }
+    }
+
+    /// Parse the valid requirements of a selector in the given dialect,
+    /// skipping invalid ones rather than failing the whole selector
+    pub fn parse_recover(dialect: impl Into<Dialect>, selector: &str) -> (Self, Vec<ParseWarning>) {
+        Self::parse_skipping(selector, dialect.into().options())
+    }
 
-        Ok(Expressions(expressions))
+    /// Parse the valid requirements of a selector, skipping each invalid one
+    /// from the previous to the next comma outside a list of values
+    fn parse_skipping(selector: &str, options: ParseOptions) -> (Self, Vec<ParseWarning>) {
+        if let Err(error) = check_length(selector, &options) {
+            let span = error.span();
+            return (Expressions::default(), vec![ParseWarning { span, error }]);
+        }
+        let mut lexer = ParsedExpression::lexer_with_extras(selector, options);
+        let (mut expressions, mut skipped) = (vec![], vec![]);
+        // Expressions parsed and position reached when the requirement began
+        let (mut segment, mut end) = ((0, 0), 0);
+        loop {
+            let parsed = parse_expression(&mut lexer);
+            if matches!(parsed, Ok(None)) {
+                break;
+            }
+            let start = lexer.span().start;
+            if end == 0 || selector[end..start].contains(',') {
+                segment = (expressions.len(), start);
+            }
+            match parsed {
+                Ok(_) if let Some(error) = too_many_requirements(&lexer, expressions.len()) => {
+                    let span = start..selector.len();
+                    skipped.push(ParseWarning { span, error });
+                    break;
+                }
+                Ok(expression) => expressions.extend(expression),
+                Err(error) => {
+                    expressions.truncate(segment.0);
+                    skip_requirement(&mut lexer);
+                    skipped.push(ParseWarning {
+                        span: segment.1..lexer.span().end,
+                        error,
+                    });
+                }
+            }
+            end = lexer.span().end;
+        }
+        (Expressions(expressions), skipped)
     }
 }
 
-#[derive(Logos, Debug, PartialEq)]
-#[logos(skip r"[ \t\n\f]+")]
-enum EqualityToken {
-    #[token("=")]
-    #[token("==")]
-    Equal,
-    #[token("!=")]
-    NotEqual,
-    #[regex(r"[-./\w]+", |lex| lex.slice().to_owned())]
-    Value(String),
+#[cfg(not(feature = "rayon"))]
+fn parse_all<S: AsRef<str>>(selectors: &[S], options: ParseOptions) -> Vec<Result<Expressions>> {
+    selectors
+        .iter()
+        .map(|selector| Expressions::parse_with_options(selector.as_ref(), options))
+        .collect()
+}
+
+/// A part of a selector skipped by [`Expressions::parse_recover`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Position of the skipped requirement in the selector
+    pub span: Span,
+
+    /// Reason the requirement was skipped
+    pub error: ParseError,
+}
+
+/// Reject selectors longer than the options allow, before lexing them
+fn check_length(selector: &str, options: &ParseOptions) -> Result<()> {
+    match options.max_length {
+        Some(max) if selector.len() > max => Err(ParseError::LimitExceeded(
+            String::new(),
+            max..selector.len(),
+            Limit::Length(max),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Reject the requirement just lexed if as many were parsed as the options
+/// allow
+fn too_many_requirements<'s, T>(lexer: &Lexer<'s, T>, parsed: usize) -> Option<ParseError>
+where
+    T: Logos<'s, Source = str, Extras = ParseOptions>,
+{
+    let max = lexer.extras.max_requirements?;
+    (parsed >= max).then(|| {
+        ParseError::LimitExceeded(
+            lexer.slice().to_owned(),
+            lexer.span(),
+            Limit::Requirements(max),
+        )
+    })
+}
+
+/// Advance the lexer to the next comma outside parentheses
+fn skip_requirement(lexer: &mut Lexer<'_, ParsedExpression>) {
+    let remainder = lexer.remainder();
+    let mut depth = 0usize;
+    let end = remainder
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => return true,
+                _ => {}
+            }
+            false
+        })
+        .map_or(remainder.len(), |(i, _)| i);
+    lexer.bump(end);
 }
 
-#[derive(Logos, Debug, PartialEq)]
-#[logos(skip r"[, \t\n\f]+")]
-enum SetToken {
-    #[token("!")]
-    Not,
+impl TryFrom<&str> for Expressions {
+    type Error = ParseError;
+
+    fn try_from(selector: &str) -> Result<Self> {
+        Self::parse_with_options(selector, ParseOptions::strict())
+    }
+}
 
-    #[regex(r"\([\w\s,]+\)", |lex| parse_value_list(lex.slice()))]
-    ValuesList(Vec<String>),
+impl TryFrom<String> for Expressions {
+    type Error = ParseError;
 
-    #[token("in")]
-    In,
+    fn try_from(selector: String) -> Result<Self> {
+        Self::try_from(selector.as_str())
+    }
+}
 
-    #[token("notin")]
-    NotIn,
+impl TryFrom<Cow<'_, str>> for Expressions {
+    type Error = ParseError;
 
-    #[regex(r"\w+", |lex| lex.slice().to_owned())]
-    Value(String),
+    fn try_from(selector: Cow<'_, str>) -> Result<Self> {
+        Self::try_from(selector.as_ref())
+    }
 }
 
-#[derive(Logos, Debug, PartialEq)]
-#[logos(skip r"[, \(\)\t\n\f]+")]
-enum ValuesListToken {
-    #[regex(r"[a-zA-Z_-]+", |lex| lex.slice().to_owned())]
-    Value(String),
+impl FromStr for Expressions {
+    type Err = ParseError;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::try_from(selector)
+    }
 }
 
 /// Parse selector expression
@@ -200,95 +943,523 @@ pub fn parse_expression(
     lexer
         .next()
         .map(|token| match token {
-            Ok(ex) => Ok(ex),
-            _ => Err(ParseError::StringParse(
-                lexer.slice().to_owned(),
-                lexer.span(),
-            )),
+            Ok(ex) => match check_lexed(lexer, ex.lexed()) {
+                Some(error) => Err(error),
+                None => Ok(ex),
+            },
+            _ => Err(diagnose(lexer)),
         })
         .transpose()
 }
 
-/// Parse an equality based expression.
-fn parse_equality(source: &str) -> Option<Expression> {
-    let mut lexer = EqualityToken::lexer(source);
-    let key = lexer.next()?.ok()?;
-    let op = lexer.next()?.ok()?;
-    let value = lexer.next()?.ok()?;
-    match (key, op, value) {
-        (EqualityToken::Value(key), EqualityToken::Equal, EqualityToken::Value(value)) => {
-            Some(Expression::Equal(key, value))
-        }
-        (EqualityToken::Value(key), EqualityToken::NotEqual, EqualityToken::Value(value)) => {
-            Some(Expression::NotEqual(key, value))
+/// Parts of a lexed requirement inspected by the checks shared between
+/// [`Expression`] and [`ExpressionRef`] parsing
+enum Lexed<'e> {
+    /// Value of an equality or inequality
+    Equality(&'e str),
+
+    /// Key and number of values of an `in` requirement
+    In(&'e str, usize),
+
+    /// Number of values of a `notin` requirement
+    NotIn(usize),
+
+    Other,
+}
+
+impl Expression {
+    fn lexed(&self) -> Lexed<'_> {
+        match self {
+            Expression::Equal(_, value) | Expression::NotEqual(_, value) => Lexed::Equality(value),
+            Expression::In(key, values) => Lexed::In(key, values.len()),
+            Expression::NotIn(_, values) => Lexed::NotIn(values.len()),
+            _ => Lexed::Other,
         }
-        _ => None,
     }
 }
 
-/// Parse a set based expression.
-fn parse_set(source: &str) -> Option<Expression> {
-    let mut lexer = SetToken::lexer(source);
-    let key = lexer.next()?.ok()?;
-    match key {
-        SetToken::Not => match lexer.next()?.ok()? {
-            SetToken::Value(value) => Some(Expression::DoesNotExist(value)),
-            _ => None,
-        },
-        SetToken::Value(key) => {
-            let op = match lexer.next() {
-                Some(op) => op.ok()?,
-                None => return Some(Expression::Exists(key)),
-            };
-            let value = lexer.next()?.ok()?;
-            match (op, value) {
-                (SetToken::In, SetToken::ValuesList(values)) => Some(Expression::In(
-                    key,
-                    values.into_iter().collect::<BTreeSet<String>>(),
-                )),
-                (SetToken::NotIn, SetToken::ValuesList(values)) => Some(Expression::NotIn(
-                    key,
-                    values.into_iter().collect::<BTreeSet<String>>(),
-                )),
-                (_, _) => None,
+/// Reject a requirement which lexed successfully but is misspelled or
+/// exceeds the limits of the options
+fn check_lexed<'s, T>(lexer: &Lexer<'s, T>, lexed: Lexed<'_>) -> Option<ParseError>
+where
+    T: Logos<'s, Source = str, Extras = ParseOptions>,
+{
+    misspelled_operator(lexer, &lexed).or_else(|| too_many_values(lexer, &lexed))
+}
+
+/// Reject set based requirements with more values than the options allow
+fn too_many_values<'s, T>(lexer: &Lexer<'s, T>, lexed: &Lexed<'_>) -> Option<ParseError>
+where
+    T: Logos<'s, Source = str, Extras = ParseOptions>,
+{
+    let max = lexer.extras.max_values?;
+    let (Lexed::In(_, len) | Lexed::NotIn(len)) = *lexed else {
+        return None;
+    };
+    (len > max).then(|| {
+        ParseError::LimitExceeded(lexer.slice().to_owned(), lexer.span(), Limit::Values(max))
+    })
+}
+
+/// Detect operator typos which still lex as valid requirements, such as `=!`
+/// or `===` lexing as an equality with an empty value followed by more
+/// operator characters, and `a not in (b)` lexing as `a` and `not in (b)`
+fn misspelled_operator<'s, T>(lexer: &Lexer<'s, T>, lexed: &Lexed<'_>) -> Option<ParseError>
+where
+    T: Logos<'s, Source = str, Extras = ParseOptions>,
+{
+    let (slice, span) = (lexer.slice(), lexer.span());
+    let (start, end, suggestion) = match *lexed {
+        Lexed::Equality("") => {
+            let remainder = lexer.remainder();
+            let extra = remainder.len() - remainder.trim_start_matches(['=', '!']).len();
+            if !slice.ends_with('=') || extra == 0 {
+                return None;
             }
+            let start = span.start + slice.trim_end_matches(['=', '!']).len();
+            let text = &lexer.source()[start..span.end + extra];
+            let suggestion = if text.contains('!') { "!=" } else { "==" };
+            (start, span.end + extra, suggestion)
         }
-        SetToken::ValuesList(_) | SetToken::In | SetToken::NotIn => None,
-    }
+        Lexed::In(key, _) if key.eq_ignore_ascii_case("not") => {
+            let before = lexer.source()[..span.start].trim_end();
+            if !before.ends_with(is_key_char) {
+                return None;
+            }
+            let keyword = slice[3..].trim_start();
+            let end = span.start + slice.len() - keyword.len() + 2;
+            (span.start, end, "notin")
+        }
+        _ => return None,
+    };
+    let text = lexer.source()[start..end].to_owned();
+    Some(ParseError::MisspelledOperator(text, start..end, suggestion))
 }
 
-// Parse a list of values into vector
-fn parse_value_list(source: &str) -> Option<Vec<String>> {
-    let lexer = ValuesListToken::lexer(source);
-    let mut values = vec![];
-    for value in lexer {
-        values.push(match value.ok()? {
-            ValuesListToken::Value(value) => value,
-        });
+/// Classify the token the lexer just rejected
+fn diagnose<'s, T>(lexer: &Lexer<'s, T>) -> ParseError
+where
+    T: Logos<'s, Source = str, Extras = ParseOptions>,
+{
+    let (slice, span, options) = (lexer.slice(), lexer.span(), &lexer.extras);
+    let text = slice.to_owned();
+    let mut chars = slice.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        match c {
+            '(' | ')' => return ParseError::UnbalancedParens(text, span),
+            '=' | '!' | '<' | '>' | '~' => return ParseError::EmptyKey(text, span),
+            c if !is_key_char(c) && c != '\\' => return ParseError::StringParse(text, span),
+            _ => {}
+        }
+    }
+
+    if slice.eq_ignore_ascii_case("in") || slice.eq_ignore_ascii_case("notin") {
+        let before = &lexer.source()[..span.start];
+        let not = before.trim_end();
+        if slice.eq_ignore_ascii_case("in")
+            && not.len() < before.len()
+            && not.to_ascii_lowercase().ends_with("not")
+            && !not[..not.len() - 3].ends_with(is_key_char)
+        {
+            let start = not.len() - 3;
+            let text = lexer.source()[start..span.end].to_owned();
+            return ParseError::MisspelledOperator(text, start..span.end, "notin");
+        }
+
+        let remainder = lexer.remainder();
+        let trimmed = remainder.trim_start();
+        if trimmed.starts_with('(') {
+            let start = span.end + remainder.len() - trimmed.len();
+            return match trimmed.find(')') {
+                Some(end) => {
+                    ParseError::InvalidValue(trimmed[..=end].to_owned(), start..start + end + 1)
+                }
+                None => ParseError::UnbalancedParens("(".into(), start..start + 1),
+            };
+        }
+        return ParseError::MissingValuesList(text, span);
+    }
+
+    // A key followed by a keyword spelled in a case the options reject
+    let separators = [',', ' ', '\t', '\n', '\x0c'];
+    let key_end = |c: char| !is_key_char(c) && c != '\\';
+    let mut cursor = Cursor(slice.trim_start_matches(separators));
+    if cursor.word(key_end).is_some() {
+        let rest = cursor.0.trim_start_matches(separators);
+        let keyword = Cursor(rest).word(key_end).unwrap_or_default();
+        let suggestion = ["in", "notin"]
+            .into_iter()
+            .find(|suggestion| keyword.eq_ignore_ascii_case(suggestion));
+        if let Some(suggestion) = suggestion {
+            if keyword == suggestion || options.case_insensitive_keywords {
+                return ParseError::InvalidValue(text, span);
+            }
+            let start = span.start + slice.len() - rest.len();
+            let keyword_span = start..start + keyword.len();
+            return ParseError::MisspelledOperator(keyword.to_owned(), keyword_span, suggestion);
+        }
     }
 
-    Some(values)
+    // A key followed by an operator the options don't allow
+    let mut cursor = Cursor(slice.trim_start_matches([' ', '\t', '\n', '\x0c']));
+    if cursor.word(key_end).is_some() {
+        let operator = cursor.0.trim_start_matches([' ', '\t', '\n', '\x0c']);
+        if (operator.starts_with("=~") || operator.starts_with("!~")) && !regex_enabled(options)
+            || operator.starts_with('~') && !glob_enabled(options)
+        {
+            return ParseError::UnknownOperator(text, span);
+        }
+    }
+    ParseError::InvalidValue(text, span)
 }
 
-#[cfg(test)]
-mod tests {
-    use logos::Logos;
+#[cfg(feature = "regex")]
+fn regex_enabled(options: &ParseOptions) -> bool {
+    options.regex_operators
+}
 
-    use crate::ParseError;
+#[cfg(not(feature = "regex"))]
+fn regex_enabled(_: &ParseOptions) -> bool {
+    false
+}
 
-    use super::Expression;
+#[cfg(feature = "extended")]
+fn glob_enabled(options: &ParseOptions) -> bool {
+    options.glob_operator
+}
 
-    use super::{ParsedExpression, parse_expression, parse_value_list};
+#[cfg(not(feature = "extended"))]
+fn glob_enabled(_: &ParseOptions) -> bool {
+    false
+}
+
+/// Operator of an equality or integer comparison
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EqualityOperator {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    LessThan,
+    Matches,
+    NotMatches,
+}
+
+/// Operators by spelling, with the longer ones sharing a prefix first
+const EQUALITY_OPERATORS: [(&str, EqualityOperator); 7] = [
+    ("==", EqualityOperator::Equal),
+    ("=~", EqualityOperator::Matches),
+    ("!=", EqualityOperator::NotEqual),
+    ("!~", EqualityOperator::NotMatches),
+    ("=", EqualityOperator::Equal),
+    (">", EqualityOperator::GreaterThan),
+    ("<", EqualityOperator::LessThan),
+];
+
+/// Cursor over a requirement, splitting it into its parts in a single pass.
+///
+/// The lexer already checked the characters of each part of a matched
+/// requirement, so the cursor only looks for where the parts end.
+struct Cursor<'s>(&'s str);
+
+impl<'s> Cursor<'s> {
+    /// Skip spaces, tabs and line breaks between the parts, rejecting any
+    /// other whitespace
+    fn gap(&mut self) -> Option<()> {
+        self.0 = self.0.trim_start_matches([' ', '\t', '\n', '\x0c']);
+        (!self.0.starts_with(char::is_whitespace)).then_some(())
+    }
+
+    /// Consume the prefix if the rest starts with it
+    fn eat(&mut self, prefix: &str) -> bool {
+        match self.0.strip_prefix(prefix) {
+            Some(rest) => {
+                self.0 = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Take a non-empty key or value, up to the first unescaped character
+    /// ending it
+    fn word(&mut self, end: impl Fn(char) -> bool) -> Option<&'s str> {
+        let mut escaped = false;
+        let len = self
+            .0
+            .find(|c| {
+                let stop = !escaped && end(c);
+                escaped = !escaped && c == '\\';
+                stop
+            })
+            .unwrap_or(self.0.len());
+        let (word, rest) = self.0.split_at(len);
+        self.0 = rest;
+        (!word.is_empty()).then_some(word)
+    }
+
+    /// Take a single or double quoted value, without the quotes
+    fn quoted(&mut self) -> Option<&'s str> {
+        let quote = self.0.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+        let len = self.0[1..].find(quote)?;
+        let value = &self.0[1..=len];
+        self.0 = &self.0[len + 2..];
+        Some(value)
+    }
+}
+
+/// Requirement built by the parser, owned or borrowing from the selector
+trait FromParts<'s>: From<ExpressionRef<'s>> {
+    /// Set based requirement with its values in the order they were written
+    fn set(key: Cow<'s, str>, negated: bool, values: Vec<Cow<'s, str>>) -> Self;
+}
+
+impl<'s> FromParts<'s> for Expression {
+    fn set(key: Cow<'s, str>, negated: bool, values: Vec<Cow<'s, str>>) -> Self {
+        let values = values.into_iter().map(Cow::into_owned).collect();
+        match negated {
+            false => Expression::In(key.into(), values),
+            true => Expression::NotIn(key.into(), values),
+        }
+    }
+}
+
+/// Parse a requirement the lexer matched, which is a possibly negated key,
+/// or a key followed by a set, equality, comparison or wildcard operator
+fn parse_requirement<'s, T: FromParts<'s>>(source: &'s str, options: &ParseOptions) -> Option<T> {
+    let mut cursor = Cursor(source);
+    let not = cursor.eat("!");
+    let raw_key = cursor.word(|c| c.is_whitespace() || matches!(c, '=' | '!' | '>' | '<' | '~'))?;
+    let key = unescape(raw_key, options)?;
+    // The keywords are never keys of set based requirements
+    let keyword = raw_key.eq_ignore_ascii_case("in") || raw_key.eq_ignore_ascii_case("notin");
+    match cursor.0.trim_start().chars().next() {
+        _ if not && (keyword || !cursor.0.is_empty()) => None,
+        None if keyword => None,
+        None if not => Some(ExpressionRef::DoesNotExist(key).into()),
+        None => Some(ExpressionRef::Exists(key).into()),
+        Some('~') => {
+            cursor.0 = cursor.0.trim_start();
+            parse_glob(cursor, key, options).map(Into::into)
+        }
+        Some(_) if cursor.gap().is_none() => None,
+        Some('=' | '!' | '>' | '<') => parse_equality(cursor, key, options).map(Into::into),
+        Some(_) if keyword => None,
+        Some(_) => parse_set(cursor, key, options),
+    }
+}
+
+/// Parse the operator and value of an equality or integer comparison
+fn parse_equality<'s>(
+    mut cursor: Cursor<'s>,
+    key: Cow<'s, str>,
+    options: &ParseOptions,
+) -> Option<ExpressionRef<'s>> {
+    let &(_, op) = EQUALITY_OPERATORS.iter().find(|(op, _)| cursor.eat(op))?;
+    cursor.gap()?;
+    let value = if cursor.0.is_empty() {
+        // Label values may be empty, but bounds and patterns may not
+        if !matches!(op, EqualityOperator::Equal | EqualityOperator::NotEqual) {
+            return None;
+        }
+        "".into()
+    } else if let Some(value) = cursor.quoted() {
+        // Regular expressions are always allowed to be quoted
+        let regex = matches!(op, EqualityOperator::Matches | EqualityOperator::NotMatches);
+        if !options.quoted_values && !regex {
+            return None;
+        }
+        value.into()
+    } else {
+        unescape(cursor.word(char::is_whitespace)?, options)?
+    };
+    match op {
+        EqualityOperator::Equal => Some(ExpressionRef::Equal(key, value)),
+        EqualityOperator::NotEqual => Some(ExpressionRef::NotEqual(key, value)),
+        EqualityOperator::GreaterThan if value.parse::<i64>().is_ok() => {
+            Some(ExpressionRef::GreaterThan(key, value))
+        }
+        EqualityOperator::LessThan if value.parse::<i64>().is_ok() => {
+            Some(ExpressionRef::LessThan(key, value))
+        }
+        #[cfg(feature = "regex")]
+        EqualityOperator::Matches if options.regex_operators => {
+            Some(ExpressionRef::Matches(key, Pattern::new(value).ok()?))
+        }
+        #[cfg(feature = "regex")]
+        EqualityOperator::NotMatches if options.regex_operators => {
+            Some(ExpressionRef::NotMatches(key, Pattern::new(value).ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the pattern of a wildcard requirement
+#[cfg(feature = "extended")]
+fn parse_glob<'s>(
+    mut cursor: Cursor<'s>,
+    key: Cow<'s, str>,
+    options: &ParseOptions,
+) -> Option<ExpressionRef<'s>> {
+    if !options.glob_operator || !cursor.eat("~") {
+        return None;
+    }
+    cursor.0 = cursor.0.trim_start();
+    let pattern = match cursor.quoted() {
+        Some(pattern) => pattern.into(),
+        None => unescape(cursor.word(char::is_whitespace)?, options)?,
+    };
+    Some(ExpressionRef::Glob(key, GlobPattern::new(pattern).ok()?))
+}
+
+#[cfg(not(feature = "extended"))]
+fn parse_glob<'s>(_: Cursor<'s>, _: Cow<'s, str>, _: &ParseOptions) -> Option<ExpressionRef<'s>> {
+    None
+}
+
+/// Parse the keyword and values of a set based requirement
+fn parse_set<'s, T: FromParts<'s>>(
+    mut cursor: Cursor<'s>,
+    key: Cow<'s, str>,
+    options: &ParseOptions,
+) -> Option<T> {
+    let keyword = cursor.word(char::is_whitespace)?;
+    let (negated, lowercase) = if keyword.eq_ignore_ascii_case("in") {
+        (false, keyword == "in")
+    } else if keyword.eq_ignore_ascii_case("notin") {
+        (true, keyword == "notin")
+    } else {
+        return None;
+    };
+    if !lowercase && !options.case_insensitive_keywords {
+        return None;
+    }
+    cursor.gap()?;
+    Some(T::set(key, negated, parse_value_list(cursor.0, options)?))
+}
+
+// Parse a list of values into vector
+fn parse_value_list<'s>(source: &'s str, options: &ParseOptions) -> Option<Vec<Cow<'s, str>>> {
+    let mut cursor = Cursor(source);
+    let mut values = vec![];
+    loop {
+        cursor.0 = cursor
+            .0
+            .trim_start_matches([',', ' ', '(', ')', '\t', '\n', '\x0c']);
+        if cursor.0.is_empty() {
+            return Some(values);
+        }
+        values.push(match cursor.quoted() {
+            Some(value) if options.quoted_values => value.into(),
+            Some(_) => return None,
+            None => {
+                let end = |c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')' | '"' | '\'');
+                unescape(cursor.word(end)?, options)?
+            }
+        });
+    }
+}
+
+/// Resolve backslash escapes in an unquoted key or value, if the options
+/// allow them, and reject non-ASCII characters unless Unicode is allowed
+fn unescape<'s>(source: &'s str, options: &ParseOptions) -> Option<Cow<'s, str>> {
+    if !options.unicode && !source.is_ascii() {
+        return None;
+    }
+    if !source.contains('\\') {
+        return Some(source.into());
+    }
+    if !options.escapes {
+        return None;
+    }
+
+    let mut chars = source.chars();
+    let mut unescaped = String::with_capacity(source.len());
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next()? } else { c });
+    }
+    Some(unescaped.into())
+}
+
+/// Escape characters which can't be written plainly in a key
+fn escape_key(key: &str) -> Cow<'_, str> {
+    escape(key, is_key_char)
+}
+
+/// Escape characters which can't be written plainly in a value
+fn escape_value(value: &str) -> Cow<'_, str> {
+    escape(value, is_value_char)
+}
+
+fn escape(source: &str, plain: fn(char) -> bool) -> Cow<'_, str> {
+    if source.chars().all(plain) {
+        return source.into();
+    }
+
+    let mut escaped = String::with_capacity(source.len() + 1);
+    for c in source.chars() {
+        if !plain(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.into()
+}
+
+/// Check whether the string can be used as a key in Kubernetes selector syntax
+pub(crate) fn is_key(key: &str) -> bool {
+    !key.is_empty() && key.is_ascii() && key.chars().all(is_key_char)
+}
+
+/// Check whether the string can be used as a value in Kubernetes selector syntax
+pub(crate) fn is_value(value: &str) -> bool {
+    !value.is_empty() && value.is_ascii() && value.chars().all(is_value_char)
+}
+
+fn is_key_char(c: char) -> bool {
+    is_value_char(c) || c == '/'
+}
+
+fn is_value_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '.' | '_')
+}
+
+#[cfg(feature = "extended")]
+fn is_glob_char(c: char) -> bool {
+    is_key_char(c) || matches!(c, '*' | '?' | '[' | ']')
+}
+
+#[cfg(test)]
+mod tests {
+    use logos::Logos;
+
+    use std::borrow::Cow;
+    use std::collections::BTreeMap;
+
+    use crate::{Dialect, Expressions, Limit, ParseError, ParseOptions};
+
+    use super::Expression;
+
+    use super::{ParsedExpression, parse_expression, parse_value_list};
 
     #[test]
     fn values_lexer() {
+        let strict = ParseOptions::strict();
         assert_eq!(
             Some(vec!["a".into(), "b".into(), "c".into()]),
-            parse_value_list(" (a,b, c)")
+            parse_value_list(" (a,b, c)", &strict)
+        );
+        assert_eq!(Some(vec!["a".into()]), parse_value_list("(a)", &strict));
+        assert_eq!(
+            Some(vec!["v1.2".into(), "b-c_d".into()]),
+            parse_value_list("(v1.2, b-c_d)", &strict)
+        );
+        assert_eq!(Some(vec![]), parse_value_list("()", &strict));
+        assert_eq!(Some(vec![]), parse_value_list("", &strict));
+        assert_eq!(None, parse_value_list("(a, 'b c')", &strict));
+        assert_eq!(
+            Some(vec!["a".into(), "b c".into(), "d,'e'".into()]),
+            parse_value_list(r#"(a, 'b c', "d,'e'")"#, &ParseOptions::lenient())
         );
-        assert_eq!(Some(vec!["a".into()]), parse_value_list("(a)"));
-        assert_eq!(Some(vec![]), parse_value_list("()"));
-        assert_eq!(Some(vec![]), parse_value_list(""));
     }
 
     #[test]
@@ -345,11 +1516,11 @@ mod tests {
             parse_expression(&mut lexer).unwrap()
         );
         assert_eq!(
-            Err(ParseError::StringParse("(".into(), 71..72)),
+            Err(ParseError::UnbalancedParens("(".into(), 71..72)),
             parse_expression(&mut lexer)
         );
         assert_eq!(
-            Err(ParseError::StringParse(")".into(), 72..73)),
+            Err(ParseError::UnbalancedParens(")".into(), 72..73)),
             parse_expression(&mut lexer)
         );
         assert_eq!(
@@ -358,4 +1529,593 @@ mod tests {
         );
         assert_eq!(None, parse_expression(&mut lexer).unwrap());
     }
+
+    #[test]
+    fn requirement_parts() {
+        let lenient = ParseOptions::lenient();
+        for (selector, expected) in [
+            (r"a\ b in (x y,,z)", Some(r"a\ b in (x,y,z)")),
+            ("a == 'b c'", Some(r"a=b\ c")),
+            ("a=\t", Some("a=")),
+            ("a>-1", Some("a>-1")),
+            (r"a\=b!=c", Some(r"a\=b!=c")),
+            ("a=\r", None),
+            ("a\rin (b)", None),
+            ("a in (b\rc)", None),
+            ("IN", None),
+            ("!notin", None),
+            (r"i\n", Some("in")),
+            #[cfg(feature = "extended")]
+            (r"a\ ~b*", Some(r"a\ ~b*")),
+        ] {
+            assert_eq!(
+                Expressions::parse_with_options(selector, lenient)
+                    .ok()
+                    .map(|expressions| expressions.to_string())
+                    .as_deref(),
+                expected,
+                "{selector:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn std_parsing() {
+        let expected = Expressions::try_from("a=b,!c").unwrap();
+        assert_eq!(Ok(expected.clone()), "a=b,!c".parse());
+        assert_eq!(
+            Ok(expected.clone()),
+            Expressions::try_from("a=b,!c".to_string())
+        );
+        assert_eq!(Ok(expected), Expressions::try_from(Cow::Borrowed("a=b,!c")));
+        assert!("a=(".parse::<Expressions>().is_err());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for selector in [
+            "",
+            "a=b",
+            "a!=b",
+            "a in (b,c)",
+            "a notin (b)",
+            "a",
+            "!a",
+            "foo.bar/baz=qux,a in (b,c),!d",
+            "a>5,b<-10",
+            "example.com/a in (v1.0,v2),example.com/b notin (x-y),example.com/c,!example.com/d",
+        ] {
+            let parsed = Expressions::try_from(selector).unwrap();
+            assert_eq!(parsed.to_string(), selector);
+            assert_eq!(Expressions::try_from(parsed.to_string()), Ok(parsed));
+        }
+
+        let parsed = Expressions::try_from("a == b, c in ( e,d )").unwrap();
+        assert_eq!(parsed.to_string(), "a=b,c in (d,e)");
+    }
+
+    #[test]
+    fn collection() {
+        let mut expressions: Expressions = [
+            Expression::Equal("a".into(), "b".into()),
+            Expression::Exists("c".into()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(expressions.len(), 2);
+        assert!(!expressions.is_empty());
+        assert!(Expressions::default().is_empty());
+
+        expressions.push(Expression::DoesNotExist("d".into()));
+        expressions.extend([Expression::NotEqual("e".into(), "f".into())]);
+        assert_eq!(expressions.to_string(), "a=b,c,!d,e!=f");
+        assert_eq!(expressions.iter().len(), 4);
+        assert_eq!(
+            expressions.iter().map(Expression::key).collect::<Vec<_>>(),
+            ["a", "c", "d", "e"]
+        );
+        assert_eq!(
+            expressions.iter().next_back(),
+            Some(&Expression::NotEqual("e".into(), "f".into()))
+        );
+
+        let mut keys = vec![];
+        for expression in &expressions {
+            keys.push(expression.key());
+        }
+        assert_eq!(keys, ["a", "c", "d", "e"]);
+        assert_eq!(*expressions.as_slice()[1], Expression::Exists("c".into()));
+    }
+
+    #[test]
+    fn combinators() {
+        let expressions = Expression::equal("app", "web")
+            .and(Expression::r#in("env", ["prod", "stage"]))
+            .and(Expression::not_in("region", ["us"]))
+            .and(Expression::not_equal("tier", "db"))
+            .and(Expression::exists("team"))
+            .and(Expression::does_not_exist("canary"));
+
+        assert_eq!(
+            Ok(expressions),
+            Expressions::try_from(
+                "app=web,env in (prod,stage),region notin (us),tier!=db,team,!canary"
+            )
+        );
+        assert_eq!(
+            Expression::exists("a").and(Expression::exists("b").and(Expression::exists("c"))),
+            Expressions::try_from("a,b,c").unwrap()
+        );
+    }
+
+    #[test]
+    fn match_labels() {
+        let labels = BTreeMap::from([
+            ("app".to_string(), "web".to_string()),
+            ("tier".to_string(), "".to_string()),
+        ]);
+        let expressions =
+            Expressions::from_match_labels(&labels).and(Expressions::try_from("!canary").unwrap());
+        assert_eq!(expressions.to_string(), "app=web,tier=,!canary");
+        assert!(expressions.matches(&labels));
+        assert_eq!(
+            Expressions::from_match_labels(&BTreeMap::new()),
+            Expressions::default()
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let expressions =
+            Expressions::try_from("tier!=db,app=web,env in (b,a),app in (x),!canary,app=web")
+                .unwrap();
+        let canonical = expressions.to_canonical();
+        assert_eq!(
+            canonical.to_string(),
+            "app in (x),app=web,!canary,env in (a,b),tier!=db"
+        );
+        assert_eq!(
+            Expressions::try_from("!canary,env in (a,b),app=web,tier!=db,app in (x)")
+                .unwrap()
+                .to_canonical(),
+            canonical
+        );
+        assert_eq!(canonical.to_canonical(), canonical);
+    }
+
+    #[test]
+    fn key_accessors() {
+        let expressions =
+            Expressions::try_from("app=web,env in (prod,stage,dev),!canary,env!=dev,team").unwrap();
+
+        assert_eq!(
+            expressions.keys().collect::<Vec<_>>(),
+            ["app", "env", "canary", "team"]
+        );
+        assert_eq!(
+            expressions
+                .get("env")
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["env in (dev,prod,stage)", "env!=dev"]
+        );
+        assert_eq!(expressions.get("region").count(), 0);
+
+        assert_eq!(expressions.values_for("app"), Some(["web"].into()));
+        assert_eq!(
+            expressions.values_for("env"),
+            Some(["prod", "stage"].into())
+        );
+        assert_eq!(expressions.values_for("team"), None);
+        assert_eq!(expressions.values_for("region"), None);
+    }
+
+    #[test]
+    fn conjunction() {
+        let user = Expressions::try_from("app=web,tenant=foo").unwrap();
+        let platform = Expressions::try_from("tenant=foo,!restricted").unwrap();
+
+        assert_eq!(
+            user.clone().and(platform.clone()).to_string(),
+            "app=web,tenant=foo,!restricted"
+        );
+        assert_eq!(
+            user.clone() & platform,
+            user.clone().and(Expression::does_not_exist("restricted"))
+        );
+        assert_eq!(
+            (user & Expression::equal("tenant", "foo")).to_string(),
+            "app=web,tenant=foo"
+        );
+        assert_eq!(
+            (Expression::exists("a") & Expression::exists("b") & Expression::exists("a"))
+                .to_string(),
+            "a,b"
+        );
+    }
+
+    #[test]
+    fn integer_comparisons() {
+        assert_eq!(
+            Expressions::try_from("replicas > 5, node.io/gen<10").unwrap(),
+            Expression::greater_than("replicas", 5).and(Expression::less_than("node.io/gen", 10))
+        );
+        assert_eq!(
+            Expressions::try_from("a>b"),
+            Err(ParseError::InvalidValue("a>b".into(), 0..3))
+        );
+        assert_eq!(
+            Expressions::try_from("a>1.5"),
+            Err(ParseError::InvalidValue("a>1.5".into(), 0..5))
+        );
+    }
+
+    #[test]
+    fn all_errors() {
+        assert_eq!(
+            Expressions::try_from_all("app=web, !canary"),
+            Ok(Expressions::try_from("app=web,!canary").unwrap())
+        );
+        assert_eq!(
+            Expressions::try_from_all("=web, tier in (a, b), env=prod, a>b, x=("),
+            Err(vec![
+                ParseError::EmptyKey("=".into(), 0..1),
+                ParseError::InvalidValue("a>b".into(), 32..35),
+                ParseError::UnbalancedParens("(".into(), 39..40),
+            ])
+        );
+    }
+
+    #[test]
+    fn batch() {
+        let selectors = ["app=web", "a=(", "", "tier in (a,b)"].map(String::from);
+        let parsed = Expressions::parse_batch(&selectors);
+        assert_eq!(parsed.len(), 4);
+        for (selector, parsed) in selectors.iter().zip(parsed) {
+            assert_eq!(parsed, Expressions::try_from(selector.as_str()));
+        }
+        assert_eq!(
+            Expressions::parse_batch_with(Dialect::Extended, &["a='b c'"]),
+            [Ok(Expression::equal("a", "b c").into())]
+        );
+    }
+
+    #[test]
+    fn recover() {
+        let selector = "app=web, tier in db, env in (prod, a*), !canary, x=(";
+        let (expressions, warnings) = Expressions::parse_recover(Dialect::Kubernetes, selector);
+        assert_eq!(
+            expressions,
+            Expressions::try_from("app=web,!canary").unwrap()
+        );
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| &selector[warning.span.clone()])
+                .collect::<Vec<_>>(),
+            ["tier in db", "env in (prod, a*)", "x=("]
+        );
+        assert_eq!(
+            warnings[0].error,
+            ParseError::MissingValuesList("in".into(), 14..16)
+        );
+
+        let (expressions, warnings) = Expressions::parse_recover(Dialect::Extended, "a='b c'");
+        assert_eq!((expressions.len(), warnings), (1, vec![]));
+    }
+
+    #[test]
+    fn misspelled_operators() {
+        for (selector, text, span, suggestion) in [
+            ("env not in (a)", "not in", 4..10, "notin"),
+            ("env NOT  in (a)", "NOT  in", 4..11, "notin"),
+            ("env notIn (a)", "notIn", 4..9, "notin"),
+            ("env IN (a)", "IN", 4..6, "in"),
+            ("a=!b", "=!", 1..3, "!="),
+            ("a, b === c", "===", 5..8, "=="),
+            ("a!==b", "!==", 1..4, "!="),
+        ] {
+            assert_eq!(
+                Expressions::try_from(selector),
+                Err(ParseError::MisspelledOperator(
+                    text.into(),
+                    span,
+                    suggestion
+                )),
+                "{selector}"
+            );
+        }
+        assert!(Expressions::try_from("a=,!b").is_ok());
+        assert_eq!(
+            Expressions::try_from("cannot in (a)"),
+            Ok(Expression::r#in("cannot", ["a"]).into())
+        );
+        assert!(Expressions::try_from("a, not in (b)").is_ok());
+    }
+
+    #[test]
+    fn limits() {
+        let options = ParseOptions {
+            max_length: Some(20),
+            max_requirements: Some(2),
+            max_values: Some(2),
+            ..ParseOptions::strict()
+        };
+        assert!(Expressions::parse_with_options("a=b,c in (d,e)", options).is_ok());
+        assert_eq!(
+            Expressions::parse_with_options("a=b,c in (d,e),f", options),
+            Err(ParseError::LimitExceeded(
+                "f".into(),
+                15..16,
+                Limit::Requirements(2)
+            ))
+        );
+        assert_eq!(
+            Expressions::parse_with_options("c notin (d,e,f)", options),
+            Err(ParseError::LimitExceeded(
+                "c notin (d,e,f)".into(),
+                0..15,
+                Limit::Values(2)
+            ))
+        );
+        let error = Expressions::parse_with_options(&"a,".repeat(100), options).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::LimitExceeded(String::new(), 20..200, Limit::Length(20))
+        );
+        assert_eq!(
+            error.to_string(),
+            "selector is longer than 20 bytes: '' at 20..200"
+        );
+
+        let (expressions, warnings) = Expressions::parse_recover(options, "a, b, c, d");
+        assert_eq!(expressions.to_string(), "a,b");
+        assert_eq!(warnings[0].span, 6..10);
+    }
+
+    #[test]
+    fn error_kinds() {
+        for (selector, error) in [
+            ("=web", ParseError::EmptyKey("=".into(), 0..1)),
+            ("a, != b", ParseError::EmptyKey("!".into(), 3..4)),
+            (
+                "a=b,tier in db",
+                ParseError::MissingValuesList("in".into(), 9..11),
+            ),
+            (
+                "tier notin",
+                ParseError::MissingValuesList("notin".into(), 5..10),
+            ),
+            (
+                "tier in (a, b",
+                ParseError::UnbalancedParens("(".into(), 8..9),
+            ),
+            (
+                "a in (b, *)",
+                ParseError::InvalidValue("(b, *)".into(), 5..11),
+            ),
+            ("a=b)", ParseError::UnbalancedParens(")".into(), 3..4)),
+            ("a=~b", ParseError::UnknownOperator("a=~b".into(), 0..4)),
+            ("a ~ b*", ParseError::UnknownOperator("a ~ b*".into(), 0..6)),
+            ("a=b*", ParseError::StringParse("*".into(), 3..4)),
+        ] {
+            let parsed = Expressions::try_from(selector);
+            assert_eq!(parsed, Err(error), "{selector}");
+            let error = parsed.unwrap_err();
+            assert_eq!(&selector[error.span()], error.text());
+        }
+    }
+
+    #[cfg(feature = "kube-rs")]
+    #[test]
+    fn kube_conversion() {
+        use crate::ConversionError;
+
+        let selector = Expressions::try_from("a=b,!c").unwrap().to_selector();
+        assert_eq!(selector.unwrap().to_string(), "a=b,!c");
+        assert_eq!(
+            kube::core::Selector::try_from(Expressions::try_from("a=b,c>1").unwrap()),
+            Err(ConversionError::Unsupported(
+                Expression::greater_than("c", 1).into()
+            ))
+        );
+
+        let expressions = Expressions::try_from("a in (b,c),d notin (e),f=g,h!=i,j,!k").unwrap();
+        let selector = kube::core::Selector::try_from(expressions.clone()).unwrap();
+        assert_eq!(Expressions::from(selector), expressions);
+        assert_eq!(
+            Expression::from(kube::core::Expression::Exists("a".into())),
+            Expression::exists("a")
+        );
+    }
+
+    #[test]
+    fn quoted_values() {
+        let lenient = ParseOptions::lenient();
+        let selector = r#"note = "a, b (c)", tier != 'x="y"', env in ("us east", eu)"#;
+        let expressions = Expressions::parse_with_options(selector, lenient).unwrap();
+        assert_eq!(
+            expressions,
+            Expression::equal("note", "a, b (c)")
+                .and(Expression::not_equal("tier", r#"x="y""#))
+                .and(Expression::r#in("env", ["us east", "eu"]))
+        );
+        assert_eq!(
+            expressions.to_string(),
+            r#"note=a\,\ b\ \(c\),tier!=x\=\"y\",env in (eu,us\ east)"#
+        );
+        assert_eq!(
+            Expressions::parse_with_options(&expressions.to_string(), lenient),
+            Ok(expressions)
+        );
+
+        assert_eq!(
+            Expressions::try_from(r#"a="b c""#),
+            Err(ParseError::InvalidValue(r#"a="b c""#.into(), 0..7))
+        );
+        assert!(Expressions::try_from("a in ('b')").is_err());
+        assert!(Expressions::parse_with_options(r#"a="b"#, lenient).is_err());
+    }
+
+    #[test]
+    fn escapes() {
+        let lenient = ParseOptions::lenient();
+        let expressions =
+            Expressions::parse_with_options(r"a=b\,c, a\=b!=\=, d in (x\,y, z\)), !e\ f", lenient)
+                .unwrap();
+        assert_eq!(
+            expressions,
+            Expression::equal("a", "b,c")
+                .and(Expression::not_equal("a=b", "="))
+                .and(Expression::r#in("d", ["x,y", "z)"]))
+                .and(Expression::does_not_exist("e f"))
+        );
+        assert_eq!(
+            expressions.to_string(),
+            r"a=b\,c,a\=b!=\=,d in (x\,y,z\)),!e\ f"
+        );
+        assert_eq!(
+            Expressions::parse_with_options(&expressions.to_string(), lenient),
+            Ok(expressions)
+        );
+
+        assert!(Expressions::try_from(r"a=b\,c").is_err());
+        assert!(Expressions::parse_with_options(r"a=b\", lenient).is_err());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_operators() {
+        use crate::Pattern;
+
+        let lenient = ParseOptions::lenient();
+        let expressions = Expressions::parse_with_options(
+            r#"app =~ "web-.*", tier!~'db|cache', env=~prod"#,
+            lenient,
+        )
+        .unwrap();
+        assert_eq!(
+            expressions,
+            Expression::matching("app", Pattern::new("web-.*").unwrap())
+                .and(Expression::not_matching(
+                    "tier",
+                    Pattern::new("db|cache").unwrap()
+                ))
+                .and(Expression::matching("env", Pattern::new("prod").unwrap()))
+        );
+        assert_eq!(
+            expressions.to_string(),
+            r#"app=~"web-.*",tier!~"db|cache",env=~"prod""#
+        );
+        assert_eq!(
+            Expressions::parse_with_options(&expressions.to_string(), lenient),
+            Ok(expressions)
+        );
+
+        assert!(Expressions::try_from(r#"app=~"web-.*""#).is_err());
+        assert!(Expressions::parse_with_options(r#"app=~"(""#, lenient).is_err());
+    }
+
+    #[cfg(feature = "extended")]
+    #[test]
+    fn glob_operator() {
+        use crate::GlobPattern;
+
+        let lenient = ParseOptions::lenient();
+        let expressions =
+            Expressions::parse_with_options(r#"app ~ web-*, a\~b~"v1.[0-9] *""#, lenient).unwrap();
+        assert_eq!(
+            expressions,
+            Expression::glob("app", GlobPattern::new("web-*").unwrap()).and(Expression::glob(
+                "a~b",
+                GlobPattern::new("v1.[0-9] *").unwrap()
+            ))
+        );
+        assert_eq!(expressions.to_string(), r#"app~web-*,a\~b~"v1.[0-9] *""#);
+        assert_eq!(
+            Expressions::parse_with_options(&expressions.to_string(), lenient),
+            Ok(expressions)
+        );
+        assert!(Expressions::try_from("app~web-*").is_err());
+    }
+
+    #[test]
+    fn case_insensitive_keywords() {
+        let options = ParseOptions {
+            case_insensitive_keywords: true,
+            ..ParseOptions::strict()
+        };
+        assert_eq!(
+            Expressions::parse_with_options("Env In (Prod), tier NOTIN (db), a notin (b)", options),
+            Ok(Expression::r#in("Env", ["Prod"])
+                .and(Expression::not_in("tier", ["db"]))
+                .and(Expression::not_in("a", ["b"])))
+        );
+        assert_eq!(
+            Expressions::try_from("env In (prod)"),
+            Err(ParseError::MisspelledOperator("In".into(), 4..6, "in"))
+        );
+    }
+
+    #[test]
+    fn empty_values() {
+        assert_eq!(
+            Expressions::try_from("a=,b==, c!=,d = e"),
+            Ok(Expression::equal("a", "")
+                .and(Expression::equal("b", ""))
+                .and(Expression::not_equal("c", ""))
+                .and(Expression::equal("d", "e")))
+        );
+        assert_eq!(
+            Expressions::try_from("a=,c!=").unwrap().to_string(),
+            "a=,c!="
+        );
+        assert!(Expressions::try_from("a>").is_err());
+        assert!(Expressions::try_from("a in ()").is_err());
+    }
+
+    #[test]
+    fn unicode() {
+        let options = ParseOptions {
+            unicode: true,
+            ..ParseOptions::strict()
+        };
+        let selector = "équipe=café, région in (Île-de-France, 東京), !ünused";
+        assert_eq!(
+            Expressions::parse_with_options(selector, options),
+            Ok(Expression::equal("équipe", "café")
+                .and(Expression::r#in("région", ["Île-de-France", "東京"]))
+                .and(Expression::does_not_exist("ünused")))
+        );
+        assert_eq!(
+            Expressions::try_from("team=café"),
+            Err(ParseError::InvalidValue("team=café".into(), 0..10))
+        );
+        assert!(Expressions::try_from("région").is_err());
+        assert!(Expressions::try_from("a in (b, 東京)").is_err());
+        assert_eq!(
+            Expressions::parse_with_options("team='café'", ParseOptions::lenient()),
+            Ok(Expression::equal("team", "café").into())
+        );
+    }
+
+    #[test]
+    fn dialects() {
+        use crate::Dialect;
+
+        let selector = r#"a="b c", d=e"#;
+        assert!(Expressions::parse_with(Dialect::Kubernetes, selector).is_err());
+        assert_eq!(
+            Expressions::parse_with(Dialect::Extended, selector),
+            Ok(Expression::equal("a", "b c").and(Expression::equal("d", "e")))
+        );
+
+        let quoted = ParseOptions {
+            quoted_values: true,
+            ..ParseOptions::strict()
+        };
+        assert!(Expressions::parse_with(quoted, selector).is_ok());
+        assert!(Expressions::parse_with(Dialect::Custom(quoted), r"a=b\,c").is_err());
+        assert_eq!(Dialect::default().options(), ParseOptions::strict());
+    }
 }
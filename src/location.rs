@@ -0,0 +1,73 @@
+use std::fmt;
+
+use crate::ParseError;
+
+/// Line and column of a position in a source, both counted from 1, for
+/// reporting errors in `line:col` form
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    /// Line number, where lines are separated by `\n`
+    pub line: usize,
+
+    /// Column number in characters
+    pub column: usize,
+}
+
+impl Location {
+    /// Location of a byte offset within the source, clamped to its end
+    pub fn of(source: &str, offset: usize) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Location {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl ParseError {
+    /// Location of the start of the offending text in the parsed source
+    pub fn location(&self, source: &str) -> Location {
+        Location::of(source, self.span().start)
+    }
+
+    /// Locations of the start and end of the offending text in the parsed
+    /// source
+    pub fn locations(&self, source: &str) -> (Location, Location) {
+        let span = self.span();
+        (
+            Location::of(source, span.start),
+            Location::of(source, span.end),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expressions, Location};
+
+    #[test]
+    fn locations() {
+        assert_eq!(Location::of("", 0), Location { line: 1, column: 1 });
+        assert_eq!(Location::of("a\nbc\n", 3).to_string(), "2:2");
+        assert_eq!(Location::of("a\nbc\n", 5).to_string(), "3:1");
+        assert_eq!(Location::of("é=b", 2).to_string(), "1:2");
+        assert_eq!(Location::of("ab", 10).to_string(), "1:3");
+
+        let document = "app=web\n# tiers\ntier in db\r\n";
+        let error = Expressions::parse_document(document).unwrap_err();
+        assert_eq!(error.location(document).to_string(), "3:6");
+        let (start, end) = error.locations(document);
+        assert_eq!((start.column, end.column), (6, 8));
+    }
+}
@@ -0,0 +1,172 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+const NIL: usize = usize::MAX;
+
+/// Fixed capacity map evicting the least recently inserted or used entry
+#[derive(Clone, Debug)]
+pub(crate) struct Lru<K, V> {
+    capacity: usize,
+    promote: bool,
+    map: HashMap<K, usize>,
+    slots: Vec<Slot<K, V>>,
+    head: usize,
+    tail: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Slot<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+impl<K: Clone + Eq + Hash, V> Lru<K, V> {
+    /// Create a cache holding at most `capacity` entries.
+    ///
+    /// With `promote` unset neither lookups nor overwrites refresh entries,
+    /// so the oldest inserted entry is evicted first.
+    pub fn new(capacity: usize, promote: bool) -> Self {
+        Self {
+            capacity,
+            promote,
+            map: HashMap::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.slots.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        if self.promote {
+            self.unlink(index);
+            self.push_front(index);
+        }
+        Some(&self.slots[index].value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(&index) = self.map.get(&key) {
+            self.slots[index].value = value;
+            if self.promote {
+                self.unlink(index);
+                self.push_front(index);
+            }
+            return;
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+
+        let index = if self.slots.len() < self.capacity {
+            self.slots.push(Slot {
+                key: key.clone(),
+                value,
+                prev: NIL,
+                next: NIL,
+            });
+            self.slots.len() - 1
+        } else {
+            let index = self.tail;
+            self.unlink(index);
+            let slot = &mut self.slots[index];
+            self.map.remove(&slot.key);
+            slot.key = key.clone();
+            slot.value = value;
+            index
+        };
+
+        self.map.insert(key, index);
+        self.push_front(index);
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = (self.slots[index].prev, self.slots[index].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.slots[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.slots[next].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        self.slots[index].prev = NIL;
+        self.slots[index].next = self.head;
+        match self.head {
+            NIL => self.tail = index,
+            head => self.slots[head].prev = index,
+        }
+        self.head = index;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lru;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = Lru::new(2, true);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get("a"), Some(&1));
+
+        cache.insert("c", 3);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(&1));
+        assert_eq!(cache.get("c"), Some(&3));
+
+        cache.insert("a", 4);
+        cache.insert("d", 5);
+        assert_eq!(cache.get("c"), None);
+        assert_eq!(cache.get("a"), Some(&4));
+    }
+
+    #[test]
+    fn evicts_first_inserted() {
+        let mut cache = Lru::new(2, false);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get("a"), Some(&1));
+
+        cache.insert("c", 3);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(&2));
+
+        cache.insert("b", 4);
+        cache.insert("d", 5);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some(&3));
+
+        let mut cache = Lru::new(0, true);
+        cache.insert("a", 1);
+        assert_eq!(cache.get("a"), None);
+    }
+}
@@ -0,0 +1,247 @@
+//! Building blocks for language servers of files holding selectors.
+//!
+//! Positions are exchanged in UTF-16 code units, the default encoding of the
+//! Language Server Protocol.
+
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, Hover, HoverContents, MarkupContent, MarkupKind,
+    NumberOrString, Position, Range, SemanticToken, SemanticTokenType, SemanticTokensLegend,
+};
+
+use crate::{Dialect, Expression, Expressions, TokenKind, tokenize};
+
+/// Token types of [`semantic_tokens`], in the order of their indices
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::PROPERTY,
+            SemanticTokenType::OPERATOR,
+            SemanticTokenType::STRING,
+        ],
+        token_modifiers: vec![],
+    }
+}
+
+/// Semantic tokens of keys, operators and values, relative to each other as
+/// the protocol expects
+pub fn semantic_tokens(source: &str) -> Vec<SemanticToken> {
+    let mut previous = Position::default();
+    let mut tokens = vec![];
+    for token in tokenize(source) {
+        let token_type = match token.kind {
+            TokenKind::Key => 0,
+            TokenKind::Operator => 1,
+            TokenKind::Value => 2,
+            _ => continue,
+        };
+        let start = position(source, token.span.start);
+        tokens.push(SemanticToken {
+            delta_line: start.line - previous.line,
+            delta_start: match start.line == previous.line {
+                true => start.character - previous.character,
+                false => start.character,
+            },
+            length: utf16_len(token.text),
+            token_type,
+            token_modifiers_bitset: 0,
+        });
+        previous = start;
+    }
+    tokens
+}
+
+/// Description of the requirement at the position, if any
+pub fn hover(dialect: impl Into<Dialect>, source: &str, position: Position) -> Option<Hover> {
+    let target = offset(source, position);
+    let (start, end) = requirement_bounds(source, target);
+    let requirement = &source[start..end];
+    let spanned = Expressions::parse_spanned_with(dialect, requirement).ok()?;
+    let expression = spanned.into_iter().find(|expression| {
+        expression.span.start + start <= target && target <= expression.span.end + start
+    })?;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: describe(&expression),
+        }),
+        range: Some(range(
+            source,
+            expression.span.start + start,
+            expression.span.end + start,
+        )),
+    })
+}
+
+/// Diagnostics of every invalid requirement in the source
+pub fn diagnostics(dialect: impl Into<Dialect>, source: &str) -> Vec<Diagnostic> {
+    let (_, warnings) = Expressions::parse_recover(dialect, source);
+    warnings
+        .into_iter()
+        .map(|warning| {
+            let span = warning.error.span();
+            Diagnostic {
+                range: range(source, span.start, span.end),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(warning.error.code().into())),
+                source: Some("unselector".into()),
+                message: warning.error.hint(source),
+                ..Diagnostic::default()
+            }
+        })
+        .collect()
+}
+
+/// Position of a byte offset in the source
+pub fn position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: utf16_len(&before[line_start..]),
+    }
+}
+
+/// Byte offset of a position in the source, clamped to the end of its line
+pub fn offset(source: &str, position: Position) -> usize {
+    let line_start = match position.line {
+        0 => 0,
+        line => match source.match_indices('\n').nth(line as usize - 1) {
+            Some((i, _)) => i + 1,
+            None => return source.len(),
+        },
+    };
+    let mut units = 0;
+    for (i, c) in source[line_start..].char_indices() {
+        if units >= position.character || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16() as u32;
+    }
+    source.len()
+}
+
+fn range(source: &str, start: usize, end: usize) -> Range {
+    Range {
+        start: position(source, start),
+        end: position(source, end),
+    }
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+/// Bounds of the requirement around the offset, between commas outside
+/// lists of values
+fn requirement_bounds(source: &str, offset: usize) -> (usize, usize) {
+    let mut start = 0;
+    let mut depth = 0usize;
+    for (i, c) in source.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 && i < offset => start = i + 1,
+            ',' if depth == 0 => return (start, i),
+            _ => {}
+        }
+    }
+    (start, source.len())
+}
+
+fn describe(expression: &Expression) -> String {
+    let list = |values: &mut dyn Iterator<Item = &String>| {
+        values
+            .map(|value| format!("`{value}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let key = expression.key();
+    match expression {
+        Expression::In(_, values) => format!("`{key}` is one of {}", list(&mut values.iter())),
+        Expression::NotIn(_, values) => {
+            format!("`{key}` is unset or none of {}", list(&mut values.iter()))
+        }
+        Expression::Equal(_, value) => format!("`{key}` is `{value}`"),
+        Expression::NotEqual(_, value) => format!("`{key}` is unset or not `{value}`"),
+        Expression::Exists(_) => format!("`{key}` is set"),
+        Expression::DoesNotExist(_) => format!("`{key}` is unset"),
+        Expression::GreaterThan(_, value) => {
+            format!("`{key}` is an integer greater than `{value}`")
+        }
+        Expression::LessThan(_, value) => format!("`{key}` is an integer less than `{value}`"),
+        #[cfg(feature = "regex")]
+        Expression::Matches(_, pattern) => format!("`{key}` matches `{}`", pattern.as_str()),
+        #[cfg(feature = "regex")]
+        Expression::NotMatches(_, pattern) => {
+            format!("`{key}` is unset or doesn't match `{}`", pattern.as_str())
+        }
+        #[cfg(feature = "extended")]
+        Expression::Glob(_, pattern) => format!("`{key}` matches `{}`", pattern.as_str()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{HoverContents, Position, SemanticToken};
+
+    use super::{diagnostics, hover, offset, position, semantic_tokens};
+    use crate::Dialect;
+
+    #[test]
+    fn tokens() {
+        let tokens = semantic_tokens("é=a,\n  !b");
+        let token = |delta_line, delta_start, length, token_type| SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        };
+        assert_eq!(
+            tokens,
+            [
+                token(0, 0, 1, 0),
+                token(0, 1, 1, 1),
+                token(0, 1, 1, 2),
+                token(1, 2, 1, 1),
+                token(0, 1, 1, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn hovers() {
+        let source = "app=web, tier in (a, b), x y=";
+        let hovered = hover(Dialect::Kubernetes, source, Position::new(0, 15)).unwrap();
+        let HoverContents::Markup(content) = hovered.contents else {
+            panic!("expected markup");
+        };
+        assert_eq!(content.value, "`tier` is one of `a`, `b`");
+        let range = hovered.range.unwrap();
+        assert_eq!((range.start.character, range.end.character), (9, 23));
+
+        assert!(hover(Dialect::Kubernetes, source, Position::new(0, 8)).is_none());
+        assert!(hover(Dialect::Kubernetes, "a in b, c", Position::new(0, 0)).is_none());
+        assert!(hover(Dialect::Kubernetes, "a in b, c", Position::new(0, 8)).is_some());
+    }
+
+    #[test]
+    fn errors() {
+        let source = "app=web,\ntier in db";
+        let diagnostics = diagnostics(Dialect::Kubernetes, source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 5));
+        assert_eq!(diagnostics[0].range.end, Position::new(1, 7));
+        assert!(diagnostics[0].message.ends_with("expected '('"));
+    }
+
+    #[test]
+    fn positions() {
+        let source = "a𝄞b\ncd";
+        assert_eq!(position(source, 5), Position::new(0, 3));
+        assert_eq!(offset(source, Position::new(0, 3)), 5);
+        assert_eq!(offset(source, Position::new(0, 9)), 6);
+        assert_eq!(offset(source, Position::new(1, 1)), 8);
+        assert_eq!(offset(source, Position::new(5, 0)), source.len());
+    }
+}
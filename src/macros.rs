@@ -0,0 +1,147 @@
+/// Build [`Expressions`](crate::Expressions) from requirements written inline.
+///
+/// Keys are identifiers or string literals, values are any expressions
+/// implementing [`ToString`], or integers for `>` and `<`.
+///
+/// ```
+/// use unselector::{Expressions, selector};
+///
+/// let version = 2;
+/// let expressions = selector! {
+///     app == "nginx",
+///     "example.com/version" != version,
+///     tier in ["web", "api"],
+///     env notin ["dev"],
+///     team,
+///     !canary,
+/// };
+/// assert_eq!(
+///     expressions,
+///     Expressions::try_from(
+///         "app=nginx,example.com/version!=2,tier in (api,web),env notin (dev),team,!canary"
+///     )?
+/// );
+/// # Ok::<(), unselector::ParseError>(())
+/// ```
+#[macro_export]
+macro_rules! selector {
+    ($($requirements:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut expressions = $crate::Expressions::default();
+        $crate::__selector!(expressions; $($requirements)*);
+        expressions
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __selector {
+    ($expressions:ident;) => {};
+    ($expressions:ident; ! $key:tt $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::does_not_exist($crate::__selector_key!($key)));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt == $value:expr $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::equal(
+            $crate::__selector_key!($key),
+            ::std::string::ToString::to_string(&$value),
+        ));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt = $value:expr $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::equal(
+            $crate::__selector_key!($key),
+            ::std::string::ToString::to_string(&$value),
+        ));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt != $value:expr $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::not_equal(
+            $crate::__selector_key!($key),
+            ::std::string::ToString::to_string(&$value),
+        ));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt > $value:expr $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::greater_than($crate::__selector_key!($key), $value));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt < $value:expr $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::less_than($crate::__selector_key!($key), $value));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt in [$($value:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::r#in(
+            $crate::__selector_key!($key),
+            [$(::std::string::ToString::to_string(&$value)),*],
+        ));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt notin [$($value:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::not_in(
+            $crate::__selector_key!($key),
+            [$(::std::string::ToString::to_string(&$value)),*],
+        ));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+    ($expressions:ident; $key:tt $(, $($rest:tt)*)?) => {
+        $expressions.push($crate::Expression::exists($crate::__selector_key!($key)));
+        $crate::__selector!($expressions; $($($rest)*)?);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __selector_key {
+    ($key:ident) => {
+        ::std::string::String::from(::std::stringify!($key))
+    };
+    ($key:literal) => {
+        ::std::string::String::from($key)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Expressions;
+
+    #[test]
+    fn selector_macro() {
+        assert_eq!(selector! {}, Expressions::default());
+        assert_eq!(selector! { app }, Expressions::try_from("app").unwrap());
+        assert_eq!(
+            selector! { !canary, app = "web", tier in ["web", "api",] },
+            Expressions::try_from("!canary,app=web,tier in (web,api)").unwrap()
+        );
+
+        let env = String::from("prod");
+        assert_eq!(
+            selector! { replicas > 1, replicas < 5 },
+            Expressions::try_from("replicas>1,replicas<5").unwrap()
+        );
+        assert_eq!(
+            selector! { "app.kubernetes.io/name" == "nginx", env notin [env, "dev"] },
+            Expressions::try_from("app.kubernetes.io/name=nginx,env notin (prod,dev)").unwrap()
+        );
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn selector_str_macro() {
+        use crate::selector_str;
+
+        assert_eq!(selector_str!(""), Expressions::default());
+        assert_eq!(
+            selector_str!("app=web, env in (prod,stage) ,tier!=db,team !canary"),
+            Expressions::try_from("app=web, env in (prod,stage) ,tier!=db,team !canary").unwrap()
+        );
+        assert_eq!(
+            selector_str!(r"example.com/a == v1.0,b notin (x-y, z_1)"),
+            Expressions::try_from("example.com/a == v1.0,b notin (x-y, z_1)").unwrap()
+        );
+        assert_eq!(
+            selector_str!("a > 1, b<-2"),
+            Expressions::try_from("a > 1, b<-2").unwrap()
+        );
+    }
+}
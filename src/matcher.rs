@@ -0,0 +1,440 @@
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+
+use smallvec::{SmallVec, smallvec};
+
+#[cfg(feature = "extended")]
+use crate::GlobPattern;
+#[cfg(feature = "regex")]
+use crate::Pattern;
+use crate::{Expressions, Interner, LabelSet, Selector, requirements, requirements::Bounds};
+
+/// Sets up to this size are probed linearly instead of hashing the value
+const SMALL_SET: usize = 8;
+
+/// Selectors with more keys than this are evaluated by walking the labels
+/// and finding each one in the sorted keys, instead of looking up every key
+/// in the labels
+const INDEXED: usize = 8;
+
+/// A selector compiled for repeated evaluation.
+///
+/// Requirements are folded per key, so each label is looked up once no matter
+/// how many requirements refer to it. Keys restricted to specific values are
+/// checked first, as they are the most likely to reject a label set.
+///
+/// Selectors with many keys are instead evaluated in a single pass over the
+/// labels, binary searching each label among the sorted keys.
+#[derive(Clone, Debug)]
+pub struct Matcher {
+    keys: Vec<KeyMatcher>,
+    /// Positions in `keys` sorted by key, empty when the keys are few
+    index: Box<[usize]>,
+    /// Number of keys that must be present
+    required: usize,
+    never: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Presence {
+    Required,
+    Forbidden,
+    Any,
+}
+
+#[derive(Clone, Debug)]
+struct KeyMatcher {
+    key: Arc<str>,
+    presence: Presence,
+    allowed: Option<ValueLookup>,
+    forbidden: ValueLookup,
+    bounds: Bounds,
+    #[cfg(feature = "regex")]
+    patterns: Box<[Pattern]>,
+    #[cfg(feature = "regex")]
+    forbidden_patterns: Box<[Pattern]>,
+    #[cfg(feature = "extended")]
+    globs: Box<[GlobPattern]>,
+}
+
+impl KeyMatcher {
+    fn admits(&self, value: &str) -> bool {
+        #[cfg(feature = "regex")]
+        if !self.patterns.iter().all(|p| p.is_match(value))
+            || self.forbidden_patterns.iter().any(|p| p.is_match(value))
+        {
+            return false;
+        }
+        #[cfg(feature = "extended")]
+        if !self.globs.iter().all(|p| p.is_match(value)) {
+            return false;
+        }
+        self.bounds.contains(value)
+    }
+
+    fn matches(&self, value: Option<&str>) -> bool {
+        match (&self.presence, value) {
+            (Presence::Forbidden, value) => value.is_none(),
+            (Presence::Required, None) => false,
+            (Presence::Any, None) => true,
+            (_, Some(value)) => match &self.allowed {
+                Some(allowed) => allowed.contains(value),
+                None => !self.forbidden.contains(value) && self.admits(value),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum ValueLookup {
+    Small(Box<[Box<str>]>),
+    Large(HashSet<Box<str>>),
+}
+
+impl ValueLookup {
+    fn contains(&self, value: &str) -> bool {
+        match self {
+            ValueLookup::Small(values) => values.iter().any(|v| **v == *value),
+            ValueLookup::Large(values) => values.contains(value),
+        }
+    }
+}
+
+impl From<BTreeSet<&str>> for ValueLookup {
+    fn from(values: BTreeSet<&str>) -> Self {
+        let values = values.into_iter().map(Box::from);
+        if values.len() <= SMALL_SET {
+            ValueLookup::Small(values.collect())
+        } else {
+            ValueLookup::Large(values.collect())
+        }
+    }
+}
+
+impl Matcher {
+    /// Check whether the compiled selector matches the given labels
+    pub fn matches(&self, labels: &impl LabelSet) -> bool {
+        if self.never {
+            return false;
+        }
+        if self.index.is_empty() {
+            return self
+                .keys
+                .iter()
+                .all(|key| key.matches(labels.get(&key.key)));
+        }
+
+        // Keys missing from the labels only fail when they are required, so
+        // counting the distinct required keys seen is enough to check them
+        let mut seen: SmallVec<[u64; 4]> = smallvec![0; self.index.len().div_ceil(64)];
+        let mut required = 0;
+        for (name, value) in labels.iter() {
+            let Ok(position) =
+                (self.index).binary_search_by(|&position| (*self.keys[position].key).cmp(name))
+            else {
+                continue;
+            };
+            let key = &self.keys[self.index[position]];
+            if !key.matches(Some(value)) {
+                return false;
+            }
+            let (word, bit) = (position / 64, 1 << (position % 64));
+            if key.presence == Presence::Required && seen[word] & bit == 0 {
+                seen[word] |= bit;
+                required += 1;
+            }
+        }
+        required == self.required
+    }
+
+    /// Match every label set, returning outcomes in iteration order
+    pub fn matches_all<'a, L: LabelSet + 'a>(
+        &self,
+        label_sets: impl IntoIterator<Item = &'a L>,
+    ) -> Vec<bool> {
+        if self.never {
+            return label_sets.into_iter().map(|_| false).collect();
+        }
+        label_sets
+            .into_iter()
+            .map(|labels| self.matches(labels))
+            .collect()
+    }
+
+    /// Keys the compiled selector refers to
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(|key| &*key.key)
+    }
+
+    /// Turn the matcher into a predicate over label sets
+    pub fn into_predicate<L: LabelSet>(self) -> impl Fn(&L) -> bool + Send + Sync {
+        move |labels| self.matches(labels)
+    }
+}
+
+impl From<&Expressions> for Matcher {
+    fn from(expressions: &Expressions) -> Self {
+        Matcher::build(expressions, |key| key.into())
+    }
+}
+
+impl Matcher {
+    fn build(expressions: &Expressions, mut key: impl FnMut(&str) -> Arc<str>) -> Self {
+        let keys = requirements::by_key(expressions.iter());
+        let never = keys.values().any(|key| !key.satisfiable());
+
+        let mut keys: Vec<_> = keys
+            .into_iter()
+            .map(|(name, requirements)| KeyMatcher {
+                key: key(name),
+                presence: match (requirements.exists, requirements.absent) {
+                    (true, _) => Presence::Required,
+                    (false, true) => Presence::Forbidden,
+                    (false, false) => Presence::Any,
+                },
+                allowed: requirements.permitted().map(ValueLookup::from),
+                forbidden: requirements.forbidden.clone().into(),
+                bounds: requirements.bounds,
+                #[cfg(feature = "regex")]
+                patterns: requirements.patterns.iter().map(|&p| p.clone()).collect(),
+                #[cfg(feature = "regex")]
+                forbidden_patterns: (requirements.forbidden_patterns.iter())
+                    .map(|&p| p.clone())
+                    .collect(),
+                #[cfg(feature = "extended")]
+                globs: requirements.globs.iter().map(|&p| p.clone()).collect(),
+            })
+            .collect();
+        keys.sort_by(|a, b| {
+            (a.allowed.is_none(), &a.presence).cmp(&(b.allowed.is_none(), &b.presence))
+        });
+
+        let mut index: Box<[usize]> = match keys.len() {
+            0..=INDEXED => Box::default(),
+            len => (0..len).collect(),
+        };
+        index.sort_by(|&a, &b| keys[a].key.cmp(&keys[b].key));
+        let required = (keys.iter())
+            .filter(|key| key.presence == Presence::Required)
+            .count();
+
+        Matcher {
+            keys,
+            index,
+            required,
+            never,
+        }
+    }
+}
+
+impl Expressions {
+    /// Compile the selector for repeated evaluation
+    pub fn compile(&self) -> Matcher {
+        self.into()
+    }
+
+    /// Compile the selector, sharing its keys with every other matcher
+    /// compiled with the interner
+    pub fn compile_with(&self, interner: &Interner) -> Matcher {
+        Matcher::build(self, |key| interner.intern(key))
+    }
+
+    /// Match every label set, returning outcomes in iteration order.
+    ///
+    /// The selector is compiled once for the whole batch.
+    pub fn matches_all<'a, L: LabelSet + 'a>(
+        &self,
+        label_sets: impl IntoIterator<Item = &'a L>,
+    ) -> Vec<bool> {
+        self.compile().matches_all(label_sets)
+    }
+
+    /// Compile the selector into a predicate over label sets, usable with
+    /// [`Vec::retain`], [`Iterator::filter`] and the like.
+    pub fn to_predicate<L: LabelSet>(self) -> impl Fn(&L) -> bool + Send + Sync {
+        self.compile().into_predicate()
+    }
+}
+
+impl Selector {
+    /// Compile the selector for repeated evaluation
+    pub fn compile(&self) -> Matcher {
+        self.0.compile()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{Expressions, LabelSet};
+
+    #[test]
+    fn predicate() {
+        let mut label_sets = vec![
+            BTreeMap::from([("app".to_string(), "web".to_string())]),
+            BTreeMap::from([("app".to_string(), "api".to_string())]),
+        ];
+        let selector = Expressions::try_from("app=web").unwrap();
+
+        assert_eq!(
+            label_sets
+                .iter()
+                .filter(selector.clone().to_predicate())
+                .count(),
+            1
+        );
+
+        label_sets.retain(selector.to_predicate());
+        assert_eq!(label_sets.len(), 1);
+        assert_eq!(label_sets[0]["app"], "web");
+    }
+
+    #[test]
+    fn compiled_matches_expressions() {
+        let label_sets = [
+            BTreeMap::new(),
+            BTreeMap::from([("app", "web")]),
+            BTreeMap::from([("app", "api"), ("env", "prod")]),
+            BTreeMap::from([("app", "web"), ("env", "prod"), ("canary", "true")]),
+            BTreeMap::from([("app", "web"), ("env", "dev"), ("tier", "a")]),
+            BTreeMap::from([("app", "web"), ("env", "stage"), ("tier", "k")]),
+            BTreeMap::from([("app", "2"), ("tier", "1")]),
+            BTreeMap::from([("app", "4"), ("tier", "7")]),
+        ];
+
+        for selector in [
+            "",
+            "app=web",
+            "app!=web",
+            "app=web,app=api",
+            "app,!canary",
+            "app=web,env in (prod,stage),!canary",
+            "env notin (dev),env!=stage",
+            "env in (prod,dev),env notin (prod)",
+            "tier in (a,b,c,d,e,f,g,h,i,j),tier notin (a)",
+            "tier notin (a,b,c,d,e,f,g,h,i)",
+            "canary,!canary",
+            "tier>0",
+            "app>1,app<3",
+            "tier in (a,1,2),tier<2",
+            "app<5,app!=4",
+        ] {
+            let selector = Expressions::try_from(selector).unwrap();
+            let matcher = selector.compile();
+            for labels in &label_sets {
+                assert_eq!(
+                    matcher.matches(labels),
+                    selector.matches(labels),
+                    "{selector} on {labels:?}"
+                );
+            }
+            assert_eq!(
+                selector.matches_all(&label_sets),
+                label_sets
+                    .iter()
+                    .map(|labels| selector.matches(labels))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn indexed_keys() {
+        let selector = Expressions::try_from(
+            "a=1,b!=2,c in (3,4),d notin (5),e,!f,g>6,h<7,i,j=10,k in (11,12)",
+        )
+        .unwrap();
+        let matcher = selector.compile();
+        assert!(!matcher.index.is_empty());
+
+        let matching = BTreeMap::from([
+            ("a", "1"),
+            ("c", "4"),
+            ("e", ""),
+            ("g", "8"),
+            ("h", "3"),
+            ("i", "x"),
+            ("j", "10"),
+            ("k", "12"),
+            ("z", "unrelated"),
+        ]);
+        assert!(matcher.matches(&matching));
+        assert!(selector.matches(&matching));
+        for (key, value) in [
+            ("a", Some("2")),
+            ("a", None),
+            ("b", Some("2")),
+            ("d", Some("5")),
+            ("e", None),
+            ("f", Some("")),
+            ("g", Some("5")),
+            ("h", Some("9")),
+            ("h", None),
+            ("k", None),
+        ] {
+            let mut labels = matching.clone();
+            match value {
+                Some(value) => labels.insert(key, value),
+                None => labels.remove(key),
+            };
+            assert!(!matcher.matches(&labels), "{labels:?}");
+            assert_eq!(matcher.matches(&labels), selector.matches(&labels));
+        }
+    }
+
+    /// Labels yielding every entry as listed, duplicates included
+    struct Listed(Vec<(&'static str, &'static str)>);
+
+    impl LabelSet for Listed {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+        }
+
+        fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+            self.0.iter().copied()
+        }
+    }
+
+    #[test]
+    fn indexed_duplicate_keys() {
+        let selector = Expressions::try_from("a,b,c,d,e,f,g,h,i").unwrap();
+        let matcher = selector.compile();
+        assert!(!matcher.index.is_empty());
+
+        let mut labels = Listed(
+            ["a", "b", "c", "d", "e", "f", "g", "h", "i"]
+                .map(|k| (k, ""))
+                .into(),
+        );
+        assert!(matcher.matches(&labels));
+        labels.0[1] = ("a", "");
+        assert!(!matcher.matches(&labels));
+        assert!(!selector.matches(&labels));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn compiled_patterns() {
+        let selector = Expressions::parse_with_options(
+            r#"app=~"web-.*",app!~".*-canary",tier in (web-1,web-2,api),tier=~"web.*""#,
+            crate::ParseOptions::lenient(),
+        )
+        .unwrap();
+        let matcher = selector.compile();
+        for (app, tier) in [
+            ("web-1", "web-2"),
+            ("web-canary", "web-1"),
+            ("api", "web-1"),
+            ("web-1", "api"),
+        ] {
+            let labels = BTreeMap::from([("app", app), ("tier", tier)]);
+            assert_eq!(
+                matcher.matches(&labels),
+                selector.matches(&labels),
+                "{labels:?}"
+            );
+        }
+        assert!(matcher.matches(&BTreeMap::from([("app", "web-1"), ("tier", "web-1")])));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{Expression, Expressions, LabelSet};
+
+impl Expression {
+    /// Check whether the expression is satisfied by the given labels
+    pub fn matches(&self, labels: &impl LabelSet) -> bool {
+        match self {
+            Expression::In(key, values) => labels.get(key).is_some_and(|v| values.contains(v)),
+            Expression::NotIn(key, values) => labels.get(key).is_none_or(|v| !values.contains(v)),
+            Expression::Equal(key, value) => labels.get(key) == Some(value),
+            Expression::NotEqual(key, value) => labels.get(key) != Some(value),
+            Expression::Exists(key) => labels.contains_key(key),
+            Expression::DoesNotExist(key) => !labels.contains_key(key),
+            Expression::GreaterThan(key, bound) => {
+                compare(labels.get(key), bound).is_some_and(|o| o.is_gt())
+            }
+            Expression::LessThan(key, bound) => {
+                compare(labels.get(key), bound).is_some_and(|o| o.is_lt())
+            }
+            #[cfg(feature = "regex")]
+            Expression::Matches(key, pattern) => {
+                labels.get(key).is_some_and(|v| pattern.is_match(v))
+            }
+            #[cfg(feature = "regex")]
+            Expression::NotMatches(key, pattern) => {
+                labels.get(key).is_none_or(|v| !pattern.is_match(v))
+            }
+            #[cfg(feature = "extended")]
+            Expression::Glob(key, pattern) => labels.get(key).is_some_and(|v| pattern.is_match(v)),
+        }
+    }
+}
+
+/// Order a label value against an integer bound, if both are integers
+pub(crate) fn compare(value: Option<&str>, bound: &str) -> Option<std::cmp::Ordering> {
+    let value: i64 = value?.parse().ok()?;
+    Some(value.cmp(&bound.parse().ok()?))
+}
+
+impl Expressions {
+    /// Check whether all expressions are satisfied by the given labels.
+    ///
+    /// An empty selector matches everything.
+    pub fn matches(&self, labels: &impl LabelSet) -> bool {
+        self.iter().all(|expr| expr.matches(labels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::{Expression, Expressions, LabelSet};
+
+    fn labels(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expression_matches() {
+        let on = labels(&[("app", "web"), ("env", "prod")]);
+
+        assert!(Expression::Equal("app".into(), "web".into()).matches(&on));
+        assert!(!Expression::Equal("app".into(), "api".into()).matches(&on));
+        assert!(!Expression::Equal("team".into(), "a".into()).matches(&on));
+
+        assert!(Expression::NotEqual("app".into(), "api".into()).matches(&on));
+        assert!(Expression::NotEqual("team".into(), "a".into()).matches(&on));
+        assert!(!Expression::NotEqual("app".into(), "web".into()).matches(&on));
+
+        assert!(Expression::In("env".into(), ["prod".into(), "dev".into()].into()).matches(&on));
+        assert!(!Expression::In("env".into(), ["dev".into()].into()).matches(&on));
+        assert!(!Expression::In("team".into(), ["a".into()].into()).matches(&on));
+
+        assert!(Expression::NotIn("env".into(), ["dev".into()].into()).matches(&on));
+        assert!(Expression::NotIn("team".into(), ["a".into()].into()).matches(&on));
+        assert!(!Expression::NotIn("env".into(), ["prod".into()].into()).matches(&on));
+
+        assert!(Expression::Exists("app".into()).matches(&on));
+        assert!(!Expression::Exists("team".into()).matches(&on));
+
+        assert!(Expression::DoesNotExist("team".into()).matches(&on));
+        assert!(!Expression::DoesNotExist("app".into()).matches(&on));
+    }
+
+    #[test]
+    fn empty_values() {
+        let empty = labels(&[("app", "")]);
+        let missing = labels(&[]);
+
+        assert!(Expression::equal("app", "").matches(&empty));
+        assert!(!Expression::equal("app", "").matches(&missing));
+        assert!(!Expression::not_equal("app", "").matches(&empty));
+        assert!(Expression::not_equal("app", "").matches(&missing));
+        assert!(Expression::exists("app").matches(&empty));
+    }
+
+    #[test]
+    fn integer_comparisons() {
+        let on = labels(&[("replicas", "3"), ("app", "web")]);
+
+        assert!(Expression::greater_than("replicas", 2).matches(&on));
+        assert!(!Expression::greater_than("replicas", 3).matches(&on));
+        assert!(Expression::less_than("replicas", 4).matches(&on));
+        assert!(!Expression::less_than("replicas", -1).matches(&on));
+        assert!(!Expression::greater_than("app", 0).matches(&on));
+        assert!(!Expression::less_than("missing", 10).matches(&on));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matches() {
+        use crate::Pattern;
+
+        let on = labels(&[("app", "web-1")]);
+        let web = || Pattern::new("web-[0-9]+").unwrap();
+
+        assert!(Expression::matching("app", web()).matches(&on));
+        assert!(!Expression::matching("app", Pattern::new("web").unwrap()).matches(&on));
+        assert!(!Expression::matching("team", web()).matches(&on));
+        assert!(!Expression::not_matching("app", web()).matches(&on));
+        assert!(Expression::not_matching("team", web()).matches(&on));
+    }
+
+    #[cfg(feature = "extended")]
+    #[test]
+    fn glob_matches() {
+        use crate::GlobPattern;
+
+        let on = labels(&[("app", "web-1")]);
+        let web = || GlobPattern::new("web-?").unwrap();
+
+        assert!(Expression::glob("app", web()).matches(&on));
+        assert!(!Expression::glob("app", GlobPattern::new("api-*").unwrap()).matches(&on));
+        assert!(!Expression::glob("team", web()).matches(&on));
+    }
+
+    #[test]
+    fn expressions_matches() {
+        let on = labels(&[("app", "web"), ("env", "prod")]);
+
+        assert!(Expressions::default().matches(&on));
+        assert!(Expressions::default().matches(&BTreeMap::<String, String>::new()));
+
+        let selector = Expressions::try_from("app=web,env in (prod, stage),!canary").unwrap();
+        assert!(selector.matches(&on));
+        assert!(!selector.matches(&labels(&[("app", "web"), ("env", "dev")])));
+        assert!(!selector.matches(&labels(&[("app", "web"), ("env", "prod"), ("canary", "")])));
+    }
+
+    #[test]
+    fn generic_label_sets() {
+        struct Single(&'static str, &'static str);
+
+        impl LabelSet for Single {
+            fn get(&self, key: &str) -> Option<&str> {
+                (self.0 == key).then_some(self.1)
+            }
+
+            fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+                std::iter::once((self.0, self.1))
+            }
+        }
+
+        let selector = Expressions::try_from("app=web,!canary").unwrap();
+        assert!(selector.matches(&HashMap::from([("app", "web")])));
+        assert!(selector.matches(&BTreeMap::from([("app", "web")])));
+        assert!(selector.matches(&Single("app", "web")));
+        assert!(!selector.matches(&Single("canary", "true")));
+    }
+}
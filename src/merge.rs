@@ -0,0 +1,114 @@
+use thiserror::Error;
+
+use crate::Expressions;
+
+/// Resolution of keys which two merged selectors place different
+/// requirements on, see [`Expressions::merge`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MergePolicy {
+    /// Fail the merge
+    #[default]
+    Error,
+
+    /// Keep the requirements of the selector merged into
+    PreferSelf,
+
+    /// Keep the requirements of the selector merged in
+    PreferOther,
+
+    /// Keep the requirements of both, failing if they contradict each other
+    Intersect,
+}
+
+/// Indicates failure to merge selectors
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum MergeError {
+    #[error("selectors place different requirements on key '{0}'")]
+    Conflict(String),
+
+    #[error("requirements on key '{0}' contradict each other")]
+    Unsatisfiable(String),
+}
+
+impl Expressions {
+    /// Combine with the requirements of another selector, such as team
+    /// overrides layered over a platform selector.
+    ///
+    /// Keys only one selector refers to keep their requirements, and keys
+    /// with equivalent requirements in both keep those of this selector.
+    /// The policy decides what happens to the other keys.
+    pub fn merge(
+        &self,
+        other: &Expressions,
+        policy: MergePolicy,
+    ) -> Result<Expressions, MergeError> {
+        let mut merged = Expressions::default();
+        for key in self.keys() {
+            let ours: Expressions = self.get(key).cloned().collect();
+            let theirs: Expressions = other.get(key).cloned().collect();
+            let requirements = match policy {
+                _ if theirs.is_empty() || ours.equivalent(&theirs) => ours,
+                MergePolicy::Error => return Err(MergeError::Conflict(key.into())),
+                MergePolicy::PreferSelf => ours,
+                MergePolicy::PreferOther => theirs,
+                MergePolicy::Intersect => match ours.and(theirs) {
+                    both if both.is_satisfiable() => both,
+                    _ => return Err(MergeError::Unsatisfiable(key.into())),
+                },
+            };
+            merged = merged.and(requirements);
+        }
+        let added = other.iter().filter(|e| self.get(e.key()).next().is_none());
+        Ok(merged.and(added.cloned().collect::<Expressions>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expressions, MergeError, MergePolicy};
+
+    fn merge(a: &str, b: &str, policy: MergePolicy) -> Result<String, MergeError> {
+        let (a, b) = (
+            Expressions::try_from(a).unwrap(),
+            Expressions::try_from(b).unwrap(),
+        );
+        a.merge(&b, policy).map(|merged| merged.to_string())
+    }
+
+    #[test]
+    fn policies() {
+        let (base, team) = (
+            "env in (prod,stage),!canary",
+            "team=a,env=prod,canary notin (x)",
+        );
+        assert_eq!(
+            merge(base, team, MergePolicy::Error),
+            Err(MergeError::Conflict("env".into()))
+        );
+        assert_eq!(
+            merge(base, team, MergePolicy::PreferSelf).as_deref(),
+            Ok("env in (prod,stage),!canary,team=a")
+        );
+        assert_eq!(
+            merge(base, team, MergePolicy::PreferOther).as_deref(),
+            Ok("env=prod,canary notin (x),team=a")
+        );
+        assert_eq!(
+            merge(base, team, MergePolicy::Intersect).as_deref(),
+            Ok("env in (prod,stage),env=prod,!canary,canary notin (x),team=a")
+        );
+        assert_eq!(
+            merge("env=dev", team, MergePolicy::Intersect),
+            Err(MergeError::Unsatisfiable("env".into()))
+        );
+    }
+
+    #[test]
+    fn equivalent_keys() {
+        assert_eq!(
+            merge("a=b,c", "a in (b),d", MergePolicy::Error).as_deref(),
+            Ok("a=b,c,d")
+        );
+        assert_eq!(merge("", "", MergePolicy::Error).as_deref(), Ok(""));
+    }
+}
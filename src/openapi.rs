@@ -0,0 +1,82 @@
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::schema::{ObjectBuilder, OneOfBuilder, Ref, Schema, Type};
+use utoipa::openapi::{RefOr, Required};
+use utoipa::{IntoParams, PartialSchema, ToSchema};
+
+use crate::serde_string::{DESCRIPTION, PATTERN};
+use crate::{AnySelector, LabelSelector, SelectorQuery};
+
+/// Schema of selector strings
+fn selector_string() -> Schema {
+    ObjectBuilder::new()
+        .schema_type(Type::String)
+        .description(Some(DESCRIPTION))
+        .pattern(Some(PATTERN))
+        .into()
+}
+
+impl PartialSchema for AnySelector {
+    fn schema() -> RefOr<Schema> {
+        OneOfBuilder::new()
+            .item(selector_string())
+            .item(Ref::from_schema_name(LabelSelector::name()))
+            .into()
+    }
+}
+
+impl ToSchema for AnySelector {
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        schemas.push((LabelSelector::name().into(), LabelSelector::schema()));
+        LabelSelector::schemas(schemas);
+    }
+}
+
+impl IntoParams for SelectorQuery {
+    fn into_params(parameter_in_provider: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![
+            ParameterBuilder::new()
+                .name("labelSelector")
+                .parameter_in(parameter_in_provider().unwrap_or(ParameterIn::Query))
+                .required(Required::False)
+                .schema(Some(selector_string()))
+                .build(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::openapi::path::ParameterIn;
+    use utoipa::{IntoParams, PartialSchema, ToSchema};
+
+    use crate::{AnySelector, LabelSelector, SelectorQuery};
+
+    #[test]
+    fn schemas() {
+        let schema = serde_json::to_value(AnySelector::schema()).unwrap();
+        assert_eq!(schema["oneOf"][0]["type"], "string");
+        assert_eq!(
+            schema["oneOf"][1]["$ref"],
+            "#/components/schemas/LabelSelector"
+        );
+
+        let mut schemas = vec![];
+        AnySelector::schemas(&mut schemas);
+        assert_eq!(schemas[0].0, "LabelSelector");
+
+        let schema = serde_json::to_value(LabelSelector::schema()).unwrap();
+        assert!(schema["properties"]["matchExpressions"].is_object());
+    }
+
+    #[test]
+    fn query_params() {
+        let params = SelectorQuery::into_params(|| None);
+        let param = serde_json::to_value(&params[0]).unwrap();
+        assert_eq!(param["name"], "labelSelector");
+        assert_eq!(param["in"], "query");
+        assert_eq!(param["schema"]["pattern"], crate::serde_string::PATTERN);
+
+        let params = SelectorQuery::into_params(|| Some(ParameterIn::Header));
+        assert_eq!(serde_json::to_value(&params[0]).unwrap()["in"], "header");
+    }
+}
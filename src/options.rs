@@ -0,0 +1,116 @@
+use std::fmt;
+
+/// Grammar extensions accepted on top of the Kubernetes selector syntax.
+///
+/// The default options accept exactly what Kubernetes accepts, without limits.
+/// Limits apply to the whole input of every parser taking the options, such
+/// as all alternatives of a [`crate::SelectorDnf`] or all lines of a document.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// Allow single or double quoted values, which may contain any character
+    /// except the quote itself
+    pub quoted_values: bool,
+
+    /// Allow backslash escapes in keys and values, such as `a\,b`
+    pub escapes: bool,
+
+    /// Accept the `in` and `notin` keywords in any case, such as `In` or `NOTIN`
+    pub case_insensitive_keywords: bool,
+
+    /// Allow Unicode letters and digits in unquoted keys and values, instead
+    /// of ASCII only
+    pub unicode: bool,
+
+    /// Allow the `=~` and `!~` regular expression operators
+    #[cfg(feature = "regex")]
+    pub regex_operators: bool,
+
+    /// Allow the `~` wildcard operator
+    #[cfg(feature = "extended")]
+    pub glob_operator: bool,
+
+    /// Reject selectors longer than this many bytes
+    pub max_length: Option<usize>,
+
+    /// Reject selectors with more requirements than this
+    pub max_requirements: Option<usize>,
+
+    /// Reject `in` and `notin` requirements with more values than this
+    pub max_values: Option<usize>,
+}
+
+/// A limit of [`ParseOptions`] exceeded by a selector
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// Most bytes in a selector
+    Length(usize),
+
+    /// Most requirements in a selector
+    Requirements(usize),
+
+    /// Most values in a set based requirement
+    Values(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Length(max) => write!(f, "selector is longer than {max} bytes"),
+            Limit::Requirements(max) => write!(f, "selector has more than {max} requirements"),
+            Limit::Values(max) => write!(f, "requirement has more than {max} values"),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Options for the Kubernetes selector syntax
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Options enabling every extension
+    pub fn lenient() -> Self {
+        ParseOptions {
+            quoted_values: true,
+            escapes: true,
+            case_insensitive_keywords: true,
+            unicode: true,
+            #[cfg(feature = "regex")]
+            regex_operators: true,
+            #[cfg(feature = "extended")]
+            glob_operator: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Grammar variant used to parse selectors
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// The Kubernetes selector syntax, as accepted by kubectl
+    #[default]
+    Kubernetes,
+
+    /// The Kubernetes syntax with every extension enabled
+    Extended,
+
+    /// The Kubernetes syntax with a custom set of extensions
+    Custom(ParseOptions),
+}
+
+impl Dialect {
+    /// Grammar extensions enabled by the dialect
+    pub fn options(&self) -> ParseOptions {
+        match self {
+            Dialect::Kubernetes => ParseOptions::strict(),
+            Dialect::Extended => ParseOptions::lenient(),
+            Dialect::Custom(options) => *options,
+        }
+    }
+}
+
+impl From<ParseOptions> for Dialect {
+    fn from(options: ParseOptions) -> Self {
+        Dialect::Custom(options)
+    }
+}
@@ -0,0 +1,56 @@
+use rayon::prelude::*;
+
+use crate::{Expressions, LabelSet, Matcher, ParseOptions, Result};
+
+impl Matcher {
+    /// Match label sets in parallel, returning indices of the matching ones
+    /// in ascending order
+    pub fn par_matches<L: LabelSet + Sync>(&self, label_sets: &[L]) -> Vec<usize> {
+        label_sets
+            .par_iter()
+            .enumerate()
+            .filter(|(_, labels)| self.matches(labels))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+impl Expressions {
+    /// Match label sets in parallel, returning indices of the matching ones
+    /// in ascending order.
+    ///
+    /// The selector is compiled once for the whole slice.
+    pub fn par_matches<L: LabelSet + Sync>(&self, label_sets: &[L]) -> Vec<usize> {
+        self.compile().par_matches(label_sets)
+    }
+}
+
+/// Parse selectors in parallel, keeping their order
+pub(crate) fn parse_all<S: AsRef<str> + Sync>(
+    selectors: &[S],
+    options: ParseOptions,
+) -> Vec<Result<Expressions>> {
+    selectors
+        .par_iter()
+        .map(|selector| Expressions::parse_with_options(selector.as_ref(), options))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::Expressions;
+
+    #[test]
+    fn par_matches() {
+        let label_sets: Vec<_> = (0..1000)
+            .map(|i| BTreeMap::from([("app", if i % 3 == 0 { "web" } else { "api" })]))
+            .collect();
+        let selector = Expressions::try_from("app=web").unwrap();
+
+        let matching = selector.par_matches(&label_sets);
+        assert_eq!(matching.len(), 334);
+        assert_eq!(matching, (0..1000).step_by(3).collect::<Vec<_>>());
+    }
+}
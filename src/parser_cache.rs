@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use crate::{Dialect, Expressions, ParseOptions, Result, lru::Lru};
+
+/// Parsed selectors memoized by their text, for services receiving the same
+/// selectors over and over.
+///
+/// Only successfully parsed selectors are cached, invalid ones are parsed
+/// again on every call.
+#[derive(Clone, Debug)]
+pub struct ParserCache {
+    options: ParseOptions,
+    cache: Lru<Box<str>, Arc<Expressions>>,
+}
+
+impl ParserCache {
+    /// Cache up to `capacity` selectors parsed in the Kubernetes grammar,
+    /// evicting the least recently used ones
+    pub fn new(capacity: usize) -> Self {
+        Self::with_dialect(Dialect::Kubernetes, capacity)
+    }
+
+    /// Cache up to `capacity` selectors parsed in the given dialect
+    pub fn with_dialect(dialect: impl Into<Dialect>, capacity: usize) -> Self {
+        Self {
+            options: dialect.into().options(),
+            cache: Lru::new(capacity, true),
+        }
+    }
+
+    /// Parse the selector, reusing the cached requirements if it was parsed
+    /// before
+    pub fn parse(&mut self, selector: &str) -> Result<Arc<Expressions>> {
+        if let Some(expressions) = self.cache.get(selector) {
+            return Ok(expressions.clone());
+        }
+        let expressions = Arc::new(Expressions::parse_with_options(selector, self.options)?);
+        self.cache.insert(selector.into(), expressions.clone());
+        Ok(expressions)
+    }
+
+    /// Number of cached selectors
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Indicates whether no selectors are cached
+    pub fn is_empty(&self) -> bool {
+        self.cache.len() == 0
+    }
+
+    /// Maximum number of cached selectors
+    pub fn capacity(&self) -> usize {
+        self.cache.capacity()
+    }
+
+    /// Drop all cached selectors
+    pub fn clear(&mut self) {
+        self.cache.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{Dialect, Expressions, ParseError, ParserCache};
+
+    #[test]
+    fn memoizes_selectors() {
+        let mut cache = ParserCache::new(2);
+        let web = cache.parse("app=web").unwrap();
+        assert_eq!(*web, Expressions::try_from("app=web").unwrap());
+        assert!(Arc::ptr_eq(&web, &cache.parse("app=web").unwrap()));
+
+        cache.parse("app=api").unwrap();
+        cache.parse("app=web").unwrap();
+        cache.parse("app=db").unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&web, &cache.parse("app=web").unwrap()));
+
+        assert_eq!(
+            cache.parse("a='b'"),
+            Err(ParseError::InvalidValue("a='b'".into(), 0..5))
+        );
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.capacity(), 2);
+    }
+
+    #[test]
+    fn dialect() {
+        let mut cache = ParserCache::with_dialect(Dialect::Extended, 1);
+        assert_eq!(
+            *cache.parse("a='b c'").unwrap(),
+            Expressions::parse_with(Dialect::Extended, "a='b c'").unwrap()
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeSet;
+
+use crate::{Expression, Expressions, LabelSet, matching::compare};
+
+/// Outcome of matching against a partially known label set
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PartialMatch {
+    /// Selector matches regardless of the unknown labels
+    Match,
+
+    /// Selector does not match regardless of the unknown labels
+    NoMatch,
+
+    /// Outcome depends on the unknown labels
+    Unknown,
+}
+
+impl PartialMatch {
+    /// Three-valued conjunction of two outcomes
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (PartialMatch::NoMatch, _) | (_, PartialMatch::NoMatch) => PartialMatch::NoMatch,
+            (PartialMatch::Unknown, _) | (_, PartialMatch::Unknown) => PartialMatch::Unknown,
+            (PartialMatch::Match, PartialMatch::Match) => PartialMatch::Match,
+        }
+    }
+}
+
+impl From<bool> for PartialMatch {
+    fn from(matches: bool) -> Self {
+        if matches {
+            PartialMatch::Match
+        } else {
+            PartialMatch::NoMatch
+        }
+    }
+}
+
+/// What is known about a single label
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelState<'a> {
+    /// Label is set to the value
+    Value(&'a str),
+
+    /// Label is not set
+    Absent,
+
+    /// Label is set, but its value is unknown
+    UnknownValue,
+
+    /// Nothing is known about the label
+    Unknown,
+}
+
+/// A label set where some labels may be unknown.
+///
+/// Every [`LabelSet`] is a fully known partial label set.
+pub trait PartialLabelSet {
+    /// Get what is known about a label
+    fn state(&self, key: &str) -> LabelState<'_>;
+}
+
+impl<T: LabelSet + ?Sized> PartialLabelSet for T {
+    fn state(&self, key: &str) -> LabelState<'_> {
+        match self.get(key) {
+            Some(value) => LabelState::Value(value),
+            None => LabelState::Absent,
+        }
+    }
+}
+
+/// Known labels with an additional set of keys in unknown state.
+///
+/// Unknown keys take precedence over the known labels.
+#[derive(Clone, Debug, Default)]
+pub struct PartialLabels<L> {
+    /// Labels with known values
+    pub labels: L,
+
+    /// Keys that are set to an unknown value
+    pub unknown_values: BTreeSet<String>,
+
+    /// Keys about which nothing is known
+    pub unknown: BTreeSet<String>,
+}
+
+impl<L: LabelSet> PartialLabelSet for PartialLabels<L> {
+    fn state(&self, key: &str) -> LabelState<'_> {
+        if self.unknown.contains(key) {
+            LabelState::Unknown
+        } else if self.unknown_values.contains(key) {
+            LabelState::UnknownValue
+        } else {
+            self.labels.state(key)
+        }
+    }
+}
+
+impl Expression {
+    /// Evaluate the expression against labels, some of which may be unknown
+    pub fn matches_partial(&self, labels: &impl PartialLabelSet) -> PartialMatch {
+        let state = labels.state(self.key());
+        match (self, state) {
+            // Nothing can be in an empty set, or is always outside of it
+            (Expression::In(_, values), _) if values.is_empty() => PartialMatch::NoMatch,
+            (Expression::NotIn(_, values), _) if values.is_empty() => PartialMatch::Match,
+
+            (Expression::In(_, values), LabelState::Value(v)) => values.contains(v).into(),
+            (Expression::NotIn(_, values), LabelState::Value(v)) => (!values.contains(v)).into(),
+            (Expression::Equal(_, value), LabelState::Value(v)) => (value == v).into(),
+            (Expression::NotEqual(_, value), LabelState::Value(v)) => (value != v).into(),
+            (Expression::GreaterThan(_, bound), LabelState::Value(v)) => {
+                compare(Some(v), bound).is_some_and(|o| o.is_gt()).into()
+            }
+            (Expression::LessThan(_, bound), LabelState::Value(v)) => {
+                compare(Some(v), bound).is_some_and(|o| o.is_lt()).into()
+            }
+            #[cfg(feature = "regex")]
+            (Expression::Matches(_, pattern), LabelState::Value(v)) => pattern.is_match(v).into(),
+            #[cfg(feature = "regex")]
+            (Expression::NotMatches(_, pattern), LabelState::Value(v)) => {
+                (!pattern.is_match(v)).into()
+            }
+            #[cfg(feature = "extended")]
+            (Expression::Glob(_, pattern), LabelState::Value(v)) => pattern.is_match(v).into(),
+            #[cfg(feature = "extended")]
+            (Expression::Glob(..), LabelState::Absent) => PartialMatch::NoMatch,
+            #[cfg(feature = "regex")]
+            (Expression::Matches(..), LabelState::Absent) => PartialMatch::NoMatch,
+            #[cfg(feature = "regex")]
+            (Expression::NotMatches(..), LabelState::Absent) => PartialMatch::Match,
+
+            (
+                Expression::In(..)
+                | Expression::Equal(..)
+                | Expression::GreaterThan(..)
+                | Expression::LessThan(..),
+                LabelState::Absent,
+            ) => PartialMatch::NoMatch,
+            (Expression::NotIn(..) | Expression::NotEqual(..), LabelState::Absent) => {
+                PartialMatch::Match
+            }
+
+            (Expression::Exists(_), LabelState::Value(_) | LabelState::UnknownValue) => {
+                PartialMatch::Match
+            }
+            (Expression::Exists(_), LabelState::Absent) => PartialMatch::NoMatch,
+            (Expression::DoesNotExist(_), LabelState::Value(_) | LabelState::UnknownValue) => {
+                PartialMatch::NoMatch
+            }
+            (Expression::DoesNotExist(_), LabelState::Absent) => PartialMatch::Match,
+
+            (_, LabelState::UnknownValue | LabelState::Unknown) => PartialMatch::Unknown,
+        }
+    }
+}
+
+impl Expressions {
+    /// Evaluate all expressions against labels, some of which may be unknown.
+    ///
+    /// A single non-matching expression is decisive even if others are unknown.
+    pub fn matches_partial(&self, labels: &impl PartialLabelSet) -> PartialMatch {
+        self.iter()
+            .map(|expr| expr.matches_partial(labels))
+            .fold(PartialMatch::Match, PartialMatch::and)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{Expressions, PartialLabels, PartialMatch};
+
+    fn partial(
+        known: &[(&'static str, &'static str)],
+        unknown_values: &[&str],
+        unknown: &[&str],
+    ) -> PartialLabels<BTreeMap<&'static str, &'static str>> {
+        PartialLabels {
+            labels: known.iter().copied().collect(),
+            unknown_values: unknown_values.iter().map(|k| k.to_string()).collect(),
+            unknown: unknown.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn partial_matches() {
+        for (selector, labels, expected) in [
+            (
+                "app=web",
+                partial(&[("app", "web")], &[], &[]),
+                PartialMatch::Match,
+            ),
+            ("app=web", partial(&[], &[], &[]), PartialMatch::NoMatch),
+            (
+                "app=web",
+                partial(&[], &["app"], &[]),
+                PartialMatch::Unknown,
+            ),
+            ("app", partial(&[], &["app"], &[]), PartialMatch::Match),
+            ("app", partial(&[], &[], &["app"]), PartialMatch::Unknown),
+            ("!app", partial(&[], &["app"], &[]), PartialMatch::NoMatch),
+            ("!app", partial(&[], &[], &["app"]), PartialMatch::Unknown),
+            (
+                "env notin (dev)",
+                partial(&[], &[], &["env"]),
+                PartialMatch::Unknown,
+            ),
+            (
+                "env notin (dev)",
+                partial(&[("env", "prod")], &[], &[]),
+                PartialMatch::Match,
+            ),
+            (
+                "env notin (dev)",
+                partial(&[("env", "prod")], &[], &["env"]),
+                PartialMatch::Unknown,
+            ),
+            (
+                "app=web,env=prod",
+                partial(&[("app", "api")], &[], &["env"]),
+                PartialMatch::NoMatch,
+            ),
+            (
+                "app=web,env=prod",
+                partial(&[("app", "web")], &[], &["env"]),
+                PartialMatch::Unknown,
+            ),
+            ("", partial(&[], &[], &["env"]), PartialMatch::Match),
+        ] {
+            let selector = Expressions::try_from(selector).unwrap();
+            assert_eq!(selector.matches_partial(&labels), expected, "{selector}");
+        }
+    }
+
+    #[test]
+    fn fully_known_labels() {
+        let selector = Expressions::try_from("app=web,!canary").unwrap();
+        let labels = BTreeMap::from([("app", "web")]);
+        assert_eq!(selector.matches_partial(&labels), PartialMatch::Match);
+    }
+}
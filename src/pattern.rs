@@ -0,0 +1,106 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A regular expression which has to match label values as a whole.
+///
+/// Patterns compare and serialize by their source.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    source: String,
+    regex: Regex,
+}
+
+impl Pattern {
+    /// Compile the pattern, anchored to both ends of the value
+    pub fn new(source: impl Into<String>) -> Result<Self, regex::Error> {
+        let source = source.into();
+        let regex = Regex::new(&format!("^(?:{source})$"))?;
+        Ok(Pattern { source, regex })
+    }
+
+    /// Check whether the value matches the pattern
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+
+    /// Source the pattern was compiled from
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for Pattern {}
+
+impl Hash for Pattern {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl TryFrom<&str> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(source: &str) -> Result<Self, regex::Error> {
+        Pattern::new(source)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Pattern::new(source).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Pattern {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Pattern".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "Regular expression matching whole values",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pattern;
+
+    #[test]
+    fn anchored() {
+        let pattern = Pattern::new("web-.*").unwrap();
+        assert!(pattern.is_match("web-1"));
+        assert!(!pattern.is_match("a-web-1"));
+        assert_eq!(pattern.as_str(), "web-.*");
+        assert!(Pattern::new("a|b").unwrap().is_match("b"));
+        assert!(Pattern::new("(").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    Dialect, Expression, Expressions, Limit, ParseError, ParseOptions, Result, check_length,
+};
+
+/// Label holding the metric name in Prometheus
+const METRIC_NAME: &str = "__name__";
+
+impl Expressions {
+    /// Parse Prometheus label matchers, such as `up{job="api", env!="dev"}`
+    pub fn parse_prometheus(selector: &str) -> Result<Self> {
+        Self::parse_prometheus_with(Dialect::Kubernetes, selector)
+    }
+
+    /// Parse Prometheus label matchers in the given dialect.
+    ///
+    /// The regular expression operators `=~` and `!~` are only accepted by
+    /// dialects allowing them. Prometheus does not distinguish empty labels
+    /// from missing ones, so `l=""` becomes `!l` and `l!=""` becomes `l`.
+    /// The metric name counts towards the requirement limit.
+    pub fn parse_prometheus_with(dialect: impl Into<Dialect>, selector: &str) -> Result<Self> {
+        let options = dialect.into().options();
+        check_length(selector, &options)?;
+        let mut parser = Parser {
+            input: selector,
+            pos: 0,
+            options,
+        };
+        parser.selector()
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    options: ParseOptions,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_space(&mut self) {
+        self.pos = self.input.len() - self.rest().trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    /// Metric or label name, where only metric names may contain colons
+    fn name(&mut self, colons: bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                !(c.is_ascii_alphabetic()
+                    || c == '_'
+                    || colons && c == ':'
+                    || i > 0 && c.is_ascii_digit())
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn error(&self, start: usize) -> ParseError {
+        let end = self.input[self.pos..]
+            .chars()
+            .next()
+            .map_or(self.pos, |c| self.pos + c.len_utf8());
+        ParseError::StringParse(self.input[start..end].to_owned(), start..end)
+    }
+
+    fn selector(&mut self) -> Result<Expressions> {
+        let mut expressions = Expressions::default();
+        self.skip_space();
+        let metric = self.name(true);
+        if !metric.is_empty() {
+            expressions.push(Expression::equal(METRIC_NAME, metric));
+        }
+
+        self.skip_space();
+        if self.eat("{") {
+            loop {
+                self.skip_space();
+                if self.eat("}") {
+                    break;
+                }
+                let start = self.pos;
+                let matcher = self.matcher()?;
+                if let Some(max) = self.options.max_requirements
+                    && expressions.len() >= max
+                {
+                    return Err(ParseError::LimitExceeded(
+                        self.input[start..self.pos].to_owned(),
+                        start..self.pos,
+                        Limit::Requirements(max),
+                    ));
+                }
+                expressions.push(matcher);
+                self.skip_space();
+                if !self.eat(",") && !self.rest().starts_with('}') {
+                    return Err(self.error(self.pos));
+                }
+            }
+        } else if metric.is_empty() {
+            return Err(self.error(self.pos));
+        }
+
+        self.skip_space();
+        match self.rest().is_empty() {
+            true => Ok(expressions),
+            false => Err(self.error(self.pos)),
+        }
+    }
+
+    fn matcher(&mut self) -> Result<Expression> {
+        let start = self.pos;
+        let name = self.name(false).to_owned();
+        if name.is_empty() {
+            return Err(self.error(start));
+        }
+
+        self.skip_space();
+        let operator = ["=~", "!~", "!=", "="]
+            .into_iter()
+            .find(|op| self.eat(op))
+            .ok_or_else(|| self.error(start))?;
+        self.skip_space();
+        let value = self.string().ok_or_else(|| self.error(start))?;
+
+        Ok(match (operator, value.is_empty()) {
+            ("=", true) => Expression::does_not_exist(name),
+            ("=", false) => Expression::equal(name, value),
+            ("!=", true) => Expression::exists(name),
+            ("!=", false) => Expression::not_equal(name, value),
+            #[cfg(feature = "regex")]
+            (_, _) if self.options.regex_operators => {
+                let pattern = crate::Pattern::new(value).map_err(|_| self.error(start))?;
+                match operator {
+                    "=~" => Expression::matching(name, pattern),
+                    _ => Expression::not_matching(name, pattern),
+                }
+            }
+            _ => {
+                return Err(ParseError::StringParse(
+                    self.input[start..self.pos].to_owned(),
+                    start..self.pos,
+                ));
+            }
+        })
+    }
+
+    /// Quoted string literal, with escapes unless quoted with backticks
+    fn string(&mut self) -> Option<String> {
+        let quote = self
+            .rest()
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+        self.pos += 1;
+
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                c if c == quote => {
+                    self.pos += i + 1;
+                    return Some(value);
+                }
+                '\\' if quote != '`' => value.push(match chars.next()?.1 {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    c => c,
+                }),
+                c => value.push(c),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, Expressions, Limit, ParseError, ParseOptions};
+
+    #[test]
+    fn matchers() {
+        assert_eq!(
+            Expressions::parse_prometheus(r#"up{job="api", env != 'dev', path=`a\b`, x="\"y\"",}"#),
+            Ok(Expression::equal("__name__", "up")
+                .and(Expression::equal("job", "api"))
+                .and(Expression::not_equal("env", "dev"))
+                .and(Expression::equal("path", r"a\b"))
+                .and(Expression::equal("x", r#""y""#)))
+        );
+        assert_eq!(
+            Expressions::parse_prometheus(r#"{team="", tier!=""}"#),
+            Ok(Expression::does_not_exist("team").and(Expression::exists("tier")))
+        );
+        assert_eq!(
+            Expressions::parse_prometheus("{}"),
+            Ok(Expressions::default())
+        );
+        assert_eq!(
+            Expressions::parse_prometheus("up"),
+            Ok(Expression::equal("__name__", "up").into())
+        );
+        assert_eq!(
+            Expressions::parse_prometheus(r#"job:http_requests:rate5m{env="prod"}"#),
+            Ok(Expression::equal("__name__", "job:http_requests:rate5m")
+                .and(Expression::equal("env", "prod")))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str, span| Err(ParseError::StringParse(text.into(), span));
+        assert_eq!(Expressions::parse_prometheus(""), error("", 0..0));
+        assert_eq!(Expressions::parse_prometheus(r#"{a="b""#), error("", 6..6));
+        assert_eq!(
+            Expressions::parse_prometheus(r#"{a=b}"#),
+            error("a=b", 1..4)
+        );
+        assert_eq!(
+            Expressions::parse_prometheus(r#"{a="b" c="d"}"#),
+            error("c", 7..8)
+        );
+        assert_eq!(
+            Expressions::parse_prometheus(r#"{a:b="c"}"#),
+            error("a:", 1..3)
+        );
+        assert_eq!(
+            Expressions::parse_prometheus(r#"{a=~"b.*"}"#),
+            error(r#"a=~"b.*""#, 1..9)
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let options = ParseOptions {
+            max_length: Some(20),
+            max_requirements: Some(2),
+            ..ParseOptions::strict()
+        };
+        assert_eq!(
+            Expressions::parse_prometheus_with(options, r#"up{a="b", c="d"}"#),
+            Err(ParseError::LimitExceeded(
+                r#"c="d""#.into(),
+                10..15,
+                Limit::Requirements(2)
+            ))
+        );
+        assert_eq!(
+            Expressions::parse_prometheus_with(options, r#"{a="b", c="d", e="f"}"#),
+            Err(ParseError::LimitExceeded(
+                String::new(),
+                20..21,
+                Limit::Length(20)
+            ))
+        );
+        assert!(Expressions::parse_prometheus_with(options, r#"{a="b", c="d"}"#).is_ok());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matchers() {
+        use crate::{Dialect, Pattern};
+
+        assert_eq!(
+            Expressions::parse_prometheus_with(
+                Dialect::Extended,
+                r#"{instance=~"10\\..*", job!~"dev|test"}"#
+            ),
+            Ok(
+                Expression::matching("instance", Pattern::new(r"10\..*").unwrap()).and(
+                    Expression::not_matching("job", Pattern::new("dev|test").unwrap())
+                )
+            )
+        );
+        assert!(Expressions::parse_prometheus_with(Dialect::Extended, r#"{a=~"("}"#).is_err());
+    }
+}
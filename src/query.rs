@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use crate::{Expressions, ParseError, Result};
+
+/// Query parameters of list endpoints accepting a `labelSelector`, for use
+/// with HTTP frameworks which deserialize decoded query strings
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectorQuery {
+    /// Requirements of the selector, matching everything if unset
+    #[serde(default, with = "crate::serde_string")]
+    pub label_selector: Expressions,
+}
+
+impl Expressions {
+    /// Parse a percent-encoded `labelSelector` query parameter value.
+    ///
+    /// `+` is decoded as a space, as in form encoding. Spans of parse errors
+    /// refer to the decoded selector.
+    pub fn from_query_param(value: &str) -> Result<Self> {
+        Self::try_from(decode(value)?.as_str())
+    }
+
+    /// Percent-encode the selector for use as a query parameter value
+    pub fn to_query_param(&self) -> String {
+        encode(&self.to_string())
+    }
+}
+
+fn decode(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let byte = value
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| {
+                        let end = i + value[i..]
+                            .chars()
+                            .take(3)
+                            .map(char::len_utf8)
+                            .sum::<usize>();
+                        ParseError::StringParse(value[i..end].to_owned(), i..end)
+                    })?;
+                decoded.push(byte);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded)
+        .map_err(|_| ParseError::StringParse(value.to_owned(), 0..value.len()))
+}
+
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => write!(encoded, "%{byte:02X}").unwrap(),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expressions, ParseError};
+
+    #[test]
+    fn round_trip() {
+        let expressions = Expressions::try_from("app=web,tier!=db,env in (a,b),!canary").unwrap();
+        let encoded = expressions.to_query_param();
+        assert_eq!(
+            encoded,
+            "app%3Dweb%2Ctier%21%3Ddb%2Cenv%20in%20%28a%2Cb%29%2C%21canary"
+        );
+        assert_eq!(Expressions::from_query_param(&encoded), Ok(expressions));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn selector_query() {
+        use crate::SelectorQuery;
+
+        let query: SelectorQuery =
+            serde_json::from_str(r#"{"labelSelector": "app=web,!canary"}"#).unwrap();
+        assert_eq!(
+            query.label_selector,
+            Expressions::try_from("app=web,!canary").unwrap()
+        );
+        let query: SelectorQuery = serde_json::from_str("{}").unwrap();
+        assert!(query.label_selector.is_empty());
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            Expressions::from_query_param("env+in+(a,b),tier%21%3ddb"),
+            Ok(Expressions::try_from("env in (a,b),tier!=db").unwrap())
+        );
+        assert_eq!(
+            Expressions::from_query_param("a%2"),
+            Err(ParseError::StringParse("%2".into(), 1..3))
+        );
+        assert_eq!(
+            Expressions::from_query_param("a%zz"),
+            Err(ParseError::StringParse("%zz".into(), 1..4))
+        );
+        assert_eq!(
+            Expressions::from_query_param("a%+1"),
+            Err(ParseError::StringParse("%+1".into(), 1..4))
+        );
+        assert_eq!(
+            Expressions::from_query_param("%é"),
+            Err(ParseError::StringParse("%é".into(), 0..3))
+        );
+        assert_eq!(
+            Expressions::from_query_param("a%FF"),
+            Err(ParseError::StringParse("a%FF".into(), 0..4))
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use logos::Span;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Expected, ParseError};
+
+/// Structured description of a parse error, for returning from APIs
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ErrorReport {
+    /// Stable identifier of the kind of error, see [`ParseError::code`]
+    pub code: String,
+
+    /// Description of the error
+    pub message: String,
+
+    /// Position of the offending text in the source
+    pub span: Span,
+
+    /// Line of the source holding the offending text
+    pub snippet: String,
+
+    /// Tokens which would have been accepted at the error
+    pub expected: Vec<Expected>,
+}
+
+impl ErrorReport {
+    /// Describe an error which occurred while parsing the source
+    pub fn new(error: &ParseError, source: &str) -> Self {
+        let span = error.span();
+        let mut start = span.start.min(source.len());
+        while !source.is_char_boundary(start) {
+            start -= 1;
+        }
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        ErrorReport {
+            code: error.code().into(),
+            message: error.to_string(),
+            span,
+            snippet: source[line_start..line_end].trim_end_matches('\r').into(),
+            expected: error.expected(source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorReport, Expressions};
+
+    #[test]
+    fn report() {
+        let document = "app=web\ntier in db\n";
+        let error = Expressions::parse_document(document).unwrap_err();
+        let report = ErrorReport::new(&error, document);
+        assert_eq!(report.code, "missing_values_list");
+        assert_eq!(report.span, 13..15);
+        assert_eq!(report.snippet, "tier in db");
+        assert_eq!(report.message, error.to_string());
+
+        let report = ErrorReport::new(&error, "selector=abcä\nb");
+        assert_eq!(report.snippet, "selector=abcä");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let error = Expressions::try_from("=a").unwrap_err();
+        let report = serde_json::to_value(ErrorReport::new(&error, "=a")).unwrap();
+        assert_eq!(
+            report,
+            serde_json::json!({
+                "code": "empty_key",
+                "message": error.to_string(),
+                "span": {"start": 0, "end": 1},
+                "snippet": "=a",
+                "expected": ["key"],
+            })
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::Expression;
+#[cfg(feature = "extended")]
+use crate::GlobPattern;
+#[cfg(feature = "regex")]
+use crate::Pattern;
+
+/// Exclusive integer range of label values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Bounds {
+    /// Integer the value must be greater than
+    pub above: Option<i64>,
+
+    /// Integer the value must be less than
+    pub below: Option<i64>,
+}
+
+impl Bounds {
+    /// Check whether the value is an integer within the bounds, if there are any
+    pub fn contains(&self, value: &str) -> bool {
+        if *self == Bounds::default() {
+            return true;
+        }
+        value.parse::<i64>().is_ok_and(|value| {
+            self.above.is_none_or(|above| value > above)
+                && self.below.is_none_or(|below| value < below)
+        })
+    }
+
+    /// Check whether no integer is within the bounds
+    pub fn is_empty(&self) -> bool {
+        match (self.above, self.below) {
+            (Some(above), Some(below)) => i128::from(below) - i128::from(above) < 2,
+            (Some(above), None) => above == i64::MAX,
+            (None, Some(below)) => below == i64::MIN,
+            (None, None) => false,
+        }
+    }
+
+    /// Smallest and largest integer within the bounds
+    fn range(&self) -> (i128, i128) {
+        (
+            self.above
+                .map_or(i64::MIN.into(), |above| i128::from(above) + 1),
+            self.below
+                .map_or(i64::MAX.into(), |below| i128::from(below) - 1),
+        )
+    }
+}
+
+/// All requirements a selector places on a single key, folded together
+#[derive(Clone, Debug, Default)]
+pub(crate) struct KeyRequirements<'a> {
+    /// Key must be set
+    pub exists: bool,
+
+    /// Key must not be set
+    pub absent: bool,
+
+    /// Values the key may take, if restricted
+    pub allowed: Option<BTreeSet<&'a str>>,
+
+    /// Values the key must not take
+    pub forbidden: BTreeSet<&'a str>,
+
+    /// Integer range the value must fall into
+    pub bounds: Bounds,
+
+    /// Patterns the value must match
+    #[cfg(feature = "regex")]
+    pub patterns: Vec<&'a Pattern>,
+
+    /// Patterns the value must not match
+    #[cfg(feature = "regex")]
+    pub forbidden_patterns: Vec<&'a Pattern>,
+
+    /// Wildcard patterns the value must match
+    #[cfg(feature = "extended")]
+    pub globs: Vec<&'a GlobPattern>,
+
+    /// Some comparison has a bound which is not an integer, and never matches
+    pub invalid_bound: bool,
+}
+
+impl<'a> KeyRequirements<'a> {
+    fn allow(&mut self, values: impl IntoIterator<Item = &'a str>) {
+        let values: BTreeSet<_> = values.into_iter().collect();
+        self.allowed = Some(match self.allowed.take() {
+            Some(allowed) => allowed.intersection(&values).copied().collect(),
+            None => values,
+        });
+    }
+
+    fn add(&mut self, requirement: &'a Expression) {
+        match requirement {
+            Expression::In(_, values) => {
+                self.exists = true;
+                self.allow(values.iter().map(String::as_str));
+            }
+            Expression::Equal(_, value) => {
+                self.exists = true;
+                self.allow([value.as_str()]);
+            }
+            Expression::NotIn(_, values) => {
+                self.forbidden.extend(values.iter().map(String::as_str))
+            }
+            Expression::NotEqual(_, value) => {
+                self.forbidden.insert(value);
+            }
+            Expression::Exists(_) => self.exists = true,
+            Expression::DoesNotExist(_) => self.absent = true,
+            Expression::GreaterThan(_, bound) => {
+                self.exists = true;
+                match bound.parse() {
+                    Ok(bound) => self.bounds.above = self.bounds.above.max(Some(bound)),
+                    Err(_) => self.invalid_bound = true,
+                }
+            }
+            Expression::LessThan(_, bound) => {
+                self.exists = true;
+                match bound.parse() {
+                    Ok(bound) => {
+                        self.bounds.below = Some(self.bounds.below.map_or(bound, |b| b.min(bound)))
+                    }
+                    Err(_) => self.invalid_bound = true,
+                }
+            }
+            #[cfg(feature = "regex")]
+            Expression::Matches(_, pattern) => {
+                self.exists = true;
+                self.patterns.push(pattern);
+            }
+            #[cfg(feature = "regex")]
+            Expression::NotMatches(_, pattern) => self.forbidden_patterns.push(pattern),
+            #[cfg(feature = "extended")]
+            Expression::Glob(_, pattern) => {
+                self.exists = true;
+                self.globs.push(pattern);
+            }
+        }
+    }
+
+    /// Check whether the value satisfies requirements other than value sets
+    fn admits(&self, value: &str) -> bool {
+        #[cfg(feature = "regex")]
+        if !self.patterns.iter().all(|p| p.is_match(value))
+            || self.forbidden_patterns.iter().any(|p| p.is_match(value))
+        {
+            return false;
+        }
+        #[cfg(feature = "extended")]
+        if !self.globs.iter().all(|p| p.is_match(value)) {
+            return false;
+        }
+        self.bounds.contains(value)
+    }
+
+    /// Allowed values which are not forbidden at the same time
+    pub fn permitted(&self) -> Option<BTreeSet<&'a str>> {
+        self.allowed.as_ref().map(|allowed| {
+            allowed
+                .difference(&self.forbidden)
+                .copied()
+                .filter(|value| self.admits(value))
+                .collect()
+        })
+    }
+
+    /// Check whether some state of the key satisfies all requirements.
+    ///
+    /// Forbidden values never exhaust a range of integers, as each of them
+    /// can be spelled with leading zeros.
+    pub fn satisfiable(&self) -> bool {
+        !(self.exists && self.absent
+            || self.invalid_bound
+            || self.bounds.is_empty()
+            || self.permitted().is_some_and(|p| p.is_empty()))
+    }
+
+    /// Check whether the key may be set to the value
+    fn admits_value(&self, value: &str) -> bool {
+        !self.absent
+            && self.allowed.as_ref().is_none_or(|a| a.contains(value))
+            && !self.forbidden.contains(value)
+            && self.admits(value)
+    }
+
+    /// Check whether every state of the key satisfying the other requirements
+    /// also satisfies these, assuming both are satisfiable.
+    ///
+    /// Patterns are compared by their source, so this may answer `false` for
+    /// differently written patterns which accept the same values.
+    pub fn includes(&self, other: &KeyRequirements) -> bool {
+        if self.exists && !other.exists {
+            return false;
+        }
+        if other.absent {
+            return true;
+        }
+        if let Some(permitted) = other.permitted() {
+            return permitted.iter().all(|value| self.admits_value(value));
+        }
+
+        // The other requirements admit infinitely many values, as even
+        // bounded integers can be spelled with any number of leading zeros
+        if self.absent || self.allowed.is_some() {
+            return false;
+        }
+        if self.bounds != Bounds::default() {
+            let ((low, high), (other_low, other_high)) =
+                (self.bounds.range(), other.bounds.range());
+            if other.bounds == Bounds::default() || other_low < low || other_high > high {
+                return false;
+            }
+        }
+        #[cfg(feature = "regex")]
+        if !sources(&self.patterns).is_subset(&sources(&other.patterns))
+            || !sources(&self.forbidden_patterns).is_subset(&sources(&other.forbidden_patterns))
+        {
+            return false;
+        }
+        #[cfg(feature = "extended")]
+        if !sources(&self.globs).is_subset(&sources(&other.globs)) {
+            return false;
+        }
+        (self.forbidden.iter()).all(|value| other.forbidden.contains(value) || !other.admits(value))
+    }
+
+    /// A value satisfying the requirements, for keys which must be set.
+    ///
+    /// Values for patterns are guessed from their sources, so this may find
+    /// none for satisfiable requirements.
+    pub fn example(&self) -> Option<String> {
+        if let Some(permitted) = self.permitted() {
+            return permitted.first().map(|value| value.to_string());
+        }
+        // One more candidate than values which could be forbidden
+        let count = self.forbidden.len() as i128 + 1;
+        let mut candidates: Vec<String> = vec![];
+        if self.bounds != Bounds::default() {
+            let (low, high) = self.bounds.range();
+            candidates.extend((low..=high.min(low + count)).map(|value| value.to_string()));
+            let (sign, digits) = if low < 0 { ("-", -low) } else { ("", low) };
+            candidates.extend(
+                (1..=count).map(|zeros| format!("{sign}{}{digits}", "0".repeat(zeros as usize))),
+            );
+        } else {
+            #[cfg(feature = "regex")]
+            candidates.extend(self.patterns.iter().map(|p| literal(p.as_str())));
+            #[cfg(feature = "extended")]
+            candidates.extend(self.globs.iter().map(|p| literal(p.as_str())));
+            candidates.extend((0..count).map(|i| match i {
+                0 => "value".to_string(),
+                i => format!("value-{i}"),
+            }));
+        }
+        candidates
+            .into_iter()
+            .find(|value| !self.forbidden.contains(value.as_str()) && self.admits(value))
+    }
+
+    /// Fewest requirements on the key equivalent to these, assuming they are
+    /// satisfiable
+    pub fn minimize(&self, key: &str) -> Vec<Expression> {
+        if self.absent {
+            return vec![Expression::does_not_exist(key)];
+        }
+        if let Some(permitted) = self.permitted() {
+            return vec![match <[_; 1]>::try_from(Vec::from_iter(permitted)) {
+                Ok([value]) => Expression::equal(key, value),
+                Err(values) => Expression::r#in(key, values),
+            }];
+        }
+
+        let mut expressions = vec![];
+        if let Some(above) = self.bounds.above {
+            expressions.push(Expression::greater_than(key, above));
+        }
+        if let Some(below) = self.bounds.below {
+            expressions.push(Expression::less_than(key, below));
+        }
+        #[cfg(feature = "regex")]
+        for pattern in distinct(&self.patterns) {
+            expressions.push(Expression::matching(key, pattern.clone()));
+        }
+        #[cfg(feature = "extended")]
+        for pattern in distinct(&self.globs) {
+            expressions.push(Expression::glob(key, pattern.clone()));
+        }
+        // Every requirement so far implies the key is set
+        if self.exists && expressions.is_empty() {
+            expressions.push(Expression::exists(key));
+        }
+        #[cfg(feature = "regex")]
+        for pattern in distinct(&self.forbidden_patterns) {
+            expressions.push(Expression::not_matching(key, pattern.clone()));
+        }
+
+        let forbidden: Vec<_> = (self.forbidden.iter())
+            .copied()
+            .filter(|value| self.admits(value))
+            .collect();
+        match <[_; 1]>::try_from(forbidden) {
+            Ok([value]) => expressions.push(Expression::not_equal(key, value)),
+            Err(values) if values.is_empty() => {}
+            Err(values) => expressions.push(Expression::not_in(key, values)),
+        }
+        expressions
+    }
+}
+
+#[cfg(any(feature = "regex", feature = "extended"))]
+trait Source {
+    fn source(&self) -> &str;
+}
+
+#[cfg(feature = "regex")]
+impl Source for Pattern {
+    fn source(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "extended")]
+impl Source for GlobPattern {
+    fn source(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Sources of the patterns, for comparing them
+#[cfg(any(feature = "regex", feature = "extended"))]
+fn sources<'a>(patterns: &[&'a impl Source]) -> BTreeSet<&'a str> {
+    patterns.iter().map(|pattern| pattern.source()).collect()
+}
+
+/// Characters of a pattern which are valid in label values, as a guess of a
+/// value matching it
+#[cfg(any(feature = "regex", feature = "extended"))]
+fn literal(source: &str) -> String {
+    (source.chars())
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect()
+}
+
+/// Patterns with distinct sources, in order
+#[cfg(any(feature = "regex", feature = "extended"))]
+fn distinct<'a, P: Source>(patterns: &[&'a P]) -> impl Iterator<Item = &'a P> {
+    let mut seen = BTreeSet::new();
+    (patterns.iter().copied()).filter(move |pattern| seen.insert(pattern.source()))
+}
+
+/// Fold requirements by the key they apply to
+pub(crate) fn by_key<'a>(
+    requirements: impl IntoIterator<Item = &'a Expression>,
+) -> BTreeMap<&'a str, KeyRequirements<'a>> {
+    let mut keys: BTreeMap<&str, KeyRequirements> = BTreeMap::new();
+    for requirement in requirements {
+        keys.entry(requirement.key()).or_default().add(requirement);
+    }
+    keys
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Dialect, Expressions, LabelSet, ParseError, ParsedExpression, Result};
+
+/// A filter on resource identity and labels, such as
+/// `kind=Pod, namespace in (a,b), app=web`.
+///
+/// Requirements on the unprefixed `kind`, `namespace` and `name` keys apply to
+/// the resource itself, every other requirement applies to its labels.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResourceFilter {
+    /// Requirements on the resource kind
+    pub kind: Expressions,
+
+    /// Requirements on the namespace, which is unset for cluster scoped resources
+    pub namespace: Expressions,
+
+    /// Requirements on the resource name
+    pub name: Expressions,
+
+    /// Requirements on the resource labels
+    pub labels: Expressions,
+}
+
+impl ResourceFilter {
+    /// Parse a filter in the given dialect
+    pub fn parse_with(dialect: impl Into<Dialect>, filter: &str) -> Result<Self> {
+        Ok(Expressions::parse_with(dialect, filter)?.into())
+    }
+
+    /// Check whether a resource passes the filter
+    pub fn matches(
+        &self,
+        kind: &str,
+        namespace: Option<&str>,
+        name: &str,
+        labels: &impl LabelSet,
+    ) -> bool {
+        self.kind.matches(&attribute("kind", Some(kind)))
+            && self.namespace.matches(&attribute("namespace", namespace))
+            && self.name.matches(&attribute("name", Some(name)))
+            && self.labels.matches(labels)
+    }
+}
+
+/// A label set holding a single resource attribute, if it is set
+fn attribute<'a>(key: &'a str, value: Option<&'a str>) -> BTreeMap<&'a str, &'a str> {
+    value.map(|value| (key, value)).into_iter().collect()
+}
+
+impl From<Expressions> for ResourceFilter {
+    fn from(expressions: Expressions) -> Self {
+        let mut filter = ResourceFilter::default();
+        for ParsedExpression::Expression(expression) in expressions {
+            let part = match expression.key() {
+                "kind" => &mut filter.kind,
+                "namespace" => &mut filter.namespace,
+                "name" => &mut filter.name,
+                _ => &mut filter.labels,
+            };
+            part.push(expression);
+        }
+        filter
+    }
+}
+
+impl From<ResourceFilter> for Expressions {
+    fn from(filter: ResourceFilter) -> Self {
+        filter
+            .kind
+            .and(filter.namespace)
+            .and(filter.name)
+            .and(filter.labels)
+    }
+}
+
+impl fmt::Display for ResourceFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = [&self.kind, &self.namespace, &self.name, &self.labels];
+        let mut parts = parts.into_iter().filter(|part| !part.is_empty());
+        if let Some(first) = parts.next() {
+            write!(f, "{first}")?;
+            for part in parts {
+                write!(f, ",{part}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for ResourceFilter {
+    type Error = ParseError;
+
+    fn try_from(filter: &str) -> Result<Self> {
+        Ok(Expressions::try_from(filter)?.into())
+    }
+}
+
+impl FromStr for ResourceFilter {
+    type Err = ParseError;
+
+    fn from_str(filter: &str) -> Result<Self> {
+        Self::try_from(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{Expressions, ResourceFilter};
+
+    #[test]
+    fn split() {
+        let filter: ResourceFilter = "kind=Pod, namespace in (a,b), app=web, name!=db, !canary"
+            .parse()
+            .unwrap();
+        assert_eq!(filter.kind, Expressions::try_from("kind=Pod").unwrap());
+        assert_eq!(
+            filter.namespace,
+            Expressions::try_from("namespace in (a,b)").unwrap()
+        );
+        assert_eq!(filter.name, Expressions::try_from("name!=db").unwrap());
+        assert_eq!(
+            filter.labels,
+            Expressions::try_from("app=web,!canary").unwrap()
+        );
+        assert_eq!(
+            filter.to_string(),
+            "kind=Pod,namespace in (a,b),name!=db,app=web,!canary"
+        );
+    }
+
+    #[test]
+    fn matches() {
+        let filter: ResourceFilter = "kind in (Pod,Job), !namespace, app=web".parse().unwrap();
+        let web = BTreeMap::from([("app", "web")]);
+        assert!(filter.matches("Pod", None, "web-1", &web));
+        assert!(!filter.matches("Pod", Some("default"), "web-1", &web));
+        assert!(!filter.matches("Node", None, "web-1", &web));
+        assert!(!filter.matches("Pod", None, "web-1", &BTreeMap::<&str, &str>::new()));
+        assert!(ResourceFilter::default().matches("Pod", Some("a"), "b", &web));
+    }
+}
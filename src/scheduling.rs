@@ -0,0 +1,119 @@
+use k8s_openapi::api::core::v1::{NodeSelectorRequirement, NodeSelectorTerm, PodAffinityTerm};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
+
+use crate::{ConversionError, Expression, Expressions};
+
+impl Expressions {
+    /// Node selector term requiring node labels to match, for node affinity.
+    ///
+    /// Unlike label selectors, node selectors support the integer comparisons.
+    pub fn to_node_selector_term(&self) -> Result<NodeSelectorTerm, ConversionError> {
+        let requirements = self
+            .iter()
+            .map(node_selector_requirement)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(NodeSelectorTerm {
+            match_expressions: (!requirements.is_empty()).then_some(requirements),
+            match_fields: None,
+        })
+    }
+
+    /// Pod affinity term selecting pods by these requirements within the
+    /// topology domain identified by the key, such as `kubernetes.io/hostname`
+    pub fn to_pod_affinity_term(
+        &self,
+        topology_key: impl Into<String>,
+    ) -> Result<PodAffinityTerm, ConversionError> {
+        Ok(PodAffinityTerm {
+            label_selector: Some(LabelSelector::try_from(self.clone())?),
+            topology_key: topology_key.into(),
+            ..Default::default()
+        })
+    }
+}
+
+fn node_selector_requirement(
+    expression: &Expression,
+) -> Result<NodeSelectorRequirement, ConversionError> {
+    let requirement = |operator: &str, values: Vec<&String>| NodeSelectorRequirement {
+        key: expression.key().to_owned(),
+        operator: operator.to_owned(),
+        values: (!values.is_empty()).then(|| values.into_iter().cloned().collect()),
+    };
+    Ok(match expression {
+        Expression::In(_, values) => requirement("In", values.iter().collect()),
+        Expression::NotIn(_, values) => requirement("NotIn", values.iter().collect()),
+        Expression::Equal(_, value) => requirement("In", vec![value]),
+        Expression::NotEqual(_, value) => requirement("NotIn", vec![value]),
+        Expression::Exists(_) => requirement("Exists", vec![]),
+        Expression::DoesNotExist(_) => requirement("DoesNotExist", vec![]),
+        Expression::GreaterThan(_, value) => requirement("Gt", vec![value]),
+        Expression::LessThan(_, value) => requirement("Lt", vec![value]),
+        #[cfg(any(feature = "regex", feature = "extended"))]
+        _ => return Err(ConversionError::Unsupported(expression.clone().into())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::core::v1::NodeSelectorRequirement;
+
+    use crate::Expressions;
+
+    fn requirement(key: &str, operator: &str, values: &[&str]) -> NodeSelectorRequirement {
+        NodeSelectorRequirement {
+            key: key.into(),
+            operator: operator.into(),
+            values: (!values.is_empty()).then(|| values.iter().map(|v| v.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn node_selector_term() {
+        let expressions =
+            Expressions::try_from("zone in (a,b),arch=amd64,tier!=spot,gpu,!taint,cores>4")
+                .unwrap();
+        assert_eq!(
+            expressions
+                .to_node_selector_term()
+                .unwrap()
+                .match_expressions,
+            Some(vec![
+                requirement("zone", "In", &["a", "b"]),
+                requirement("arch", "In", &["amd64"]),
+                requirement("tier", "NotIn", &["spot"]),
+                requirement("gpu", "Exists", &[]),
+                requirement("taint", "DoesNotExist", &[]),
+                requirement("cores", "Gt", &["4"]),
+            ])
+        );
+        assert_eq!(
+            Expressions::default()
+                .to_node_selector_term()
+                .unwrap()
+                .match_expressions,
+            None
+        );
+    }
+
+    #[test]
+    fn pod_affinity_term() {
+        let term = Expressions::try_from("app=web,!canary")
+            .unwrap()
+            .to_pod_affinity_term("kubernetes.io/hostname")
+            .unwrap();
+        assert_eq!(term.topology_key, "kubernetes.io/hostname");
+        assert_eq!(
+            Expressions::try_from(term.label_selector.unwrap())
+                .unwrap()
+                .to_string(),
+            "app=web,!canary"
+        );
+        assert!(
+            Expressions::try_from("a<1")
+                .unwrap()
+                .to_pod_affinity_term("zone")
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Dialect, Expression, Expressions, Iter, LabelSet, ParseError, Result};
+
+/// A parsed label selector, mirroring apimachinery's `labels.Selector`.
+///
+/// An empty selector matches everything, while a selector with contradictory
+/// requirements, such as `a=b,a=c`, matches nothing.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Selector(pub(crate) Expressions);
+
+impl Selector {
+    /// Create a selector matching everything
+    pub fn everything() -> Self {
+        Self::default()
+    }
+
+    /// Check whether the selector matches the given labels
+    pub fn matches(&self, labels: &impl LabelSet) -> bool {
+        self.0.matches(labels)
+    }
+
+    /// Indicates whether this selector matches every label set
+    pub fn matches_everything(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Indicates whether this selector can't match any label set
+    pub fn matches_nothing(&self) -> bool {
+        !self.0.is_satisfiable()
+    }
+
+    /// Requirements of the selector, in parse order
+    pub fn requirements(&self) -> Iter<'_> {
+        self.0.iter()
+    }
+
+    /// Add a requirement to the selector
+    pub fn with(mut self, requirement: Expression) -> Self {
+        self.0.push(requirement);
+        self
+    }
+
+    /// Unwrap the underlying expressions
+    pub fn into_expressions(self) -> Expressions {
+        self.0
+    }
+}
+
+impl From<Expressions> for Selector {
+    fn from(expressions: Expressions) -> Self {
+        Self(expressions)
+    }
+}
+
+impl From<Selector> for Expressions {
+    fn from(selector: Selector) -> Self {
+        selector.0
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Selector {
+    /// Parse a selector in the given dialect
+    pub fn parse_with(dialect: impl Into<Dialect>, selector: &str) -> Result<Self> {
+        Expressions::parse_with(dialect, selector).map(Self)
+    }
+}
+
+impl TryFrom<&str> for Selector {
+    type Error = ParseError;
+
+    fn try_from(selector: &str) -> Result<Self> {
+        Expressions::try_from(selector).map(Self)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = ParseError;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::try_from(selector)
+    }
+}
+
+#[cfg(feature = "kube-rs")]
+impl TryFrom<Selector> for kube::core::Selector {
+    type Error = crate::ConversionError;
+
+    fn try_from(selector: Selector) -> std::result::Result<Self, Self::Error> {
+        selector.0.try_into()
+    }
+}
+
+#[cfg(feature = "kube-rs")]
+impl From<kube::core::Selector> for Selector {
+    fn from(selector: kube::core::Selector) -> Self {
+        Self(selector.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{Expression, Selector};
+
+    #[test]
+    fn everything_and_nothing() {
+        assert!(Selector::everything().matches_everything());
+        assert!(Selector::everything().matches(&BTreeMap::<String, String>::new()));
+        assert!(!Selector::everything().matches_nothing());
+
+        for (selector, nothing) in [
+            ("a=b", false),
+            ("a=b,a=c", true),
+            ("a=b,a==b", false),
+            ("a in (b,c),a=c", false),
+            ("a in (b,c),a notin (b,c)", true),
+            ("a in (b,c),a notin (b),a!=c", true),
+            ("a,!a", true),
+            ("a=b,!a", true),
+            ("a notin (b),!a", false),
+            ("a=b,b=c", false),
+            ("a>3,a<5", false),
+            ("a>3,a<4", true),
+            ("a>1,a!=2,a<3", false),
+            ("a in (1,2),a>2", true),
+            ("a>1,!a", true),
+        ] {
+            let selector: Selector = selector.parse().unwrap();
+            assert!(!selector.matches_everything());
+            assert_eq!(selector.matches_nothing(), nothing, "{selector}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let selector: Selector = "app=web,env in (prod,stage),!canary".parse().unwrap();
+        assert_eq!(selector.to_string(), "app=web,env in (prod,stage),!canary");
+        assert_eq!(selector.requirements().count(), 3);
+
+        let selector = selector.with(Expression::Exists("team".into()));
+        assert_eq!(
+            selector.requirements().next_back(),
+            Some(&Expression::Exists("team".into()))
+        );
+        assert!(selector.matches(&BTreeMap::from([
+            ("app", "web"),
+            ("env", "prod"),
+            ("team", "a")
+        ])));
+    }
+}
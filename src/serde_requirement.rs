@@ -0,0 +1,129 @@
+//! Serialize [`Expression`] fields shaped as Kubernetes label selector
+//! requirements, such as `{"key": "env", "operator": "In", "values": ["prod"]}`.
+//!
+//! Equality requirements are written as single valued `In` and `NotIn`
+//! requirements. Expressions without an equivalent requirement fail to
+//! serialize.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use unselector::{Expression, Expressions};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Rule {
+//!     #[serde(with = "unselector::serde_requirement")]
+//!     requirement: Expression,
+//!
+//!     #[serde(with = "unselector::serde_requirement::seq")]
+//!     requirements: Expressions,
+//! }
+//! ```
+
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Expression, LabelSelectorRequirement};
+
+/// Serialize the expression as a requirement
+pub fn serialize<S: Serializer>(expression: &Expression, serializer: S) -> Result<S::Ok, S::Error> {
+    LabelSelectorRequirement::try_from(expression.clone())
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+/// Deserialize the expression from a requirement
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Expression, D::Error> {
+    LabelSelectorRequirement::deserialize(deserializer)?
+        .try_into()
+        .map_err(D::Error::custom)
+}
+
+/// Serialize [`Expressions`](crate::Expressions) fields as lists of requirements
+pub mod seq {
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{Expression, Expressions, LabelSelectorRequirement};
+
+    /// Serialize the expressions as a list of requirements
+    pub fn serialize<S: Serializer>(
+        expressions: &Expressions,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        expressions
+            .iter()
+            .map(|expression| LabelSelectorRequirement::try_from(expression.clone()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+
+    /// Deserialize the expressions from a list of requirements
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Expressions, D::Error> {
+        Vec::<LabelSelectorRequirement>::deserialize(deserializer)?
+            .into_iter()
+            .map(Expression::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Expression, Expressions};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Rule {
+        #[serde(with = "crate::serde_requirement")]
+        requirement: Expression,
+
+        #[serde(default, with = "crate::serde_requirement::seq")]
+        requirements: Expressions,
+    }
+
+    #[test]
+    fn round_trip() {
+        let rule = Rule {
+            requirement: Expression::r#in("env", ["prod", "stage"]),
+            requirements: Expressions::try_from("tier!=db,!canary").unwrap(),
+        };
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"requirement":{"key":"env","operator":"In","values":["prod","stage"]},"#,
+                r#""requirements":[{"key":"tier","operator":"NotIn","values":["db"]},"#,
+                r#"{"key":"canary","operator":"DoesNotExist"}]}"#,
+            )
+        );
+        assert_eq!(
+            serde_json::from_str::<Rule>(&json).unwrap(),
+            Rule {
+                requirement: rule.requirement,
+                requirements: Expressions::try_from("tier notin (db),!canary").unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let rule = Rule {
+            requirement: Expression::greater_than("cpu", 2),
+            requirements: Expressions::default(),
+        };
+        let error = serde_json::to_string(&rule).unwrap_err();
+        assert!(error.to_string().contains("no equivalent"));
+
+        let error = serde_json::from_str::<Rule>(
+            r#"{"requirement": {"key": "env", "operator": "Exists", "values": ["a"]}}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("requirement is invalid"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Serialize [`Expressions`] fields as selector strings.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use unselector::Expressions;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "unselector::serde_string")]
+//!     selector: Expressions,
+//! }
+//! ```
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::Expressions;
+
+/// Description of selector strings in generated schemas
+#[cfg(any(feature = "schemars", feature = "utoipa"))]
+pub(crate) const DESCRIPTION: &str =
+    "Kubernetes label selector, such as `app=web,tier in (a,b),!canary`";
+
+/// Characters selector strings may consist of, for generated schemas
+#[cfg(any(feature = "schemars", feature = "utoipa"))]
+pub(crate) const PATTERN: &str = r"^[-A-Za-z0-9_./=!<>(), ]*$";
+
+/// Serialize the expressions as a selector string
+pub fn serialize<S: Serializer>(
+    expressions: &Expressions,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(expressions)
+}
+
+/// Deserialize the expressions by parsing a selector string
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Expressions, D::Error> {
+    let selector = String::deserialize(deserializer)?;
+    Expressions::try_from(selector.as_str()).map_err(D::Error::custom)
+}
+
+/// Schema of selector strings, for use with
+/// `#[schemars(schema_with = "unselector::serde_string::json_schema")]`
+#[cfg(feature = "schemars")]
+pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+        "type": "string",
+        "description": DESCRIPTION,
+        "pattern": PATTERN,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::Expressions;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "crate::serde_string")]
+        selector: Expressions,
+    }
+
+    #[test]
+    fn round_trip() {
+        let config: Config = serde_json::from_str(r#"{"selector": "app=web, !canary"}"#).unwrap();
+        assert_eq!(
+            config.selector,
+            Expressions::try_from("app=web,!canary").unwrap()
+        );
+        assert_eq!(
+            serde_json::to_string(&config).unwrap(),
+            r#"{"selector":"app=web,!canary"}"#
+        );
+
+        let error = serde_json::from_str::<Config>(r#"{"selector": "a in b"}"#).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("set operator is not followed by a list of values")
+        );
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema() {
+        use crate::AnySelector;
+
+        let schema = serde_json::to_value(schemars::schema_for!(AnySelector)).unwrap();
+        let variants = schema["anyOf"].as_array().unwrap();
+        assert_eq!(variants[0]["type"], "string");
+        assert_eq!(variants[0]["pattern"], super::PATTERN);
+        assert_eq!(variants[1]["$ref"], "#/$defs/LabelSelector");
+        assert!(schema["$defs"]["LabelSelector"]["properties"]["matchLabels"].is_object());
+    }
+}
@@ -0,0 +1,264 @@
+//! Serialize [`Expressions`] fields as Helm style `selector:` maps, where a
+//! scalar is an exact value and a list holds alternatives.
+//!
+//! ```yaml
+//! selector:
+//!   app: web
+//!   env: [prod, stage]
+//! ```
+//!
+//! reads as `app=web,env in (prod,stage)`. Numbers and booleans are taken as
+//! their string form, as they are when a chart renders them into labels.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use unselector::Expressions;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Values {
+//!     #[serde(with = "unselector::serde_values")]
+//!     selector: Expressions,
+//! }
+//! ```
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::de::{Error as _, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Error as _, SerializeMap};
+use serde::{Deserializer, Serializer};
+
+use crate::{Expression, Expressions, is_key, is_value};
+
+/// Serialize the expressions as a map of keys to a value or a list of values
+pub fn serialize<S: Serializer>(
+    expressions: &Expressions,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut entries = BTreeMap::new();
+    for expression in expressions {
+        let entry = match entries.entry(expression.key()) {
+            Entry::Vacant(entry) => entry,
+            Entry::Occupied(_) => {
+                return Err(S::Error::custom(format!(
+                    "key '{}' is selected more than once",
+                    expression.key()
+                )));
+            }
+        };
+        match expression {
+            Expression::Equal(_, value) => entry.insert(Values::One(value)),
+            Expression::In(_, values) => entry.insert(Values::Many(values)),
+            expression => {
+                return Err(S::Error::custom(format!(
+                    "'{expression}' has no equivalent in a values selector"
+                )));
+            }
+        };
+    }
+
+    let mut map = serializer.serialize_map(Some(entries.len()))?;
+    for (key, values) in entries {
+        match values {
+            Values::One(value) => map.serialize_entry(key, value)?,
+            Values::Many(values) => map.serialize_entry(key, values)?,
+        }
+    }
+    map.end()
+}
+
+enum Values<'a> {
+    One(&'a String),
+    Many(&'a BTreeSet<String>),
+}
+
+/// Deserialize the expressions from a map of keys to a value or a list of
+/// values
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Expressions, D::Error> {
+    deserializer.deserialize_map(SelectorVisitor)
+}
+
+struct SelectorVisitor;
+
+impl<'de> Visitor<'de> for SelectorVisitor {
+    type Value = Expressions;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of label keys to a value or a list of values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Expressions, A::Error> {
+        let mut expressions = Expressions::default();
+        while let Some(key) = map.next_key::<String>()? {
+            if !is_key(&key) {
+                return Err(A::Error::custom(format!("invalid label key '{key}'")));
+            }
+            let values: Vec<String> = map.next_value_seed(ValuesSeed(&key))?;
+            expressions.push(match <[_; 1]>::try_from(values) {
+                Ok([value]) => Expression::equal(key, value),
+                Err(values) => Expression::r#in(key, values),
+            });
+        }
+        Ok(expressions)
+    }
+}
+
+/// Reads a scalar or a non-empty list of scalars for the given key
+struct ValuesSeed<'a>(&'a str);
+
+impl<'de> serde::de::DeserializeSeed<'de> for ValuesSeed<'_> {
+    type Value = Vec<String>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Vec<String>, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl ValuesSeed<'_> {
+    fn value<E: serde::de::Error>(&self, value: String) -> Result<String, E> {
+        match value.is_empty() || is_value(&value) {
+            true => Ok(value),
+            false => Err(E::custom(format!(
+                "invalid value '{value}' for label key '{}'",
+                self.0
+            ))),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for ValuesSeed<'_> {
+    type Value = Vec<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a value or a list of values for label key '{}'", self.0)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Vec<String>, E> {
+        Ok(vec![self.value(value.to_owned())?])
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, value: bool) -> Result<Vec<String>, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Vec<String>, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Vec<String>, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Vec<String>, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<String>, A::Error> {
+        let mut values = vec![];
+        while let Some(value) = seq.next_element_seed(ScalarSeed(&self))? {
+            values.push(value);
+        }
+        match values.is_empty() {
+            true => Err(A::Error::custom(format!(
+                "empty list of values for label key '{}'",
+                self.0
+            ))),
+            false => Ok(values),
+        }
+    }
+}
+
+/// Reads a single scalar within a list of values
+struct ScalarSeed<'a, 'b>(&'a ValuesSeed<'b>);
+
+impl<'de> serde::de::DeserializeSeed<'de> for ScalarSeed<'_, '_> {
+    type Value = String;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<String, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ScalarSeed<'_, '_> {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a value for label key '{}'", self.0.0)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<String, E> {
+        self.0.value(value.to_owned())
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, value: bool) -> Result<String, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<String, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<String, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<String, E> {
+        self.visit_str(&value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::Expressions;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Values {
+        #[serde(with = "crate::serde_values")]
+        selector: Expressions,
+    }
+
+    #[test]
+    fn round_trip() {
+        let values: Values = serde_json::from_str(
+            r#"{"selector": {"app": "web", "env": ["prod", "stage"], "tier": [3], "debug": false}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            values.selector,
+            Expressions::try_from("app=web,env in (prod,stage),tier=3,debug=false").unwrap()
+        );
+        assert_eq!(
+            serde_json::to_string(&values).unwrap(),
+            r#"{"selector":{"app":"web","debug":"false","env":["prod","stage"],"tier":"3"}}"#
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for (json, message) in [
+            (r#"{"env": []}"#, "empty list of values for label key 'env'"),
+            (
+                r#"{"env": "a b"}"#,
+                "invalid value 'a b' for label key 'env'",
+            ),
+            (r#"{"a b": "c"}"#, "invalid label key 'a b'"),
+            (r#"{"env": {"a": "b"}}"#, "a value or a list of values"),
+            (r#"{"env": [["a"]]}"#, "a value for label key 'env'"),
+            (r#""app=web""#, "a map of label keys"),
+        ] {
+            let json = format!(r#"{{"selector": {json}}}"#);
+            let error = serde_json::from_str::<Values>(&json).unwrap_err();
+            assert!(error.to_string().contains(message), "{error}");
+        }
+
+        for selector in ["app=web,app=api", "!canary"] {
+            let values = Values {
+                selector: Expressions::try_from(selector).unwrap(),
+            };
+            assert!(serde_json::to_string(&values).is_err(), "{selector}");
+        }
+    }
+}
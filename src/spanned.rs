@@ -0,0 +1,93 @@
+use std::ops::Deref;
+
+use logos::{Logos, Span};
+
+use crate::{
+    Dialect, Expression, Expressions, ParsedExpression, Result, check_length, parse_expression,
+    too_many_requirements,
+};
+
+/// A parsed value with the position it was parsed from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    /// Parsed value
+    pub value: T,
+
+    /// Position of the value in the input
+    pub span: Span,
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl Expressions {
+    /// Parse a selector, keeping the position of each requirement
+    pub fn parse_spanned(selector: &str) -> Result<Vec<Spanned<Expression>>> {
+        Self::parse_spanned_with(Dialect::Kubernetes, selector)
+    }
+
+    /// Parse a selector in the given dialect, keeping the position of each
+    /// requirement
+    pub fn parse_spanned_with(
+        dialect: impl Into<Dialect>,
+        selector: &str,
+    ) -> Result<Vec<Spanned<Expression>>> {
+        let options = dialect.into().options();
+        check_length(selector, &options)?;
+        let mut lexer = ParsedExpression::lexer_with_extras(selector, options);
+        let mut expressions = vec![];
+        while let Some(ParsedExpression::Expression(value)) = parse_expression(&mut lexer)? {
+            if let Some(error) = too_many_requirements(&lexer, expressions.len()) {
+                return Err(error);
+            }
+            expressions.push(Spanned {
+                value,
+                span: lexer.span(),
+            });
+        }
+        Ok(expressions)
+    }
+}
+
+impl From<Vec<Spanned<Expression>>> for Expressions {
+    fn from(expressions: Vec<Spanned<Expression>>) -> Self {
+        expressions
+            .into_iter()
+            .map(|expression| expression.value)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, Expressions, ParseError};
+
+    #[test]
+    fn spans() {
+        let selector = "app = web, tier in (a, b),!canary";
+        let spanned = Expressions::parse_spanned(selector).unwrap();
+        assert_eq!(
+            spanned
+                .iter()
+                .map(|expression| &selector[expression.span.clone()])
+                .collect::<Vec<_>>(),
+            ["app = web", "tier in (a, b)", "!canary"]
+        );
+        assert_eq!(spanned[2].key(), "canary");
+        assert_eq!(spanned[0].value, Expression::equal("app", "web"));
+        assert_eq!(
+            Expressions::from(spanned),
+            Expressions::try_from(selector).unwrap()
+        );
+
+        assert_eq!(
+            Expressions::parse_spanned("a, =b"),
+            Err(ParseError::EmptyKey("=".into(), 3..4))
+        );
+    }
+}
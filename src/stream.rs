@@ -0,0 +1,131 @@
+use std::iter::FusedIterator;
+
+use logos::{Lexer, Logos};
+
+use crate::{
+    Dialect, Expression, ParseError, ParsedExpression, Result, check_length, parse_expression,
+    too_many_requirements,
+};
+
+/// Iterator over the requirements of a selector, parsing each one only when
+/// it is requested, see [`parse_iter`]
+pub struct ParseIter<'a> {
+    lexer: Lexer<'a, ParsedExpression>,
+    parsed: usize,
+    failed: Option<ParseError>,
+    done: bool,
+}
+
+/// Parse the requirements of a selector one at a time, without collecting
+/// them into [`crate::Expressions`].
+///
+/// The first error ends the iteration.
+///
+/// ```
+/// use unselector::{Expression, parse_iter};
+///
+/// let mut requirements = parse_iter("app=web,tier in (a,b),!");
+/// assert_eq!(requirements.next(), Some(Ok(Expression::equal("app", "web"))));
+/// assert!(requirements.nth(1).unwrap().is_err());
+/// assert_eq!(requirements.next(), None);
+/// ```
+pub fn parse_iter(selector: &str) -> ParseIter<'_> {
+    parse_iter_with(Dialect::Kubernetes, selector)
+}
+
+/// Parse the requirements of a selector in the given dialect one at a time
+pub fn parse_iter_with(dialect: impl Into<Dialect>, selector: &str) -> ParseIter<'_> {
+    let options = dialect.into().options();
+    ParseIter {
+        failed: check_length(selector, &options).err(),
+        lexer: ParsedExpression::lexer_with_extras(selector, options),
+        parsed: 0,
+        done: false,
+    }
+}
+
+impl Iterator for ParseIter<'_> {
+    type Item = Result<Expression>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = match self.failed.take() {
+            Some(error) => Some(Err(error)),
+            None => match parse_expression(&mut self.lexer) {
+                Ok(Some(ParsedExpression::Expression(expression))) => {
+                    match too_many_requirements(&self.lexer, self.parsed) {
+                        Some(error) => Some(Err(error)),
+                        None => Some(Ok(expression)),
+                    }
+                }
+                Ok(None) => None,
+                Err(error) => Some(Err(error)),
+            },
+        };
+        self.parsed += 1;
+        self.done = !matches!(next, Some(Ok(_)));
+        next
+    }
+}
+
+impl FusedIterator for ParseIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Dialect, Expression, Expressions, Limit, ParseError, ParseOptions, parse_iter,
+        parse_iter_with,
+    };
+
+    #[test]
+    fn lazy() {
+        let selector = "app=web,tier in (a,b),!canary";
+        assert_eq!(
+            parse_iter(selector).collect::<Result<Expressions, _>>(),
+            Expressions::try_from(selector)
+        );
+        assert_eq!(
+            parse_iter("a,b,=c").collect::<Vec<_>>(),
+            [
+                Ok(Expression::exists("a")),
+                Ok(Expression::exists("b")),
+                Err(ParseError::EmptyKey("=".into(), 4..5))
+            ]
+        );
+        assert_eq!(parse_iter("").next(), None);
+        assert_eq!(
+            parse_iter_with(Dialect::Extended, "a='b c'").next(),
+            Some(Ok(Expression::equal("a", "b c")))
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let options = ParseOptions {
+            max_length: Some(3),
+            max_requirements: Some(1),
+            ..ParseOptions::strict()
+        };
+        assert_eq!(
+            parse_iter_with(options, "a,b").collect::<Vec<_>>(),
+            [
+                Ok(Expression::exists("a")),
+                Err(ParseError::LimitExceeded(
+                    "b".into(),
+                    2..3,
+                    Limit::Requirements(1)
+                ))
+            ]
+        );
+        assert_eq!(
+            parse_iter_with(options, "a,bc").collect::<Vec<_>>(),
+            [Err(ParseError::LimitExceeded(
+                String::new(),
+                3..4,
+                Limit::Length(3)
+            ))]
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{ParseError, Result, is_key, is_value};
+
+/// Effect of a taint on pods which do not tolerate it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TaintEffect {
+    /// New pods are not scheduled onto the node
+    NoSchedule,
+
+    /// The scheduler tries to avoid the node
+    PreferNoSchedule,
+
+    /// Running pods are evicted from the node
+    NoExecute,
+}
+
+impl fmt::Display for TaintEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TaintEffect::NoSchedule => "NoSchedule",
+            TaintEffect::PreferNoSchedule => "PreferNoSchedule",
+            TaintEffect::NoExecute => "NoExecute",
+        })
+    }
+}
+
+impl FromStr for TaintEffect {
+    type Err = ParseError;
+
+    fn from_str(effect: &str) -> Result<Self> {
+        match effect {
+            "NoSchedule" => Ok(TaintEffect::NoSchedule),
+            "PreferNoSchedule" => Ok(TaintEffect::PreferNoSchedule),
+            "NoExecute" => Ok(TaintEffect::NoExecute),
+            _ => Err(ParseError::StringParse(effect.to_owned(), 0..effect.len())),
+        }
+    }
+}
+
+/// A node taint, as accepted by `kubectl taint`, such as `key=value:NoSchedule`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Taint {
+    /// Key of the taint
+    pub key: String,
+
+    /// Optional value of the taint
+    pub value: Option<String>,
+
+    /// Effect on pods not tolerating the taint
+    pub effect: TaintEffect,
+}
+
+impl Taint {
+    /// Check whether the taint is tolerated by the toleration
+    pub fn tolerated_by(&self, toleration: &Toleration) -> bool {
+        toleration.tolerates(self)
+    }
+}
+
+impl fmt::Display for Taint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}={value}:{}", self.key, self.effect),
+            None => write!(f, "{}:{}", self.key, self.effect),
+        }
+    }
+}
+
+impl TryFrom<&str> for Taint {
+    type Error = ParseError;
+
+    fn try_from(taint: &str) -> Result<Self> {
+        let error = || ParseError::StringParse(taint.to_owned(), 0..taint.len());
+        let (requirement, effect) = taint.rsplit_once(':').ok_or_else(error)?;
+        let (key, value) = split_requirement(requirement).ok_or_else(error)?;
+        Ok(Taint {
+            key: key.ok_or_else(error)?.to_owned(),
+            value: value.map(str::to_owned),
+            effect: effect.parse().map_err(|_| error())?,
+        })
+    }
+}
+
+impl FromStr for Taint {
+    type Err = ParseError;
+
+    fn from_str(taint: &str) -> Result<Self> {
+        Self::try_from(taint)
+    }
+}
+
+/// A toleration of taints, written like a taint with optional parts.
+///
+/// `key=value:NoSchedule` tolerates the exact taint, `key` tolerates any taint
+/// with that key, and an empty key as in `:NoExecute` tolerates every taint
+/// with the effect.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Toleration {
+    /// Key of tolerated taints, or any key if unset
+    pub key: Option<String>,
+
+    /// Value of tolerated taints, or any value if unset
+    pub value: Option<String>,
+
+    /// Effect of tolerated taints, or any effect if unset
+    pub effect: Option<TaintEffect>,
+}
+
+impl Toleration {
+    /// Check whether the toleration tolerates the taint
+    pub fn tolerates(&self, taint: &Taint) -> bool {
+        self.effect.is_none_or(|effect| effect == taint.effect)
+            && self.key.as_ref().is_none_or(|key| *key == taint.key)
+            && self
+                .value
+                .as_deref()
+                .is_none_or(|value| value == taint.value.as_deref().unwrap_or_default())
+    }
+}
+
+impl fmt::Display for Toleration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(key) = &self.key {
+            f.write_str(key)?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, "={value}")?;
+        }
+        if let Some(effect) = &self.effect {
+            write!(f, ":{effect}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for Toleration {
+    type Error = ParseError;
+
+    fn try_from(toleration: &str) -> Result<Self> {
+        let error = || ParseError::StringParse(toleration.to_owned(), 0..toleration.len());
+        let (requirement, effect) = match toleration.rsplit_once(':') {
+            Some((requirement, effect)) => {
+                (requirement, Some(effect.parse().map_err(|_| error())?))
+            }
+            None => (toleration, None),
+        };
+        let (key, value) = split_requirement(requirement).ok_or_else(error)?;
+        // A value can only be matched against a specific key
+        if key.is_none() && value.is_some() {
+            return Err(error());
+        }
+        Ok(Toleration {
+            key: key.map(str::to_owned),
+            value: value.map(str::to_owned),
+            effect,
+        })
+    }
+}
+
+impl FromStr for Toleration {
+    type Err = ParseError;
+
+    fn from_str(toleration: &str) -> Result<Self> {
+        Self::try_from(toleration)
+    }
+}
+
+/// Split `key=value` into its parts, where both may be missing
+fn split_requirement(requirement: &str) -> Option<(Option<&str>, Option<&str>)> {
+    let (key, value) = match requirement.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (requirement, None),
+    };
+    let key = match key {
+        "" => None,
+        key if is_key(key) => Some(key),
+        _ => return None,
+    };
+    match value {
+        Some(value) if !value.is_empty() && !is_value(value) => None,
+        value => Some((key, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseError, Taint, TaintEffect, Toleration};
+
+    #[test]
+    fn parse_taints() {
+        assert_eq!(
+            "dedicated=gpu:NoSchedule".parse(),
+            Ok(Taint {
+                key: "dedicated".into(),
+                value: Some("gpu".into()),
+                effect: TaintEffect::NoSchedule,
+            })
+        );
+        assert_eq!(
+            "node.kubernetes.io/unreachable:NoExecute".parse(),
+            Ok(Taint {
+                key: "node.kubernetes.io/unreachable".into(),
+                value: None,
+                effect: TaintEffect::NoExecute,
+            })
+        );
+        for taint in ["a=b:PreferNoSchedule", "a:NoSchedule", "a=:NoExecute"] {
+            assert_eq!(taint.parse::<Taint>().unwrap().to_string(), taint);
+        }
+
+        let error = |text: &str| Err(ParseError::StringParse(text.into(), 0..text.len()));
+        assert_eq!(Taint::try_from("a=b"), error("a=b"));
+        assert_eq!(Taint::try_from(":NoSchedule"), error(":NoSchedule"));
+        assert_eq!(Taint::try_from("a=b:Never"), error("a=b:Never"));
+        assert_eq!(Taint::try_from("a b:NoSchedule"), error("a b:NoSchedule"));
+    }
+
+    #[test]
+    fn tolerations() {
+        let taint: Taint = "dedicated=gpu:NoSchedule".parse().unwrap();
+        for toleration in [
+            "dedicated=gpu:NoSchedule",
+            "dedicated:NoSchedule",
+            "dedicated",
+            ":NoSchedule",
+            "",
+        ] {
+            let toleration: Toleration = toleration.parse().unwrap();
+            assert!(taint.tolerated_by(&toleration), "{toleration}");
+            assert_eq!(toleration.to_string().parse(), Ok(toleration));
+        }
+        for toleration in ["dedicated=cpu", "dedicated:NoExecute", "gpu", "dedicated="] {
+            let toleration: Toleration = toleration.parse().unwrap();
+            assert!(!taint.tolerated_by(&toleration), "{toleration}");
+        }
+
+        let unvalued: Taint = "a:NoExecute".parse().unwrap();
+        assert!(Toleration::try_from("a=").unwrap().tolerates(&unvalued));
+        assert!(Toleration::try_from("=b").is_err());
+    }
+}
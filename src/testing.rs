@@ -0,0 +1,102 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{Expression, Expressions};
+
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const INNER: &[u8] = b"-_.";
+
+/// A name of up to 8 characters, starting and ending with an alphanumeric
+fn name(u: &mut Unstructured<'_>) -> Result<String> {
+    let len = u.int_in_range(1..=8)?;
+    let mut name = String::with_capacity(len);
+    for i in 0..len {
+        let inner = i > 0 && i + 1 < len && u.ratio(1, 4)?;
+        let chars = if inner { INNER } else { ALPHANUMERIC };
+        name.push(*u.choose(chars)? as char);
+    }
+    Ok(name)
+}
+
+/// A key, optionally with a DNS subdomain prefix
+fn key(u: &mut Unstructured<'_>) -> Result<String> {
+    let name = name(u)?;
+    Ok(match u.ratio(1, 4)? {
+        true => format!("{}.io/{name}", name_lowercase(u)?),
+        false => name,
+    })
+}
+
+fn name_lowercase(u: &mut Unstructured<'_>) -> Result<String> {
+    let len = u.int_in_range(1..=8)?;
+    (0..len)
+        .map(|_| Ok(*u.choose(b"abcdefghijklmnopqrstuvwxyz0123456789")? as char))
+        .collect()
+}
+
+fn value(u: &mut Unstructured<'_>) -> Result<String> {
+    match u.ratio(1, 8)? {
+        true => Ok(String::new()),
+        false => name(u),
+    }
+}
+
+fn values(u: &mut Unstructured<'_>) -> Result<Vec<String>> {
+    let len = u.int_in_range(1..=3)?;
+    (0..len).map(|_| name(u)).collect()
+}
+
+/// Generates requirements which display as selectors accepted by the strict
+/// parser. Regex and glob requirements are not generated.
+impl<'a> Arbitrary<'a> for Expression {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let key = key(u)?;
+        Ok(match u.int_in_range(0..=7)? {
+            0 => Expression::r#in(key, values(u)?),
+            1 => Expression::not_in(key, values(u)?),
+            2 => Expression::equal(key, value(u)?),
+            3 => Expression::not_equal(key, value(u)?),
+            4 => Expression::exists(key),
+            5 => Expression::does_not_exist(key),
+            6 => Expression::greater_than(key, u.arbitrary()?),
+            _ => Expression::less_than(key, u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Expressions {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let len = u.int_in_range(0..=4)?;
+        (0..len).map(|_| u.arbitrary::<Expression>()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::Expressions;
+
+    #[test]
+    fn round_trip() {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let bytes: Vec<u8> = (0..1 << 16)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..500 {
+            let expressions = Expressions::arbitrary(&mut u).unwrap();
+            let selector = expressions.to_string();
+            assert_eq!(
+                Expressions::try_from(selector.as_str()),
+                Ok(expressions),
+                "{selector}"
+            );
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use logos::Span;
+
+use crate::is_key_char;
+
+/// Category of a selector token, for syntax highlighting
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Label key starting a requirement
+    Key,
+
+    /// `=`, `==`, `!=`, `!`, `in`, `notin`, `>`, `<`, `=~`, `!~` or `~`
+    Operator,
+
+    /// Value following an operator or within a list of values
+    Value,
+
+    /// `(` opening a list of values
+    OpenParen,
+
+    /// `)` closing a list of values
+    CloseParen,
+
+    /// `,` separating requirements or values
+    Separator,
+
+    /// Character which can't start any token
+    Unknown,
+}
+
+/// A classified part of a selector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// Category of the token
+    pub kind: TokenKind,
+
+    /// Text of the token as written, which may be quoted or escaped
+    pub text: &'a str,
+
+    /// Position of the token in the selector
+    pub span: Span,
+}
+
+/// What the next word of a selector is read as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Key,
+    Operator,
+    Value,
+    List,
+    End,
+}
+
+/// Iterator over the tokens of a selector, see [`tokenize`]
+#[derive(Clone, Debug)]
+pub struct Tokens<'a> {
+    source: &'a str,
+    position: usize,
+    state: State,
+}
+
+/// Split a selector into classified tokens, skipping whitespace.
+///
+/// Tokenizing never fails, so it can highlight selectors which don't parse.
+///
+/// ```
+/// use unselector::{TokenKind, tokenize};
+///
+/// let kinds: Vec<_> = tokenize("env in (a)").map(|token| token.kind).collect();
+/// assert_eq!(
+///     kinds,
+///     [
+///         TokenKind::Key,
+///         TokenKind::Operator,
+///         TokenKind::OpenParen,
+///         TokenKind::Value,
+///         TokenKind::CloseParen
+///     ]
+/// );
+/// ```
+pub fn tokenize(selector: &str) -> Tokens<'_> {
+    Tokens {
+        source: selector,
+        position: 0,
+        state: State::Key,
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let rest = &self.source[self.position..];
+        let trimmed = rest.trim_start();
+        let start = self.position + rest.len() - trimmed.len();
+        let mut chars = trimmed.chars();
+        let c = chars.next()?;
+        let next = chars.next();
+
+        let (kind, len) = match c {
+            ',' => {
+                if self.state != State::List {
+                    self.state = State::Key;
+                }
+                (TokenKind::Separator, 1)
+            }
+            '(' => {
+                self.state = State::List;
+                (TokenKind::OpenParen, 1)
+            }
+            ')' => {
+                self.state = State::End;
+                (TokenKind::CloseParen, 1)
+            }
+            '!' | '=' if matches!(next, Some('=' | '~')) => {
+                self.state = State::Value;
+                (TokenKind::Operator, 2)
+            }
+            '!' if self.state == State::Key => (TokenKind::Operator, 1),
+            '=' | '>' | '<' | '~' => {
+                self.state = State::Value;
+                (TokenKind::Operator, 1)
+            }
+            '"' | '\'' => {
+                let len = trimmed[1..].find(c).map_or(trimmed.len(), |end| end + 2);
+                if self.state != State::List {
+                    self.state = State::End;
+                }
+                (TokenKind::Value, len)
+            }
+            c if is_key_char(c) || c == '\\' => {
+                let len = word(trimmed);
+                let word = &trimmed[..len];
+                let kind = match self.state {
+                    State::Operator
+                        if word.eq_ignore_ascii_case("in")
+                            || word.eq_ignore_ascii_case("notin") =>
+                    {
+                        self.state = State::End;
+                        TokenKind::Operator
+                    }
+                    State::Value => {
+                        self.state = State::End;
+                        TokenKind::Value
+                    }
+                    State::List => TokenKind::Value,
+                    State::Key | State::Operator | State::End => {
+                        self.state = State::Operator;
+                        TokenKind::Key
+                    }
+                };
+                (kind, len)
+            }
+            c => (TokenKind::Unknown, c.len_utf8()),
+        };
+
+        self.position = start + len;
+        Some(Token {
+            kind,
+            text: &self.source[start..self.position],
+            span: start..self.position,
+        })
+    }
+}
+
+/// Length of the key or value at the start of the source, including escapes
+fn word(source: &str) -> usize {
+    let mut chars = source.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            c if !is_key_char(c) => return i,
+            _ => {}
+        }
+    }
+    source.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenKind::*;
+    use crate::{TokenKind, tokenize};
+
+    fn tokens(selector: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(selector)
+            .map(|token| {
+                assert_eq!(&selector[token.span.clone()], token.text);
+                (token.kind, token.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classify() {
+        assert_eq!(
+            tokens("app=web, tier notin (a, 'b c'),!canary, n > 1 env"),
+            [
+                (Key, "app"),
+                (Operator, "="),
+                (Value, "web"),
+                (Separator, ","),
+                (Key, "tier"),
+                (Operator, "notin"),
+                (OpenParen, "("),
+                (Value, "a"),
+                (Separator, ","),
+                (Value, "'b c'"),
+                (CloseParen, ")"),
+                (Separator, ","),
+                (Operator, "!"),
+                (Key, "canary"),
+                (Separator, ","),
+                (Key, "n"),
+                (Operator, ">"),
+                (Value, "1"),
+                (Key, "env"),
+            ]
+        );
+        assert_eq!(
+            tokens(r"a\,b != in"),
+            [(Key, r"a\,b"), (Operator, "!="), (Value, "in")]
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(
+            tokens("a in db * ="),
+            [
+                (Key, "a"),
+                (Operator, "in"),
+                (Key, "db"),
+                (Unknown, "*"),
+                (Operator, "="),
+            ]
+        );
+        assert_eq!(tokens("a='b"), [(Key, "a"), (Operator, "="), (Value, "'b")]);
+        assert_eq!(tokens("é"), [(Key, "é")]);
+        assert_eq!(tokens("  "), []);
+    }
+}
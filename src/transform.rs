@@ -0,0 +1,137 @@
+use crate::validation::validate_key;
+use crate::{Expression, Expressions, ValidationError};
+
+impl Expression {
+    /// Mutable access to the key the expression applies to
+    fn key_mut(&mut self) -> &mut String {
+        match self {
+            Expression::In(key, _)
+            | Expression::NotIn(key, _)
+            | Expression::Equal(key, _)
+            | Expression::NotEqual(key, _)
+            | Expression::Exists(key)
+            | Expression::DoesNotExist(key)
+            | Expression::GreaterThan(key, _)
+            | Expression::LessThan(key, _) => key,
+            #[cfg(feature = "regex")]
+            Expression::Matches(key, _) | Expression::NotMatches(key, _) => key,
+            #[cfg(feature = "extended")]
+            Expression::Glob(key, _) => key,
+        }
+    }
+}
+
+impl Expressions {
+    /// Rewrite the key of every requirement
+    pub fn map_keys(&self, mut f: impl FnMut(&str) -> String) -> Expressions {
+        self.transform(|mut expression| {
+            *expression.key_mut() = f(expression.key());
+            Some(expression)
+        })
+    }
+
+    /// Rewrite the values of set based and equality requirements, given the
+    /// key and the value. Bounds and patterns are left as they are.
+    pub fn map_values(&self, mut f: impl FnMut(&str, &str) -> String) -> Expressions {
+        self.transform(|expression| {
+            Some(match expression {
+                Expression::In(key, values) => {
+                    let values = values.iter().map(|value| f(&key, value)).collect();
+                    Expression::In(key, values)
+                }
+                Expression::NotIn(key, values) => {
+                    let values = values.iter().map(|value| f(&key, value)).collect();
+                    Expression::NotIn(key, values)
+                }
+                Expression::Equal(key, value) => {
+                    let value = f(&key, &value);
+                    Expression::Equal(key, value)
+                }
+                Expression::NotEqual(key, value) => {
+                    let value = f(&key, &value);
+                    Expression::NotEqual(key, value)
+                }
+                expression => expression,
+            })
+        })
+    }
+
+    /// Rewrite every requirement, dropping those the closure returns `None`
+    /// for
+    pub fn transform(&self, f: impl FnMut(Expression) -> Option<Expression>) -> Expressions {
+        self.iter().cloned().filter_map(f).collect()
+    }
+
+    /// Scope unqualified keys into the label namespace of the prefix, such as
+    /// `example.com/`. Keys which already have a prefix are kept.
+    ///
+    /// Fails with every key which is not a valid qualified name once prefixed.
+    pub fn with_key_prefix(&self, prefix: &str) -> Result<Expressions, Vec<ValidationError>> {
+        let prefix = prefix.trim_end_matches('/');
+        let mut errors = vec![];
+        let expressions = self.map_keys(|key| match key.contains('/') {
+            true => key.to_owned(),
+            false => {
+                let key = format!("{prefix}/{key}");
+                if let Err(error) = validate_key(&key) {
+                    errors.push(error);
+                }
+                key
+            }
+        });
+        match errors.is_empty() {
+            true => Ok(expressions),
+            false => Err(errors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, Expressions, ValidationError};
+
+    #[test]
+    fn map() {
+        let expressions = Expressions::try_from("app=web,tier in (a,b),!canary,n>1").unwrap();
+        assert_eq!(
+            expressions.map_keys(|key| format!("x.io/{key}")),
+            Expressions::try_from("x.io/app=web,x.io/tier in (a,b),!x.io/canary,x.io/n>1").unwrap()
+        );
+        assert_eq!(
+            expressions.map_values(|key, value| format!("{key}-{value}")),
+            Expressions::try_from("app=app-web,tier in (tier-a,tier-b),!canary,n>1").unwrap()
+        );
+    }
+
+    #[test]
+    fn transform() {
+        let expressions = Expressions::try_from("old=a,app=web,legacy!=b").unwrap();
+        let migrated = expressions.transform(|expression| match expression.key() {
+            "legacy" => None,
+            "old" => Some(Expression::equal("new", "a")),
+            _ => Some(expression),
+        });
+        assert_eq!(migrated, Expressions::try_from("new=a,app=web").unwrap());
+    }
+
+    #[test]
+    fn key_prefix() {
+        let expressions = Expressions::try_from("app=web,x.io/tier in (a),!canary").unwrap();
+        let expected =
+            Expressions::try_from("example.com/app=web,x.io/tier in (a),!example.com/canary")
+                .unwrap();
+        assert_eq!(
+            expressions.with_key_prefix("example.com/"),
+            Ok(expected.clone())
+        );
+        assert_eq!(expressions.with_key_prefix("example.com"), Ok(expected));
+
+        assert_eq!(
+            expressions.with_key_prefix("Example.com/"),
+            Err(vec![
+                ValidationError::InvalidPrefix("Example.com/app".into()),
+                ValidationError::InvalidPrefix("Example.com/canary".into()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use thiserror::Error;
+
+use crate::{Expression, Expressions};
+
+/// Longest name part of a key, and longest value
+const MAX_NAME_LENGTH: usize = 63;
+
+/// Longest DNS subdomain prefix of a key
+const MAX_PREFIX_LENGTH: usize = 253;
+
+/// Indicates a requirement the Kubernetes API server would reject
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    #[error("prefix of key '{0}' must be a lowercase DNS subdomain")]
+    InvalidPrefix(String),
+
+    #[error("prefix of key '{0}' must be no more than 253 characters")]
+    PrefixTooLong(String),
+
+    #[error(
+        "name of key '{0}' must consist of alphanumerics, '-', '_' or '.', starting and ending with an alphanumeric"
+    )]
+    InvalidName(String),
+
+    #[error("name of key '{0}' must be no more than 63 characters")]
+    NameTooLong(String),
+
+    #[error(
+        "value '{1}' of key '{0}' must consist of alphanumerics, '-', '_' or '.', starting and ending with an alphanumeric"
+    )]
+    InvalidValue(String, String),
+
+    #[error("value '{1}' of key '{0}' must be no more than 63 characters")]
+    ValueTooLong(String, String),
+
+    #[error("set based requirement on key '{0}' must have values")]
+    EmptyValues(String),
+
+    #[error("requirement is not supported by Kubernetes: '{0}'")]
+    Unsupported(Box<Expression>),
+}
+
+impl Expression {
+    /// Check the requirement against the rules the Kubernetes API server
+    /// applies to label selectors
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let key = self.key();
+        validate_key(key)?;
+        let values = match self {
+            Expression::In(_, values) | Expression::NotIn(_, values) if values.is_empty() => {
+                return Err(ValidationError::EmptyValues(key.into()));
+            }
+            Expression::In(_, values) | Expression::NotIn(_, values) => {
+                values.iter().collect::<Vec<_>>()
+            }
+            Expression::Equal(_, value) | Expression::NotEqual(_, value) => vec![value],
+            Expression::Exists(_)
+            | Expression::DoesNotExist(_)
+            | Expression::GreaterThan(..)
+            | Expression::LessThan(..) => vec![],
+            #[cfg(any(feature = "regex", feature = "extended"))]
+            expression => return Err(ValidationError::Unsupported(expression.clone().into())),
+        };
+        for value in values {
+            if value.len() > MAX_NAME_LENGTH {
+                return Err(ValidationError::ValueTooLong(key.into(), value.clone()));
+            }
+            if !value.is_empty() && !is_name(value) {
+                return Err(ValidationError::InvalidValue(key.into(), value.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Expressions {
+    /// Check every requirement against the rules the Kubernetes API server
+    /// applies to label selectors, reporting all violations
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let errors: Vec<_> = self.iter().filter_map(|e| e.validate().err()).collect();
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+/// Check a qualified name, with an optional DNS subdomain prefix
+pub(crate) fn validate_key(key: &str) -> Result<(), ValidationError> {
+    let name = match key.split_once('/') {
+        Some((prefix, name)) => {
+            if prefix.len() > MAX_PREFIX_LENGTH {
+                return Err(ValidationError::PrefixTooLong(key.into()));
+            }
+            if !is_subdomain(prefix) {
+                return Err(ValidationError::InvalidPrefix(key.into()));
+            }
+            name
+        }
+        None => key,
+    };
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(ValidationError::NameTooLong(key.into()));
+    }
+    if !is_name(name) {
+        return Err(ValidationError::InvalidName(key.into()));
+    }
+    Ok(())
+}
+
+/// Alphanumerics, `-`, `_` and `.`, starting and ending with an alphanumeric
+fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Dot separated DNS-1123 labels of lowercase alphanumerics and `-`
+fn is_subdomain(prefix: &str) -> bool {
+    prefix.split('.').all(|label| {
+        label.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+            && label.ends_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+            && label
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, Expressions, ValidationError};
+
+    #[test]
+    fn valid() {
+        let expressions =
+            Expressions::try_from("app.kubernetes.io/name=web,env in (prod,a.b_c-d),tier=,!x")
+                .unwrap();
+        assert_eq!(expressions.validate(), Ok(()));
+        let long = format!("{}/{}", "a".repeat(253), "b".repeat(63));
+        assert_eq!(Expression::exists(long).validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid() {
+        let long = "a".repeat(64);
+        for (expression, error) in [
+            (
+                Expression::exists("Example.com/a"),
+                ValidationError::InvalidPrefix("Example.com/a".into()),
+            ),
+            (
+                Expression::exists("a..b/c"),
+                ValidationError::InvalidPrefix("a..b/c".into()),
+            ),
+            (
+                Expression::exists(format!("{}/a", "a".repeat(254))),
+                ValidationError::PrefixTooLong(format!("{}/a", "a".repeat(254))),
+            ),
+            (
+                Expression::exists("-a"),
+                ValidationError::InvalidName("-a".into()),
+            ),
+            (
+                Expression::exists("a/b/c"),
+                ValidationError::InvalidName("a/b/c".into()),
+            ),
+            (
+                Expression::exists("a/"),
+                ValidationError::InvalidName("a/".into()),
+            ),
+            (
+                Expression::exists(&long),
+                ValidationError::NameTooLong(long.clone()),
+            ),
+            (
+                Expression::equal("a", "b."),
+                ValidationError::InvalidValue("a".into(), "b.".into()),
+            ),
+            (
+                Expression::not_in("a", [&long]),
+                ValidationError::ValueTooLong("a".into(), long.clone()),
+            ),
+            (
+                Expression::r#in("a", Vec::<String>::new()),
+                ValidationError::EmptyValues("a".into()),
+            ),
+        ] {
+            assert_eq!(expression.validate(), Err(error), "{expression}");
+        }
+
+        let expressions = Expressions::try_from("-a,b=c,d=e-").unwrap();
+        assert_eq!(expressions.validate().unwrap_err().len(), 2);
+    }
+}
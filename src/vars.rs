@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use logos::Span;
+
+use crate::{Expressions, ParseError};
+
+/// Placeholders replaced in a selector, as positions in the substituted
+/// selector and in the input
+struct Substitutions(Vec<(Range<usize>, Range<usize>)>);
+
+impl Substitutions {
+    /// Position in the input of a position in the substituted selector.
+    /// Positions within a substituted value map to the placeholder.
+    fn map(&self, offset: usize, end: bool) -> usize {
+        let Some((output, input)) = self
+            .0
+            .iter()
+            .rev()
+            .find(|(output, _)| output.start <= offset)
+        else {
+            return offset;
+        };
+        match offset < output.end {
+            true if end => input.end,
+            true => input.start,
+            false => input.end + offset - output.end,
+        }
+    }
+
+    fn span(&self, span: Span) -> Span {
+        self.map(span.start, false)..self.map(span.end, true)
+    }
+}
+
+impl Expressions {
+    /// Parse a selector after replacing `${NAME}` placeholders with the
+    /// values of the variables, so templates can embed environment specific
+    /// values.
+    ///
+    /// Fails with every placeholder naming an undefined variable, or
+    /// otherwise every invalid requirement, with spans in the input.
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use unselector::Expressions;
+    ///
+    /// let vars = BTreeMap::from([("ENV".to_owned(), "prod".to_owned())]);
+    /// let expressions = Expressions::parse_with_vars("app=web,env=${ENV}", &vars).unwrap();
+    /// assert_eq!(expressions.to_string(), "app=web,env=prod");
+    /// ```
+    pub fn parse_with_vars(
+        input: &str,
+        vars: &BTreeMap<String, String>,
+    ) -> Result<Self, Vec<ParseError>> {
+        let mut selector = String::with_capacity(input.len());
+        let (mut substitutions, mut unresolved) = (vec![], vec![]);
+        let mut rest = 0;
+        while let Some(start) = input[rest..].find("${").map(|i| rest + i) {
+            let Some(end) = input[start..].find('}').map(|i| start + i + 1) else {
+                break;
+            };
+            selector.push_str(&input[rest..start]);
+            let name = &input[start + 2..end - 1];
+            match vars.get(name) {
+                Some(value) => {
+                    let output = selector.len()..selector.len() + value.len();
+                    substitutions.push((output, start..end));
+                    selector.push_str(value);
+                }
+                None => unresolved.push(ParseError::UnresolvedVariable(
+                    input[start..end].to_owned(),
+                    start..end,
+                )),
+            }
+            rest = end;
+        }
+        selector.push_str(&input[rest..]);
+
+        if !unresolved.is_empty() {
+            return Err(unresolved);
+        }
+        let substitutions = Substitutions(substitutions);
+        Expressions::try_from_all(&selector).map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|error| error.map_span(|span| substitutions.span(span)))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{Expressions, ParseError};
+
+    fn vars() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("ENV".to_owned(), "prod".to_owned()),
+            ("TIERS".to_owned(), "web,api".to_owned()),
+            ("BAD".to_owned(), "a*b".to_owned()),
+        ])
+    }
+
+    #[test]
+    fn substitution() {
+        assert_eq!(
+            Expressions::parse_with_vars("env=${ENV},tier in (${TIERS})", &vars()),
+            Ok(Expressions::try_from("env=prod,tier in (web,api)").unwrap())
+        );
+        assert_eq!(
+            Expressions::parse_with_vars("app=web,x=${", &vars()).unwrap_err()[0].code(),
+            "string_parse"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Expressions::parse_with_vars("env=${ENV},a=${A},b=${B}", &vars()),
+            Err(vec![
+                ParseError::UnresolvedVariable("${A}".into(), 13..17),
+                ParseError::UnresolvedVariable("${B}".into(), 20..24),
+            ])
+        );
+
+        let input = "env in (${BAD}),app=";
+        let errors = Expressions::parse_with_vars(input, &vars()).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(&input[errors[0].span()], "(${BAD})");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{Expression, Expressions};
+
+/// Hooks called while walking expressions, see [`Expressions::walk`].
+///
+/// Every hook does nothing by default, apart from
+/// [`visit_requirement`](Self::visit_requirement) walking into the key and
+/// values of the requirement.
+///
+/// ```
+/// use unselector::{Expressions, ExpressionVisitor};
+///
+/// #[derive(Default)]
+/// struct Keys(Vec<String>);
+///
+/// impl ExpressionVisitor for Keys {
+///     fn visit_key(&mut self, key: &str) {
+///         self.0.push(key.to_owned());
+///     }
+/// }
+///
+/// let mut keys = Keys::default();
+/// Expressions::try_from("app=web,!canary")?.walk(&mut keys);
+/// assert_eq!(keys.0, ["app", "canary"]);
+/// # Ok::<(), unselector::ParseError>(())
+/// ```
+pub trait ExpressionVisitor {
+    /// Called for every requirement, in order
+    fn visit_requirement(&mut self, expression: &Expression) {
+        walk_requirement(self, expression);
+    }
+
+    /// Called with the key of every requirement
+    fn visit_key(&mut self, key: &str) {
+        let _ = key;
+    }
+
+    /// Called with the values a requirement compares its key to, including
+    /// bounds and patterns, unless it only checks for existence
+    fn visit_values(&mut self, key: &str, values: &[&str]) {
+        let _ = (key, values);
+    }
+}
+
+/// Visit the key and values of the requirement, as
+/// [`ExpressionVisitor::visit_requirement`] does by default
+pub fn walk_requirement<V: ExpressionVisitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    let key = expression.key();
+    visitor.visit_key(key);
+    match expression {
+        Expression::In(_, values) | Expression::NotIn(_, values) => {
+            let values: Vec<_> = values.iter().map(String::as_str).collect();
+            visitor.visit_values(key, &values);
+        }
+        Expression::Equal(_, value)
+        | Expression::NotEqual(_, value)
+        | Expression::GreaterThan(_, value)
+        | Expression::LessThan(_, value) => visitor.visit_values(key, &[value]),
+        Expression::Exists(_) | Expression::DoesNotExist(_) => {}
+        #[cfg(feature = "regex")]
+        Expression::Matches(_, pattern) | Expression::NotMatches(_, pattern) => {
+            visitor.visit_values(key, &[pattern.as_str()])
+        }
+        #[cfg(feature = "extended")]
+        Expression::Glob(_, pattern) => visitor.visit_values(key, &[pattern.as_str()]),
+    }
+}
+
+impl Expressions {
+    /// Walk every requirement with the visitor
+    pub fn walk(&self, visitor: &mut impl ExpressionVisitor) {
+        for expression in self {
+            visitor.visit_requirement(expression);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Expression, ExpressionVisitor, Expressions};
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl ExpressionVisitor for Recorder {
+        fn visit_key(&mut self, key: &str) {
+            self.0.push(format!("key {key}"));
+        }
+
+        fn visit_values(&mut self, key: &str, values: &[&str]) {
+            self.0.push(format!("values {key} {}", values.join("|")));
+        }
+    }
+
+    #[test]
+    fn walk() {
+        let mut recorder = Recorder::default();
+        Expressions::try_from("app=web,env notin (a,b),!canary,n>1")
+            .unwrap()
+            .walk(&mut recorder);
+        assert_eq!(
+            recorder.0,
+            [
+                "key app",
+                "values app web",
+                "key env",
+                "values env a|b",
+                "key canary",
+                "key n",
+                "values n 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn override_requirement() {
+        struct Negations(usize);
+
+        impl ExpressionVisitor for Negations {
+            fn visit_requirement(&mut self, expression: &Expression) {
+                if matches!(
+                    expression,
+                    Expression::NotIn(..) | Expression::NotEqual(..) | Expression::DoesNotExist(_)
+                ) {
+                    self.0 += 1;
+                }
+            }
+        }
+
+        let mut negations = Negations(0);
+        Expressions::try_from("a!=b,c,!d")
+            .unwrap()
+            .walk(&mut negations);
+        assert_eq!(negations.0, 2);
+    }
+}